@@ -0,0 +1,47 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Credentials for private Maven repositories.
+
+use reqwest::RequestBuilder;
+
+/// Credentials sent with requests to URLs starting with `url_prefix`.
+#[derive(Clone)]
+pub struct RepositoryCredentials {
+    pub url_prefix: String,
+    pub secret: Secret,
+}
+
+#[derive(Clone)]
+pub enum Secret {
+    Basic { username: String, password: String },
+    Bearer(String),
+}
+
+/// Adds the credentials of the repository `url` is in to a request to it, those with the longest matching
+/// prefix if there are several. Requests to other URLs are left alone.
+pub fn authorize(credentials: &[RepositoryCredentials], request: RequestBuilder, url: &str) -> RequestBuilder {
+    let repository = credentials.iter()
+        .filter(|repository| url.starts_with(&repository.url_prefix))
+        .max_by_key(|repository| repository.url_prefix.len());
+    match repository.map(|repository| &repository.secret) {
+        Some(Secret::Basic { username, password }) => request.basic_auth(username, Some(password)),
+        Some(Secret::Bearer(token)) => request.bearer_auth(token),
+        None => request,
+    }
+}
@@ -0,0 +1,498 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Bundles: a document and every artifact it references in a single tar archive, to carry them into a network that
+//! can reach neither Ely.by nor wherever the document is published.
+//!
+//! A bundle holds the document as it was published, every artifact at `artifacts/<sha1>/<file name>` and a
+//! `manifest.json` recording where each of them was downloaded from:
+//!
+//! ```json
+//! {
+//!   "formatVersion": 1,
+//!   "created": "2025-06-01T12:00:00Z",
+//!   "document": "metadata.json",
+//!   "artifacts": [{ "url": "https://…/authlib-5.17.31-ely.2.jar", "path": "artifacts/…", "sha1": "…", "size": 1024 }]
+//! }
+//! ```
+//!
+//! Importing a bundle points the URLs of the document to where its artifacts will be served from instead, which
+//! invalidates any signature of the original document.
+
+use crate::concurrency::{AdaptiveLimiter, DownloadBudget};
+use crate::document;
+use crate::download::{self, Connection, ResponseLimits, RetryPolicy};
+use crate::error::GeneratorError;
+use crate::hashing::Hasher;
+use crate::model::MetadataFile;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// Layout of the manifest written by this version
+const FORMAT_VERSION: u32 = 1;
+const MANIFEST: &str = "manifest.json";
+const DOCUMENT: &str = "metadata.json";
+/// Directory of the unpack directory files are read out of an archive into, before they're checked
+const STAGING: &str = ".bundle.tmp";
+/// Size of a tar header, and of the blocks contents are padded to
+const BLOCK: usize = 512;
+/// Bytes of a file read into memory at a time while archiving or unpacking it
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// `manifest.json`, which says where the artifacts of a bundle came from.
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    #[serde(rename = "formatVersion")]
+    format_version: u32,
+    created: String,
+    document: String,
+    artifacts: Vec<BundledArtifact>,
+}
+
+impl Manifest {
+    fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("manifests are always serializable") + "\n"
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct BundledArtifact {
+    url: String,
+    /// Where the artifact is in the bundle
+    path: String,
+    sha1: String,
+    size: usize,
+}
+
+/// A file of an archive, read out into the staging directory.
+struct Staged {
+    path: PathBuf,
+    sha1: String,
+    size: u64,
+}
+
+/// Downloads every artifact of the document at `location`, checking each against the document, and packages them
+/// with the document into a bundle at `path`. Returns the number of artifacts bundled.
+///
+/// Artifacts are downloaded like those of a run, within the response limits, and kept on disk until they're copied
+/// into the archive.
+pub async fn export(client: &reqwest::Client, location: &str, path: &Path) -> Result<usize, GeneratorError> {
+    let contents = document::read(client, location).await?;
+    let document = MetadataFile::parse(&contents)
+        .map_err(|source| GeneratorError::Document { location: location.to_string(), source })?;
+
+    let limiter = AdaptiveLimiter::new(1, None);
+    let budget = DownloadBudget::new(None);
+    let retry = RetryPolicy { attempts: 2, backoff: Duration::from_secs(1), jitter: true };
+    let connection = Connection {
+        client,
+        credentials: &[],
+        limiter: &limiter,
+        budget: &budget,
+        cache: None,
+        retry: &retry,
+        local: None,
+        limits: ResponseLimits::default(),
+    };
+
+    let mut artifacts = Vec::new();
+    let mut bundled: Vec<BundledArtifact> = Vec::new();
+    for (label, url, digests, size) in document::artifacts(&document) {
+        // Channels and extras may point to the same artifact as an entry
+        if bundled.iter().any(|artifact| artifact.url == url) {
+            continue
+        }
+
+        tracing::info!("Downloading {}", label);
+        let downloaded = download::download_artifact(&connection, url, &[], true).await
+            .map_err(|source| GeneratorError::Download { what: label.clone(), source })?;
+        let sha1 = downloaded.digests.sha1;
+        if sha1 != digests.sha1 {
+            return Err(GeneratorError::Unbundleable(format!("{}, its SHA-1 is {} but the document says {}", label, sha1, digests.sha1)))
+        }
+        if downloaded.size != size {
+            return Err(GeneratorError::Unbundleable(format!("{}, its size is {} but the document says {}", label, downloaded.size, size)))
+        }
+
+        let entry = format!("artifacts/{}/{}", sha1, file_name(url));
+        bundled.push(BundledArtifact { url: url.to_string(), path: entry.clone(), sha1, size });
+        artifacts.push((entry, size, downloaded.contents.expect("contents are kept when asked to")));
+    }
+
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        created: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        document: DOCUMENT.to_string(),
+        artifacts: bundled,
+    };
+    let count = artifacts.len();
+    let temporary = PathBuf::from(format!("{}.tmp", path.display()));
+    let write = || -> std::io::Result<()> {
+        let mut archive = BufWriter::new(std::fs::File::create(&temporary)?);
+        let manifest = manifest.to_json();
+        append(&mut archive, MANIFEST, manifest.len() as u64, &mut manifest.as_bytes())?;
+        append(&mut archive, DOCUMENT, contents.len() as u64, &mut contents.as_slice())?;
+        for (entry, size, contents) in &artifacts {
+            append(&mut archive, entry, *size as u64, &mut std::fs::File::open(contents.path())?)?;
+        }
+        // The end of an archive is marked by two empty blocks
+        archive.write_all(&[0; 2 * BLOCK])?;
+        archive.into_inner().map_err(std::io::IntoInnerError::into_error)?.sync_all()?;
+        std::fs::rename(&temporary, path)
+    };
+    write().map_err(|source| {
+        let _ = std::fs::remove_file(&temporary);
+        GeneratorError::Io { action: "write bundle", path: path.to_path_buf(), source }
+    })?;
+
+    Ok(count)
+}
+
+/// Unpacks a bundle into `directory`, checking every artifact against the manifest, and writes the document with
+/// the URLs of its artifacts pointing below `base_url`, where the directory is meant to be served. Returns the
+/// number of artifacts unpacked.
+///
+/// Files are read out of the archive a chunk at a time into a staging directory, and only moved into place once
+/// the whole bundle checks out.
+pub fn import(path: &Path, directory: &Path, base_url: &str) -> Result<usize, GeneratorError> {
+    let staging = directory.join(STAGING);
+    let unpacked = unpack(path, directory, &staging, base_url);
+    let _ = std::fs::remove_dir_all(&staging);
+    unpacked
+}
+
+fn unpack(path: &Path, directory: &Path, staging: &Path, base_url: &str) -> Result<usize, GeneratorError> {
+    let invalid = |why: String| GeneratorError::InvalidBundle { path: path.to_path_buf(), why };
+    let limits = ResponseLimits::default();
+    let _ = std::fs::remove_dir_all(staging);
+    std::fs::create_dir_all(staging)
+        .map_err(|source| GeneratorError::Io { action: "create", path: staging.to_path_buf(), source })?;
+    let mut files = read_archive(path, staging, limits.max_artifact_size)
+        .map_err(|source| GeneratorError::Io { action: "read bundle", path: path.to_path_buf(), source })?
+        .map_err(invalid)?;
+    // The manifest and the document are read whole, so they're held to the limit of documents
+    let mut read_document = |name: &str| {
+        let file = files.remove(name).ok_or_else(|| invalid(format!("it has no {}", name)))?;
+        if file.size > limits.max_document_size {
+            return Err(invalid(format!("its {} is larger than the limit of {} bytes", name, limits.max_document_size)))
+        }
+        std::fs::read(&file.path).map_err(|source| GeneratorError::Io { action: "read", path: file.path, source })
+    };
+
+    let manifest = read_document(MANIFEST)?;
+    let manifest: serde_json::Value = serde_json::from_slice(&manifest)
+        .map_err(|_| invalid(format!("its {} isn't JSON", MANIFEST)))?;
+    if manifest["formatVersion"].as_u64() != Some(u64::from(FORMAT_VERSION)) {
+        return Err(invalid(format!("its manifest has format version {}, only {} is supported", manifest["formatVersion"], FORMAT_VERSION)))
+    }
+    let manifest = Manifest::deserialize(manifest).map_err(|why| invalid(format!("its {} is malformed: {}", MANIFEST, why)))?;
+    let name = manifest.document.as_str();
+    if safe_path(name).is_none() {
+        return Err(invalid("its manifest names no document".to_string()))
+    }
+    let contents = read_document(name)?;
+    let mut document: serde_json::Value = serde_json::from_slice(&contents)
+        .map_err(|_| invalid(format!("its {} isn't JSON", name)))?;
+
+    let mut unpacked = Vec::new();
+    let mut urls = HashMap::new();
+    for BundledArtifact { url, path: entry, sha1, .. } in &manifest.artifacts {
+        let Some(relative) = safe_path(entry) else {
+            return Err(invalid(format!("its manifest lists {} outside of the bundle", entry)))
+        };
+        let file = files.remove(entry).ok_or_else(|| invalid(format!("it has no {}", entry)))?;
+        if !file.sha1.eq_ignore_ascii_case(sha1) {
+            return Err(invalid(format!("the SHA-1 of {} is {} but its manifest says {}", entry, file.sha1, sha1)))
+        }
+
+        urls.insert(url.clone(), format!("{}/{}", base_url.trim_end_matches('/'), entry));
+        unpacked.push((relative, file.path));
+    }
+    rewrite(&mut document, &urls);
+
+    let count = unpacked.len();
+    let place = |file: &Path, put: &dyn Fn(&Path) -> std::io::Result<()>| {
+        let target = directory.join(file);
+        target.parent().map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| put(&target))
+            .map_err(|source| GeneratorError::Io { action: "write", path: target, source })
+    };
+    for (relative, staged) in unpacked {
+        place(&relative, &|target| std::fs::rename(&staged, target))?;
+    }
+    place(Path::new(MANIFEST), &|target| std::fs::write(target, manifest.to_json()))?;
+    let document = serde_json::to_string_pretty(&document).expect("JSON values are always serializable");
+    place(Path::new(name), &|target| std::fs::write(target, &document))?;
+
+    Ok(count)
+}
+
+/// Replaces every string of `value` that is a key of `urls`, keeping the order of the fields.
+fn rewrite(value: &mut serde_json::Value, urls: &HashMap<String, String>) {
+    match value {
+        serde_json::Value::String(string) => {
+            if let Some(url) = urls.get(string.as_str()) {
+                *string = url.clone();
+            }
+        }
+        serde_json::Value::Array(members) => members.iter_mut().for_each(|member| rewrite(member, urls)),
+        serde_json::Value::Object(members) => members.values_mut().for_each(|member| rewrite(member, urls)),
+        _ => {}
+    }
+}
+
+/// The last segment of a URL's path, if it only has characters safe in a file name everywhere.
+fn file_name(url: &str) -> &str {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let safe = name.chars().all(|c| c.is_ascii_alphanumeric() || "._-+".contains(c));
+    if safe && !name.is_empty() && !name.starts_with('.') && name.len() <= 100 { name } else { "artifact" }
+}
+
+/// `path` relative to where a bundle is unpacked, unless it would end up anywhere else.
+fn safe_path(path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path);
+    let normal = !path.contains('\\') && relative.components().all(|component| matches!(component, Component::Normal(_)));
+    (normal && !path.is_empty()).then(|| relative.to_path_buf())
+}
+
+/// Writes a file of `size` bytes to a tar archive in the ustar format, readable by any tar.
+fn append(archive: &mut impl Write, name: &str, size: u64, contents: &mut impl Read) -> std::io::Result<()> {
+    let mut header = [0u8; BLOCK];
+    // Names longer than the name field are split at a slash, the directory going into the prefix field
+    let (prefix, name) = match name.rsplit_once('/') {
+        Some((prefix, file)) if name.len() > 100 => (prefix, file),
+        _ => ("", name),
+    };
+    if name.len() > 100 || prefix.len() > 155 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("{} is too long to archive", name)))
+    }
+
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[100..108].copy_from_slice(b"0000644\0");
+    header[108..116].copy_from_slice(b"0000000\0");
+    header[116..124].copy_from_slice(b"0000000\0");
+    header[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+    header[136..148].copy_from_slice(format!("{:011o}\0", chrono::Utc::now().timestamp().max(0)).as_bytes());
+    header[156] = b'0';
+    header[257..265].copy_from_slice(b"ustar\x0000");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+    // The checksum is computed with its own field blank
+    header[148..156].fill(b' ');
+    let checksum: u32 = header.iter().map(|&byte| u32::from(byte)).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+
+    archive.write_all(&header)?;
+    if std::io::copy(&mut contents.take(size), archive)? != size {
+        return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, format!("{} is shorter than {} bytes", name, size)))
+    }
+    archive.write_all(&vec![0; padding(size)])
+}
+
+/// Zeroes after `size` bytes of contents, up to the end of their last block.
+fn padding(size: u64) -> usize {
+    (size.next_multiple_of(BLOCK as u64) - size) as usize
+}
+
+/// Reads every file of a tar archive out into `staging`, hashing it on the way, by name. The inner result is why the
+/// archive isn't a valid one: sizes are checked against the rest of the archive and `max_size` before anything is
+/// read, and a file that isn't in a header's place isn't found.
+fn read_archive(path: &Path, staging: &Path, max_size: u64) -> std::io::Result<Result<HashMap<String, Staged>, String>> {
+    let file = std::fs::File::open(path)?;
+    let mut remaining = file.metadata()?.len();
+    let mut archive = std::io::BufReader::new(file);
+    let mut files = HashMap::new();
+    let mut header = [0u8; BLOCK];
+    let mut chunk = vec![0; CHUNK_SIZE];
+    for index in 0.. {
+        match archive.read_exact(&mut header) {
+            Ok(()) => remaining = remaining.saturating_sub(BLOCK as u64),
+            Err(why) if why.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(Err("it ends in the middle of a header".to_string())),
+            Err(why) => return Err(why),
+        }
+        if header.iter().all(|&byte| byte == 0) {
+            break
+        }
+
+        let field = |range: std::ops::Range<usize>| {
+            let field = &header[range];
+            String::from_utf8_lossy(&field[..field.iter().position(|&byte| byte == 0).unwrap_or(field.len())]).trim().to_string()
+        };
+        let expected = u32::from_str_radix(&field(148..156), 8).ok();
+        let computed: u32 = header.iter().enumerate()
+            .map(|(index, &byte)| if (148..156).contains(&index) { u32::from(b' ') } else { u32::from(byte) })
+            .sum();
+        if expected != Some(computed) {
+            return Ok(Err("a header is corrupt".to_string()))
+        }
+        let Ok(size) = u64::from_str_radix(&field(124..136), 8) else {
+            return Ok(Err("a header has no valid size".to_string()))
+        };
+        let name = match field(345..500) {
+            prefix if !prefix.is_empty() && &header[257..262] == b"ustar" => format!("{}/{}", prefix, field(0..100)),
+            _ => field(0..100),
+        };
+        // Whatever the header says, there's no more to read than what's left of the archive
+        let stored = size + padding(size) as u64;
+        if stored > remaining {
+            return Ok(Err(format!("it ends in the middle of {}", name)))
+        }
+        if size > max_size {
+            return Ok(Err(format!("{} is larger than the limit of {} bytes", name, max_size)))
+        }
+        remaining -= stored;
+
+        // Directories, links and extended headers aren't written to bundles
+        if !matches!(header[156], b'0' | 0) {
+            std::io::copy(&mut (&mut archive).take(stored), &mut std::io::sink())?;
+            continue
+        }
+        // Named by position, what's in the header is only trusted once the manifest vouches for it
+        let staged = staging.join(index.to_string());
+        let mut output = BufWriter::new(std::fs::File::create(&staged)?);
+        let mut hasher = Hasher::new(&[]);
+        let mut contents = (&mut archive).take(size);
+        loop {
+            match contents.read(&mut chunk)? {
+                0 => break,
+                read => {
+                    hasher.update(&chunk[..read]);
+                    output.write_all(&chunk[..read])?;
+                }
+            }
+        }
+        output.flush()?;
+        std::io::copy(&mut (&mut archive).take(padding(size) as u64), &mut std::io::sink())?;
+        files.insert(name, Staged { path: staged, sha1: hasher.finish().sha1, size });
+    }
+
+    Ok(Ok(files))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A directory of its own for a test, empty.
+    fn scratch(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("{}-bundle-{}-{}", env!("CARGO_PKG_NAME"), name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        for (name, contents) in entries {
+            append(&mut archive, name, contents.len() as u64, &mut &contents[..]).unwrap();
+        }
+        archive.extend_from_slice(&[0; 2 * BLOCK]);
+        archive
+    }
+
+    fn manifest(artifacts: &[(&str, &[u8])]) -> String {
+        Manifest {
+            format_version: FORMAT_VERSION,
+            created: "2025-06-01T12:00:00Z".to_string(),
+            document: DOCUMENT.to_string(),
+            artifacts: artifacts.iter().map(|(path, contents)| BundledArtifact {
+                url: format!("https://repo.example/{}", path.rsplit('/').next().unwrap()),
+                path: path.to_string(),
+                sha1: crate::hashing::Digests::compute(contents, &[]).sha1,
+                size: contents.len(),
+            }).collect(),
+        }.to_json()
+    }
+
+    /// Imports an archive, returning why it's invalid if it is.
+    fn import_archive(directory: &Path, archive: &[u8]) -> Result<usize, String> {
+        let path = directory.join("bundle.tar");
+        std::fs::write(&path, archive).unwrap();
+        import(&path, &directory.join("unpacked"), "https://mirror.example/").map_err(|why| match why {
+            GeneratorError::InvalidBundle { why, .. } => why,
+            why => panic!("unexpected error: {}", why),
+        })
+    }
+
+    /// Gives the first header of an archive another size, with a checksum to match.
+    fn forge_size(archive: &mut [u8], size: u64) {
+        archive[124..136].copy_from_slice(format!("{:011o}\0", size).as_bytes());
+        archive[148..156].fill(b' ');
+        let checksum: u32 = archive[..BLOCK].iter().map(|&byte| u32::from(byte)).sum();
+        archive[148..156].copy_from_slice(format!("{:06o}\0 ", checksum).as_bytes());
+    }
+
+    #[test]
+    fn keeps_paths_inside_the_bundle() {
+        assert_eq!(safe_path("artifacts/0a4d/authlib.jar"), Some(PathBuf::from("artifacts/0a4d/authlib.jar")));
+        assert_eq!(safe_path("metadata.json"), Some(PathBuf::from("metadata.json")));
+        for path in ["", "../authlib.jar", "artifacts/../../authlib.jar", "/etc/passwd", "./metadata.json", "artifacts\\..\\x"] {
+            assert_eq!(safe_path(path), None, "{}", path);
+        }
+    }
+
+    #[test]
+    fn round_trips_a_bundle() {
+        let directory = scratch("round-trip");
+        let jar: &[u8] = b"PK\x03\x04 not much of a jar";
+        let entry = "artifacts/0a4d/authlib.jar";
+        let document = br#"{"formatVersion":2,"url":"https://repo.example/authlib.jar","other":"https://repo.example/other.jar"}"#;
+        let manifest = manifest(&[(entry, jar)]);
+
+        let count = import_archive(&directory, &archive(&[(MANIFEST, manifest.as_bytes()), (DOCUMENT, document), (entry, jar)]));
+        assert_eq!(count, Ok(1));
+        let unpacked = directory.join("unpacked");
+        assert_eq!(std::fs::read(unpacked.join(entry)).unwrap(), jar);
+        let imported: serde_json::Value = serde_json::from_slice(&std::fs::read(unpacked.join(DOCUMENT)).unwrap()).unwrap();
+        assert_eq!(imported["url"], "https://mirror.example/artifacts/0a4d/authlib.jar");
+        assert_eq!(imported["other"], "https://repo.example/other.jar");
+        assert!(!unpacked.join(STAGING).exists());
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn refuses_paths_outside_the_bundle() {
+        let directory = scratch("escape");
+        let jar: &[u8] = b"escaped";
+        let absolute = directory.join("absolute.jar").display().to_string();
+        for entry in ["../escaped.jar", "artifacts/../../escaped.jar", absolute.as_str()] {
+            let manifest = manifest(&[(entry, jar)]);
+            let why = import_archive(&directory, &archive(&[(MANIFEST, manifest.as_bytes()), (DOCUMENT, b"{}"), (entry, jar)]));
+            assert_eq!(why, Err(format!("its manifest lists {} outside of the bundle", entry)));
+        }
+        assert!(!directory.join("escaped.jar").exists() && !directory.join("absolute.jar").exists());
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn checks_sizes_before_reading() {
+        let directory = scratch("oversized");
+        let mut oversized = archive(&[(MANIFEST, b"{}")]);
+        forge_size(&mut oversized, 0o77777777777);
+        assert_eq!(import_archive(&directory, &oversized), Err(format!("it ends in the middle of {}", MANIFEST)));
+
+        let path = directory.join("small.tar");
+        std::fs::write(&path, archive(&[(MANIFEST, b"{\"formatVersion\": 1}")])).unwrap();
+        let why = read_archive(&path, &directory, 8).unwrap().err();
+        assert_eq!(why, Some(format!("{} is larger than the limit of 8 bytes", MANIFEST)));
+        let _ = std::fs::remove_dir_all(&directory);
+    }
+}
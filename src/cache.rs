@@ -0,0 +1,185 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! On-disk cache of HTTP responses, revalidated with conditional requests.
+
+use bytes::Bytes;
+use reqwest::header::{ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Responses stored by URL (`<sha256 of the URL>` with the body, `.json` next to it with the validators).
+/// Responses without an `ETag` or `Last-Modified` header can't be revalidated and aren't stored.
+pub struct HttpCache {
+    root: PathBuf,
+}
+
+/// A response found in the cache.
+pub struct CachedResponse {
+    /// File holding the body
+    pub path: PathBuf,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl CachedResponse {
+    pub fn body(&self) -> Option<Bytes> {
+        std::fs::read(&self.path).ok().map(Bytes::from)
+    }
+
+    /// Makes the request conditional, so the server answers 304 Not Modified if the cached response is still current.
+    pub fn conditional(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+
+    /// The `Last-Modified` header the response was stored with.
+    pub fn last_modified(&self) -> Option<&str> {
+        self.last_modified.as_deref()
+    }
+}
+
+/// What is stored next to a body: the URL it was requested from, and what it is revalidated with.
+#[derive(Serialize, Deserialize)]
+struct Validators {
+    url: String,
+    etag: Option<String>,
+    #[serde(rename = "lastModified")]
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    fn read(path: &Path) -> Option<Validators> {
+        serde_json::from_slice(&std::fs::read(path).ok()?).ok()
+    }
+}
+
+/// A stored response, as listed by `cache stats`.
+pub struct CacheEntry {
+    pub url: String,
+    /// Bytes taken by the body and the validators
+    pub size: u64,
+    /// When the response was last stored or served from the cache
+    pub last_used: SystemTime,
+    path: PathBuf,
+}
+
+impl HttpCache {
+    pub fn open(root: &Path) -> std::io::Result<HttpCache> {
+        std::fs::create_dir_all(root)?;
+        Ok(HttpCache { root: root.to_path_buf() })
+    }
+
+    pub fn get(&self, url: &str) -> Option<CachedResponse> {
+        let path = self.path(url);
+        let validators = Validators::read(&path.with_extension("json"))?;
+        // Different URLs with the same hash are unlikely, but would be a silent corruption
+        if validators.url != url || !path.is_file() {
+            return None
+        }
+        // So that `cache gc` evicts the responses that went unused the longest first
+        let _ = std::fs::File::options().append(true).open(path.with_extension("json"))
+            .and_then(|file| file.set_modified(SystemTime::now()));
+
+        Some(CachedResponse {
+            path,
+            etag: validators.etag,
+            last_modified: validators.last_modified,
+        })
+    }
+
+    pub fn put(&self, url: &str, headers: &HeaderMap, body: &[u8]) {
+        self.store(url, headers, |path| std::fs::write(path, body));
+    }
+
+    /// Caches a response whose body was written to a file, copying it.
+    pub fn put_file(&self, url: &str, headers: &HeaderMap, body: &Path) {
+        self.store(url, headers, |path| std::fs::copy(body, path).map(|_| ()));
+    }
+
+    fn store(&self, url: &str, headers: &HeaderMap, write_body: impl FnOnce(&Path) -> std::io::Result<()>) {
+        let header = |name| headers.get(name).and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok());
+        let (etag, last_modified) = (header(ETAG), header(LAST_MODIFIED));
+        if etag.is_none() && last_modified.is_none() {
+            return
+        }
+
+        let path = self.path(url);
+        let validators = Validators { url: url.to_string(), etag: etag.map(str::to_string), last_modified: last_modified.map(str::to_string) };
+        // The validators are written last, so an interrupted write leaves no entry rather than a truncated one
+        let write = std::fs::remove_file(path.with_extension("json"))
+            .or_else(|why| if why.kind() == std::io::ErrorKind::NotFound { Ok(()) } else { Err(why) })
+            .and_then(|_| write_body(&path))
+            .and_then(|_| std::fs::write(path.with_extension("json"), serde_json::to_vec(&validators).expect("validators are always serializable")));
+        if let Err(why) = write {
+            tracing::warn!("Couldn't cache response of {}: {}", url, why);
+        }
+    }
+
+    /// Every stored response, most recently used first, and the files that belong to none: bodies left behind by
+    /// an interrupted write, or anything else put in the directory.
+    pub fn entries(&self) -> std::io::Result<(Vec<CacheEntry>, Vec<PathBuf>)> {
+        let mut entries = Vec::new();
+        let mut files = Vec::new();
+        for file in std::fs::read_dir(&self.root)? {
+            let path = file?.path();
+            if path.extension().is_some_and(|extension| extension == "json") {
+                continue
+            }
+            let validators = path.with_extension("json");
+            let url = Validators::read(&validators).map(|validators| validators.url);
+            match (url, std::fs::metadata(&path), std::fs::metadata(&validators)) {
+                (Some(url), Ok(body), Ok(metadata)) if body.is_file() => entries.push(CacheEntry {
+                    url,
+                    size: body.len() + metadata.len(),
+                    last_used: metadata.modified()?,
+                    path,
+                }),
+                _ if path.is_file() => files.push(path),
+                _ => {}
+            }
+        }
+        // Validators without a body
+        for file in std::fs::read_dir(&self.root)? {
+            let path = file?.path();
+            if path.extension().is_some_and(|extension| extension == "json") && !path.with_extension("").is_file() {
+                files.push(path);
+            }
+        }
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.last_used));
+        Ok((entries, files))
+    }
+
+    /// Removes a response along with its validators, returning the bytes freed.
+    pub fn remove(&self, entry: &CacheEntry) -> std::io::Result<u64> {
+        std::fs::remove_file(entry.path.with_extension("json"))?;
+        std::fs::remove_file(&entry.path)?;
+        Ok(entry.size)
+    }
+
+    fn path(&self, url: &str) -> PathBuf {
+        self.root.join(hex::encode(Sha256::digest(url.as_bytes())))
+    }
+}
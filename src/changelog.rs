@@ -0,0 +1,60 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Keeping a Markdown changelog of what every run changed in the output.
+
+use crate::document::Difference;
+use crate::model::MetadataFile;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::io::Write;
+use std::path::Path;
+
+/// Appends a section dated `time` listing `differences` and any change of the authlib-injector URL between
+/// `previous` and `document`, creating the changelog if it doesn't exist. Nothing is written if nothing changed.
+pub fn append(path: &Path, time: DateTime<Utc>, previous: &MetadataFile, document: &MetadataFile, differences: &[Difference])
+    -> std::io::Result<()> {
+    let injector_url = |document: &MetadataFile| document.extras.authlib_injector.as_ref().map(|injector| injector.url.clone());
+    let (old_injector, new_injector) = (injector_url(previous), injector_url(document));
+    if differences.is_empty() && old_injector == new_injector {
+        return Ok(())
+    }
+
+    let mut section = format!("## {}\n\n", time.to_rfc3339_opts(SecondsFormat::Secs, true));
+    for difference in differences {
+        let line = match difference {
+            Difference::Added(version) => format!("- Added {}\n", version),
+            Difference::Removed(version) => format!("- Removed {}\n", version),
+            Difference::Changed { version, field, old, new } => format!("- Changed {} {}: {} -> {}\n", version, field, old, new),
+        };
+        section.push_str(&line);
+    }
+    match (old_injector, new_injector) {
+        (Some(old), Some(new)) if old != new => section.push_str(&format!("- authlib-injector: {} -> {}\n", old, new)),
+        (None, Some(new)) => section.push_str(&format!("- Added authlib-injector {}\n", new)),
+        (Some(old), None) => section.push_str(&format!("- Removed authlib-injector {}\n", old)),
+        _ => {}
+    }
+    section.push('\n');
+
+    let new_file = !path.exists();
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    if new_file {
+        file.write_all(b"# Changelog\n\n")?;
+    }
+    file.write_all(section.as_bytes())
+}
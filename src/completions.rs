@@ -0,0 +1,269 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Shell completion scripts, generated from the command line definition so they never fall behind it.
+//!
+//! Every script completes subcommand names, the options of the subcommand being typed along with the global ones,
+//! and the possible values of options that have them. Other values fall back to completing file names.
+
+use clap::{Arg, Command};
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Powershell,
+}
+
+/// The completion script of `command` for `shell`. `command` has to be built, so that global options have been
+/// propagated to its subcommands.
+pub fn generate(shell: Shell, command: &Command) -> String {
+    match shell {
+        Shell::Bash => bash(command),
+        Shell::Zsh => zsh(command),
+        Shell::Fish => fish(command),
+        Shell::Powershell => powershell(command),
+    }
+}
+
+fn bash(command: &Command) -> String {
+    let name = command.get_name();
+    let function = format!("_{}", name.replace('-', "_"));
+    let names: Vec<&str> = subcommands(command).map(Command::get_name).collect();
+
+    let mut script = format!("{}() {{\n    local cur prev subcommand i opts values\n", function);
+    script.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n    subcommand=\"\"\n");
+    script.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n        case \"${COMP_WORDS[i]}\" in\n");
+    script.push_str(&format!("            {}) subcommand=\"${{COMP_WORDS[i]}}\"; break ;;\n", names.join("|")));
+    script.push_str("        esac\n    done\n\n    case \"$prev\" in\n");
+    let mut seen = Vec::new();
+    for arg in std::iter::once(command).chain(subcommands(command)).flat_map(options).filter(|arg| takes_value(arg)) {
+        let flags = flags(arg);
+        if seen.contains(&flags) {
+            continue
+        }
+        let values = possible_values(arg);
+        let reply = if values.is_empty() {
+            "compgen -f -- \"$cur\"".to_string()
+        } else {
+            format!("compgen -W \"{}\" -- \"$cur\"", values.join(" "))
+        };
+        script.push_str(&format!("        {}) COMPREPLY=($({})); return ;;\n", flags.join("|"), reply));
+        seen.push(flags);
+    }
+    script.push_str("    esac\n\n    case \"$subcommand\" in\n");
+    for subcommand in subcommands(command) {
+        let opts: Vec<String> = options(subcommand).flat_map(flags).collect();
+        let values: Vec<String> = subcommand.get_positionals().flat_map(possible_values).collect();
+        script.push_str(&format!("        {}) opts=\"{}\" values=\"{}\" ;;\n", subcommand.get_name(), opts.join(" "), values.join(" ")));
+    }
+    let opts: Vec<String> = names.iter().map(|name| name.to_string()).chain(options(command).flat_map(flags)).collect();
+    script.push_str(&format!("        *) opts=\"{}\" ;;\n    esac\n", opts.join(" ")));
+    script.push_str("    if [[ \"$cur\" == -* || -z \"$subcommand\" && \"$COMP_CWORD\" -eq 1 ]]; then\n");
+    script.push_str("        COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n    elif [[ -n \"$values\" ]]; then\n");
+    script.push_str("        COMPREPLY=($(compgen -W \"$values\" -- \"$cur\"))\n    else\n");
+    script.push_str("        COMPREPLY=($(compgen -f -- \"$cur\"))\n    fi\n}\n\n");
+    script.push_str(&format!("complete -o filenames -F {} {}\n", function, name));
+    script
+}
+
+fn zsh(command: &Command) -> String {
+    let name = command.get_name();
+    let function = format!("_{}", name.replace('-', "_"));
+    let specs = |command: &Command| -> String {
+        options(command).flat_map(|arg| {
+            let help = zsh_escape(&summary(arg.get_help()));
+            let value = match possible_values(arg).as_slice() {
+                _ if !takes_value(arg) => String::new(),
+                [] => format!(":{}:_files", value_name(arg)),
+                values => format!(":{}:({})", value_name(arg), values.join(" ")),
+            };
+            let repeat = if is_repeatable(arg) { "*" } else { "" };
+            flags(arg).into_iter().map(move |flag| format!(" \\\n        '{}{}[{}]{}'", repeat, flag, help, value))
+        })
+        .collect()
+    };
+
+    let mut script = format!("#compdef {}\n\n{}() {{\n", name, function);
+    script.push_str("    local context state state_descr line\n    typeset -A opt_args\n\n");
+    script.push_str(&format!("    _arguments -C{} \\\n        '1: :->command' \\\n        '*:: :->arguments'\n\n", specs(command)));
+    script.push_str("    case $state in\n        command)\n            local -a commands\n            commands=(\n");
+    for subcommand in subcommands(command) {
+        script.push_str(&format!("                '{}:{}'\n", subcommand.get_name(), zsh_escape(&summary(subcommand.get_about()))));
+    }
+    script.push_str("            )\n            _describe command commands\n            _files\n            ;;\n");
+    script.push_str("        arguments)\n            case $line[1] in\n");
+    for subcommand in subcommands(command) {
+        let values: Vec<String> = subcommand.get_positionals().flat_map(possible_values).collect();
+        let positionals = match values.as_slice() {
+            [] => "'*:file:_files'".to_string(),
+            values => format!("'1:{}:({})'", subcommand.get_positionals().map(value_name).next().unwrap_or_default(), values.join(" ")),
+        };
+        script.push_str(&format!("                {})\n                    _arguments{} \\\n                        {}\n",
+            subcommand.get_name(), specs(subcommand).replace("\n        ", "\n                        "), positionals));
+        script.push_str("                    ;;\n");
+    }
+    script.push_str("                *)\n                    _files\n                    ;;\n");
+    script.push_str("            esac\n            ;;\n    esac\n}\n\n");
+    script.push_str(&format!("{} \"$@\"\n", function));
+    script
+}
+
+fn fish(command: &Command) -> String {
+    let name = command.get_name();
+    let names: Vec<&str> = subcommands(command).map(Command::get_name).collect();
+    let mut script = String::new();
+    let mut complete = |condition: &str, arg: &Arg| {
+        let mut line = format!("complete -c {} -n '{}'", name, condition);
+        if let Some(short) = arg.get_short() {
+            line.push_str(&format!(" -s {}", short));
+        }
+        if let Some(long) = arg.get_long() {
+            line.push_str(&format!(" -l {}", long));
+        }
+        let values = possible_values(arg);
+        if takes_value(arg) {
+            line.push_str(" -r");
+        }
+        if !values.is_empty() {
+            line.push_str(&format!(" -f -a '{}'", values.join(" ")));
+        }
+        line.push_str(&format!(" -d '{}'\n", fish_escape(&summary(arg.get_help()))));
+        script.push_str(&line);
+    };
+
+    for arg in options(command) {
+        complete("__fish_use_subcommand", arg);
+    }
+    for subcommand in subcommands(command) {
+        for arg in options(subcommand) {
+            complete(&format!("__fish_seen_subcommand_from {}", subcommand.get_name()), arg);
+        }
+    }
+    for subcommand in subcommands(command) {
+        script.push_str(&format!("complete -c {} -n '__fish_use_subcommand' -f -a {} -d '{}'\n", name, subcommand.get_name(),
+            fish_escape(&summary(subcommand.get_about()))));
+        let values: Vec<String> = subcommand.get_positionals().flat_map(possible_values).collect();
+        if !values.is_empty() {
+            script.push_str(&format!("complete -c {} -n '__fish_seen_subcommand_from {}' -f -a '{}'\n", name, subcommand.get_name(),
+                values.join(" ")));
+        }
+    }
+    // Without a subcommand the arguments of the default one are accepted, so `__fish_use_subcommand` alone
+    // would still offer subcommands after them
+    script.push_str(&format!("complete -c {} -n 'not __fish_seen_subcommand_from {}' -F\n", name, names.join(" ")));
+    script
+}
+
+fn powershell(command: &Command) -> String {
+    let name = command.get_name();
+    let results = |command: &Command, indent: &str| -> String {
+        options(command).flat_map(|arg| {
+            let help = powershell_escape(&summary(arg.get_help()));
+            flags(arg).into_iter().map(move |flag| {
+                format!("{}[CompletionResult]::new('{}', '{}', [CompletionResultType]::ParameterName, '{}')\n", indent, flag, flag, help)
+            })
+        })
+        .collect()
+    };
+
+    let mut script = String::from("using namespace System.Management.Automation\n\n");
+    script.push_str(&format!("Register-ArgumentCompleter -Native -CommandName '{}' -ScriptBlock {{\n", name));
+    script.push_str("    param($wordToComplete, $commandAst, $cursorPosition)\n\n");
+    let names: Vec<String> = subcommands(command).map(|subcommand| format!("'{}'", subcommand.get_name())).collect();
+    script.push_str(&format!("    $subcommands = @({})\n", names.join(", ")));
+    script.push_str("    $subcommand = $commandAst.CommandElements | Select-Object -Skip 1 | ForEach-Object { $_.ToString() } |\n");
+    script.push_str("        Where-Object { $subcommands -contains $_ } | Select-Object -First 1\n\n");
+    script.push_str("    $completions = @(switch ($subcommand) {\n");
+    for subcommand in subcommands(command) {
+        let values: String = subcommand.get_positionals().flat_map(possible_values)
+            .map(|value| {
+                format!("            [CompletionResult]::new('{0}', '{0}', [CompletionResultType]::ParameterValue, '{0}')\n", value)
+            })
+            .collect();
+        script.push_str(&format!("        '{}' {{\n{}{}        }}\n", subcommand.get_name(), values, results(subcommand, "            ")));
+    }
+    script.push_str("        default {\n");
+    for subcommand in subcommands(command) {
+        script.push_str(&format!("            [CompletionResult]::new('{0}', '{0}', [CompletionResultType]::ParameterValue, '{1}')\n",
+            subcommand.get_name(), powershell_escape(&summary(subcommand.get_about()))));
+    }
+    script.push_str(&results(command, "            "));
+    script.push_str("        }\n    })\n\n");
+    script.push_str("    $completions | Where-Object { $_.CompletionText -like \"$wordToComplete*\" } |\n");
+    script.push_str("        Sort-Object -Property ListItemText\n}\n");
+    script
+}
+
+fn subcommands(command: &Command) -> impl Iterator<Item = &Command> {
+    command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set())
+}
+
+fn options(command: &Command) -> impl Iterator<Item = &Arg> {
+    command.get_arguments().filter(|arg| !arg.is_hide_set() && !arg.is_positional())
+}
+
+/// `--long` and `-s` of an option, long first.
+fn flags(arg: &Arg) -> Vec<String> {
+    arg.get_long().map(|long| format!("--{}", long)).into_iter()
+        .chain(arg.get_short().map(|short| format!("-{}", short)))
+        .collect()
+}
+
+fn takes_value(arg: &Arg) -> bool {
+    arg.get_action().takes_values()
+}
+
+fn is_repeatable(arg: &Arg) -> bool {
+    matches!(arg.get_action(), clap::ArgAction::Append | clap::ArgAction::Count)
+}
+
+fn possible_values(arg: &Arg) -> Vec<String> {
+    arg.get_possible_values().into_iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+fn value_name(arg: &Arg) -> String {
+    arg.get_value_names().and_then(|names| names.first()).map_or_else(|| arg.get_id().to_string(), |name| name.to_string())
+}
+
+/// The first sentence of a help text, which is what fits next to a completion.
+fn summary(help: Option<&clap::builder::StyledStr>) -> String {
+    let help = help.map(ToString::to_string).unwrap_or_default();
+    let first_line = help.lines().next().unwrap_or_default();
+    // A sentence ends where the next one starts with a capital, not at the period of e.g.
+    let end = first_line.match_indices(". ")
+        .find(|(index, _)| first_line[index + 2..].starts_with(|c: char| c.is_uppercase()))
+        .map_or(first_line.len(), |(index, _)| index);
+    first_line[..end].trim_end_matches('.').to_string()
+}
+
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''").replace('[', "\\[").replace(']', "\\]").replace(':', "\\:")
+}
+
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+fn powershell_escape(text: &str) -> String {
+    text.replace('\'', "''")
+}
@@ -0,0 +1,162 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use reqwest::StatusCode;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+/// How long to ignore further throttling signals after reducing the limit, so that a burst
+/// of failures from requests that were already in flight only counts once
+const DECREASE_COOLDOWN: Duration = Duration::from_secs(2);
+
+/// Limits the number of concurrent requests, halving the limit whenever upstream signals
+/// it is overloaded and growing it by one after every "round" of successful requests.
+/// Optionally, requests also start no closer together than a fixed interval.
+pub struct AdaptiveLimiter {
+    max: usize,
+    state: Mutex<LimiterState>,
+    released: Notify,
+    interval: Option<Duration>,
+    /// When the next request may start, with an interval
+    next_start: Mutex<Instant>,
+}
+
+struct LimiterState {
+    limit: usize,
+    in_flight: usize,
+    successes: usize,
+    last_decrease: Option<Instant>,
+}
+
+/// A slot in the limiter, released on drop.
+pub struct Permit<'a> {
+    limiter: &'a AdaptiveLimiter,
+}
+
+impl AdaptiveLimiter {
+    pub fn new(max: usize, interval: Option<Duration>) -> AdaptiveLimiter {
+        let max = max.max(1);
+        AdaptiveLimiter {
+            max,
+            state: Mutex::new(LimiterState {
+                limit: max,
+                in_flight: 0,
+                successes: 0,
+                last_decrease: None,
+            }),
+            released: Notify::new(),
+            interval,
+            next_start: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub async fn acquire(&self) -> Permit<'_> {
+        loop {
+            // Created before checking, so a release in between isn't missed
+            let released = self.released.notified();
+            {
+                let mut state = self.state.lock().unwrap();
+                if state.in_flight < state.limit {
+                    state.in_flight += 1;
+                    break
+                }
+            }
+            released.await;
+        }
+        let permit = Permit { limiter: self };
+
+        if let Some(interval) = self.interval {
+            let start = {
+                let mut next_start = self.next_start.lock().unwrap();
+                let start = (*next_start).max(Instant::now());
+                *next_start = start + interval;
+                start
+            };
+            tokio::time::sleep_until(start.into()).await;
+        }
+        permit
+    }
+}
+
+impl Permit<'_> {
+    pub fn succeeded(&self) {
+        let mut state = self.limiter.state.lock().unwrap();
+        state.successes += 1;
+        if state.successes >= state.limit && state.limit < self.limiter.max {
+            state.limit += 1;
+            state.successes = 0;
+            self.limiter.released.notify_waiters();
+        }
+    }
+
+    pub fn throttled(&self) {
+        let mut state = self.limiter.state.lock().unwrap();
+        if state.last_decrease.is_some_and(|t| t.elapsed() < DECREASE_COOLDOWN) {
+            return
+        }
+
+        let limit = (state.limit / 2).max(1);
+        if limit < state.limit {
+            tracing::warn!("Upstream is throttling, reducing download concurrency to {}", limit);
+        }
+        state.limit = limit;
+        state.successes = 0;
+        state.last_decrease = Some(Instant::now());
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.limiter.state.lock().unwrap().in_flight -= 1;
+        self.limiter.released.notify_waiters();
+    }
+}
+
+/// Caps the number of bytes transferred by all downloads of a run together.
+pub struct DownloadBudget {
+    max: Option<u64>,
+    used: AtomicU64,
+}
+
+impl DownloadBudget {
+    pub fn new(max: Option<u64>) -> DownloadBudget {
+        DownloadBudget { max, used: AtomicU64::new(0) }
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.max
+    }
+
+    /// Bytes transferred so far.
+    pub fn used(&self) -> u64 {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    /// Accounts for `bytes` more transferred bytes. Returns false once the budget is exceeded.
+    pub fn consume(&self, bytes: u64) -> bool {
+        let used = self.used.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        self.max.is_none_or(|max| used <= max)
+    }
+}
+
+/// Whether a response status means upstream wants us to slow down.
+pub fn is_throttling(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
@@ -0,0 +1,83 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Diagnostic reports for runs that crash, so unattended runs leave something to debug.
+
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::Mutex;
+
+/// Number of most recently attempted URLs kept for the report
+const RECENT_URLS: usize = 10;
+
+struct CrashContext {
+    phase: &'static str,
+    recent_urls: VecDeque<String>,
+}
+
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext {
+    phase: "starting",
+    recent_urls: VecDeque::new(),
+});
+
+/// Records what the run is currently doing.
+pub fn set_phase(phase: &'static str) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        context.phase = phase;
+    }
+}
+
+/// Records a URL that is about to be requested.
+pub fn attempting(url: &str) {
+    if let Ok(mut context) = CONTEXT.lock() {
+        if context.recent_urls.len() == RECENT_URLS {
+            context.recent_urls.pop_front();
+        }
+        context.recent_urls.push_back(url.to_string());
+    }
+}
+
+/// Installs a panic hook that, after the default output, writes a report with the given
+/// configuration, the current phase, recently attempted URLs and a backtrace to the temporary directory.
+pub fn install_hook(config: String) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        let mut report = String::new();
+        let _ = writeln!(report, "{} {} crashed at {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"), chrono::Utc::now().to_rfc3339());
+        let _ = writeln!(report, "\n{}", info);
+        // The panic may have happened while the context was locked
+        if let Ok(context) = CONTEXT.try_lock() {
+            let _ = writeln!(report, "\nPhase: {}", context.phase);
+            let _ = writeln!(report, "\nRecently attempted URLs:");
+            for url in &context.recent_urls {
+                let _ = writeln!(report, "  {}", url);
+            }
+        }
+        let _ = writeln!(report, "\nConfiguration:\n{}", config);
+        let _ = writeln!(report, "\nBacktrace:\n{}", Backtrace::force_capture());
+
+        let path = std::env::temp_dir().join(format!("{}-crash-{}.txt", env!("CARGO_PKG_NAME"), chrono::Utc::now().format("%Y%m%dT%H%M%S")));
+        match std::fs::write(&path, report) {
+            Ok(()) => eprintln!("Crash report written to {}", path.display()),
+            Err(why) => eprintln!("Couldn't write crash report to {}: {}", path.display(), why),
+        }
+    }));
+}
@@ -0,0 +1,243 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A full-screen view of a watching or serving process, for operators keeping a terminal on it: when the last
+//! cycle ended and how, what became of every library in it, the versions that changed recently, error counts and
+//! the latest log lines.
+
+use crate::exit::ExitStatus;
+use crate::logging;
+use chrono::{DateTime, Local};
+use indexmap::IndexMap;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::{cursor, execute, terminal};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table};
+use ratatui::{Frame, Terminal};
+use std::collections::VecDeque;
+use std::io::Stdout;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Recent changes and log lines kept, the newest ones
+const HISTORY: usize = 200;
+/// How often the screen is redrawn, keeping the time until the next cycle current
+const REDRAW_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What the cycles of a process came to so far, as shown on the screen.
+#[derive(Default)]
+pub struct Dashboard {
+    state: Mutex<State>,
+}
+
+/// Shared between the generator recording cycles and the task drawing them.
+pub type SharedDashboard = Arc<Dashboard>;
+
+#[derive(Default)]
+struct State {
+    cycles: u64,
+    failed_cycles: u64,
+    failed_versions: u64,
+    /// When the last cycle ended, its exit status and why it failed
+    last_cycle: Option<(DateTime<Local>, ExitStatus, Option<String>)>,
+    next_cycle: Option<DateTime<Local>>,
+    /// The versions of every library in the last cycle
+    libraries: IndexMap<String, LibraryStatus>,
+    /// Versions hashed or failed, newest last
+    changes: VecDeque<(DateTime<Local>, String, bool)>,
+    log: VecDeque<String>,
+}
+
+#[derive(Default)]
+struct LibraryStatus {
+    versions: usize,
+    hashed: usize,
+    reused: usize,
+    failed: usize,
+}
+
+/// What a single cycle did, for [`Dashboard::record`].
+pub struct CycleReport {
+    pub status: ExitStatus,
+    /// Why the cycle failed
+    pub error: Option<String>,
+    pub versions: Vec<VersionReport>,
+}
+
+pub struct VersionReport {
+    pub library: String,
+    pub version: String,
+    /// `hashed`, `reused`, `failed` or `over budget`
+    pub status: &'static str,
+    /// Why the version failed
+    pub error: Option<String>,
+}
+
+impl Dashboard {
+    pub fn record(&self, cycle: CycleReport) {
+        let now = Local::now();
+        let mut state = self.state.lock().unwrap();
+        state.cycles += 1;
+        if cycle.error.is_some() {
+            state.failed_cycles += 1;
+        }
+
+        state.libraries.clear();
+        for version in &cycle.versions {
+            let library = state.libraries.entry(version.library.clone()).or_default();
+            library.versions += 1;
+            match version.status {
+                "hashed" => library.hashed += 1,
+                "reused" => library.reused += 1,
+                _ => library.failed += 1,
+            }
+        }
+        for version in cycle.versions.into_iter().filter(|version| version.status != "reused") {
+            let failed = version.status != "hashed";
+            if failed {
+                state.failed_versions += 1;
+            }
+            let change = match version.error {
+                Some(why) => format!("{} {} {}: {}", version.status, version.library, version.version, why),
+                None => format!("{} {} {}", version.status, version.library, version.version),
+            };
+            push_bounded(&mut state.changes, (now, change, failed));
+        }
+        state.last_cycle = Some((now, cycle.status, cycle.error));
+    }
+
+    /// Shows when the next cycle starts.
+    pub fn schedule(&self, next: DateTime<Local>) {
+        self.state.lock().unwrap().next_cycle = Some(next);
+    }
+
+    fn log(&self, line: &str) {
+        push_bounded(&mut self.state.lock().unwrap().log, line.to_string());
+    }
+}
+
+/// The terminal while the dashboard is drawn on it, given back when dropped.
+pub struct Screen {
+    terminal: Arc<Mutex<Terminal<CrosstermBackend<Stdout>>>>,
+    redraw: tokio::task::JoinHandle<()>,
+    dashboard: SharedDashboard,
+}
+
+/// Switches the terminal to a screen of its own and keeps drawing the dashboard on it, with everything logged
+/// going there rather than to stderr, until the [`Screen`] is dropped.
+pub fn show(dashboard: SharedDashboard) -> std::io::Result<Screen> {
+    execute!(std::io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+    let terminal = Arc::new(Mutex::new(Terminal::new(CrosstermBackend::new(std::io::stdout()))?));
+
+    let sink = dashboard.clone();
+    logging::divert(Some(Box::new(move |line| sink.log(line))));
+    let redraw = tokio::spawn({
+        let terminal = terminal.clone();
+        let dashboard = dashboard.clone();
+        async move {
+            loop {
+                {
+                    let state = dashboard.state.lock().unwrap();
+                    let _ = terminal.lock().unwrap().draw(|frame| draw(frame, &state));
+                }
+                tokio::time::sleep(REDRAW_INTERVAL).await;
+            }
+        }
+    });
+
+    Ok(Screen { terminal, redraw, dashboard })
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        self.redraw.abort();
+        // Waits for a redraw in progress, the task stopping at its next sleep
+        let _terminal = self.terminal.lock().unwrap();
+        logging::divert(None);
+        let _ = execute!(std::io::stdout(), terminal::LeaveAlternateScreen, cursor::Show);
+        // What was logged in the meantime would be lost with the screen otherwise
+        for line in &self.dashboard.state.lock().unwrap().log {
+            logging::write_line(line);
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, state: &State) {
+    let [header, libraries, changes, log] = Layout::vertical([
+        Constraint::Length(6),
+        Constraint::Length(state.libraries.len().max(1) as u16 + 3),
+        Constraint::Fill(1),
+        Constraint::Fill(1),
+    ]).areas(frame.area());
+
+    let now = Local::now();
+    let last_cycle = match &state.last_cycle {
+        Some((ended, status, _)) => format!("Last cycle ended {} with exit status {} ({})",
+            ended.format("%Y-%m-%d %H:%M:%S"), status.code(), status.name()),
+        None => "First cycle running".to_string(),
+    };
+    let next_cycle = match state.next_cycle {
+        Some(next) if next > now => {
+            let remaining = Duration::from_secs((next - now).num_seconds().max(0) as u64);
+            format!("Next cycle at {} (in {})", next.format("%Y-%m-%d %H:%M:%S"), humantime::format_duration(remaining))
+        }
+        Some(_) => "Cycle running".to_string(),
+        None => String::new(),
+    };
+    let mut lines = vec![
+        Line::from(last_cycle),
+        Line::from(next_cycle),
+        Line::from(format!("{} cycle(s), {} failed, {} version(s) failed", state.cycles, state.failed_cycles, state.failed_versions)),
+    ];
+    if let Some((_, _, Some(why))) = &state.last_cycle {
+        lines.push(Line::styled(why.clone(), Style::new().fg(Color::Red)));
+    }
+    frame.render_widget(Paragraph::new(lines).block(Block::bordered().title(env!("CARGO_PKG_NAME"))), header);
+
+    let bold = Style::new().add_modifier(Modifier::BOLD);
+    let rows = state.libraries.iter().map(|(name, library)| {
+        let failed = if library.failed > 0 { Style::new().fg(Color::Red) } else { Style::new() };
+        Row::new([name.clone(), library.versions.to_string(), library.hashed.to_string(), library.reused.to_string(),
+            library.failed.to_string()]).style(failed)
+    });
+    let widths = [Constraint::Fill(1), Constraint::Length(8), Constraint::Length(8), Constraint::Length(8), Constraint::Length(8)];
+    let table = Table::new(rows, widths)
+        .header(Row::new(["Library", "Versions", "Hashed", "Reused", "Failed"]).style(bold))
+        .block(Block::bordered().title("Libraries in the last cycle"));
+    frame.render_widget(table, libraries);
+
+    let visible = changes.height.saturating_sub(2) as usize;
+    let recent = state.changes.iter().rev().take(visible).map(|(time, change, failed)| {
+        let style = if *failed { Style::new().fg(Color::Red) } else { Style::new().fg(Color::Green) };
+        Line::styled(format!("{}  {}", time.format("%m-%d %H:%M:%S"), change), style)
+    });
+    frame.render_widget(Paragraph::new(recent.collect::<Vec<_>>()).block(Block::bordered().title("Recent changes")), changes);
+
+    let visible = log.height.saturating_sub(2) as usize;
+    let latest: Vec<_> = state.log.iter().skip(state.log.len().saturating_sub(visible)).map(|line| Line::from(line.as_str())).collect();
+    frame.render_widget(Paragraph::new(latest).block(Block::bordered().title("Log")), log);
+}
+
+fn push_bounded<T>(history: &mut VecDeque<T>, item: T) {
+    if history.len() == HISTORY {
+        history.pop_front();
+    }
+    history.push_back(item);
+}
@@ -0,0 +1,301 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Checks of a configuration ahead of a run: everything a run would find wrong with it only once it gets there,
+//! with a hint at what to change.
+
+use crate::auth;
+use crate::concurrency::{AdaptiveLimiter, DownloadBudget};
+use crate::download::{self, Connection, DownloadError, ResponseLimits, RetryPolicy};
+use crate::elyby;
+use crate::generator::{self, GeneratorConfig, InjectorPolicy, VersionSource};
+use crate::local::{self, LocalRepository};
+use crate::maven::{self, Packaging};
+use crate::pins::Pins;
+use crate::state::State;
+use regex::Regex;
+use std::path::Path;
+use std::time::Duration;
+
+/// The outcome of one check.
+pub struct Check {
+    /// What was checked, e.g. `Maven metadata`
+    pub subject: String,
+    pub passed: bool,
+    /// What was found, or what is wrong
+    pub detail: String,
+    /// What to change about the configuration, for some failures
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(subject: impl Into<String>, detail: impl Into<String>) -> Check {
+        Check { subject: subject.into(), passed: true, detail: detail.into(), hint: None }
+    }
+
+    fn fail(subject: impl Into<String>, detail: impl Into<String>, hint: Option<String>) -> Check {
+        Check { subject: subject.into(), passed: false, detail: detail.into(), hint }
+    }
+}
+
+/// Checks the settings of `config`, that its repositories can be reached with its credentials, that its download URL
+/// format strings resolve for the newest version each repository lists, and that its output can be written.
+pub async fn diagnose(config: &GeneratorConfig, http_client: &reqwest::Client) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let rewrite_rules = check_settings(config, &mut checks);
+
+    // A single attempt each, a flaky repository is worth knowing about
+    let retry = RetryPolicy { attempts: 0, backoff: Duration::ZERO, jitter: false };
+    let limiter = AdaptiveLimiter::new(config.max_concurrent, config.request_interval);
+    let budget = DownloadBudget::new(None);
+    let local = config.local_repo.as_deref().map(LocalRepository::new);
+    let connection = Connection {
+        client: http_client,
+        credentials: &config.credentials,
+        limiter: &limiter,
+        budget: &budget,
+        cache: None,
+        retry: &retry,
+        local: local.as_ref(),
+        limits: ResponseLimits { max_document_size: config.max_metadata_size, max_artifact_size: config.max_artifact_size },
+    };
+
+    let sample = match (config.source, &config.elyby_api_url) {
+        (VersionSource::ElybyApi, Some(api_url)) => match elyby::list_builds(&connection, api_url).await {
+            Ok(builds) => {
+                checks.push(Check::pass("Builds API", format!("{} lists {} build(s)", api_url, builds.len())));
+                builds.into_iter().next_back().map(|build| build.version)
+            }
+            Err(elyby::BuildsApiError::Download(why)) => {
+                let hint = download_hint(&why, &config.credentials, api_url);
+                checks.push(Check::fail("Builds API", format!("{}: {}", api_url, why), hint));
+                None
+            }
+            Err(why) => {
+                checks.push(Check::fail("Builds API", format!("{}: {}", api_url, why),
+                    Some("Check that --elyby-api-url points at the builds API itself".to_string())));
+                None
+            }
+        },
+        _ => {
+            let mut sample = None;
+            let repositories = std::iter::once(("Maven metadata", &config.metadata_url))
+                .chain(config.fallback_repositories.iter().map(|(url, _)| ("Fallback Maven metadata", url)));
+            for (subject, url) in repositories {
+                let newest = check_metadata(&connection, subject, url, &mut checks).await;
+                sample = sample.or(newest);
+            }
+            sample
+        }
+    };
+    // Only full versions of --versions are downloaded as they are, and then there may be no metadata to ask
+    let sample = sample.or_else(|| config.versions.last().cloned());
+    match &sample {
+        Some(version) => {
+            let template = config.url_template_override.iter()
+                .find(|(base, _)| version == base || version.starts_with(&format!("{}-", base)))
+                .map_or(&config.authlib_download_url_format, |(_, template)| template);
+            let url = generator::expand_url_template(template, &config.artifact, version, &config.packaging);
+            check_download(&connection, "Download URL", &rewrite(&rewrite_rules, url), version, &mut checks).await;
+        }
+        None => checks.push(Check::fail("Download URL", "no version to resolve the URL format string for",
+            Some("Fix the version listing above, or name a version with --versions".to_string()))),
+    }
+
+    for library in &config.libraries {
+        let subject = format!("Maven metadata of {}", library.override_key);
+        if let Some(version) = check_metadata(&connection, &subject, &library.metadata_url, &mut checks).await {
+            let url = generator::expand_url_template(&library.url_template, &library.artifact, &version, &Packaging::default());
+            let subject = format!("Download URL of {}", library.override_key);
+            check_download(&connection, &subject, &rewrite(&rewrite_rules, url), &version, &mut checks).await;
+        }
+    }
+
+    if !matches!(config.injector, InjectorPolicy::Skip) {
+        let url = &config.injector_download_url;
+        match probe(&connection, url).await {
+            Ok(()) => checks.push(Check::pass("authlib-injector", format!("{} is reachable", url))),
+            Err(why) => {
+                let hint = download_hint(&why, &config.credentials, url).or_else(|| match config.injector {
+                    InjectorPolicy::Required => Some("Use --injector optional to generate without it".to_string()),
+                    _ => None,
+                });
+                checks.push(Check::fail("authlib-injector", format!("{}: {}", url, why), hint));
+            }
+        }
+    }
+
+    if config.writes_to_stdout() {
+        checks.push(Check::pass("Output", "written to stdout"));
+    } else {
+        checks.push(check_output(&config.output_file));
+    }
+    checks
+}
+
+/// Checks the settings that a run would only reject once started, returning the compiled URL rewrite rules.
+fn check_settings(config: &GeneratorConfig, checks: &mut Vec<Check>) -> Vec<(Regex, String)> {
+    let templates = std::iter::once(&config.authlib_download_url_format)
+        .chain(config.fallback_repositories.iter().map(|(_, template)| template))
+        .chain(config.url_template_override.iter().map(|(_, template)| template))
+        .chain(std::iter::once(&config.vanilla_url_format))
+        .chain(config.libraries.iter().map(|library| &library.url_template));
+    let mut problems: Vec<String> = templates
+        .filter_map(|template| generator::check_url_template(template).err().map(|why| format!("`{}`: {}", template, why)))
+        .collect();
+
+    let patterns = config.include.iter()
+        .chain(&config.exclude)
+        .chain(config.target_version_rules.iter().map(|(pattern, _)| pattern));
+    problems.extend(patterns.filter_map(|pattern| Regex::new(pattern).err().map(|why| pattern_problem(pattern, &why))));
+    let mut rewrite_rules = Vec::new();
+    for (pattern, replacement) in &config.rewrite_url {
+        match Regex::new(pattern) {
+            Ok(regex) => rewrite_rules.push((regex, replacement.clone())),
+            Err(why) => problems.push(pattern_problem(pattern, &why)),
+        }
+    }
+
+    let files = config.exclusions.iter().map(|path| ("exclusions file", path))
+        .chain(config.merge.iter().map(|path| ("file to merge into", path)))
+        .chain(config.keyring.iter().map(|path| ("keyring", path)));
+    for (what, path) in files {
+        if let Err(why) = std::fs::metadata(path) {
+            problems.push(format!("{} {}: {}", what, path.display(), why));
+        }
+    }
+    if let Some(path) = &config.state
+        && let Err(why) = State::load(path) {
+        problems.push(format!("state file {}: {}", path.display(), why));
+    }
+    if let Some(path) = &config.pin_file
+        && let Err(why) = Pins::load(path) {
+        problems.push(format!("pin file {}: {}", path.display(), why));
+    }
+
+    if problems.is_empty() {
+        checks.push(Check::pass("Settings", "URL format strings, patterns and files are valid"));
+    }
+    for problem in problems {
+        checks.push(Check::fail("Settings", problem, None));
+    }
+    rewrite_rules
+}
+
+/// Regex syntax errors point at the problem over several lines, only the last one says what it is.
+fn pattern_problem(pattern: &str, why: &regex::Error) -> String {
+    let why = why.to_string();
+    format!("`{}`: {}", pattern, why.lines().last().unwrap_or_default().trim_start_matches("error: "))
+}
+
+/// Checks that the Maven metadata at `url` can be downloaded and read, returning the newest version it lists.
+async fn check_metadata(connection: &Connection<'_>, subject: &str, url: &str, checks: &mut Vec<Check>) -> Option<String> {
+    let body = match download::fetch_cached(connection, url).await {
+        Ok(body) => body,
+        Err(why) => {
+            checks.push(Check::fail(subject, format!("{}: {}", url, why), download_hint(&why, connection.credentials, url)));
+            return None
+        }
+    };
+
+    let metadata = maven::declare_unknown_prefixes(String::from_utf8_lossy(&body).into_owned());
+    let versions = maven::parse(&metadata).and_then(|doc| {
+        maven::read_metadata(&doc).map(|metadata| metadata.versions.into_iter().map(str::to_string).collect::<Vec<_>>())
+    });
+    match versions {
+        Ok(versions) => {
+            let newest = versions.last().cloned();
+            checks.push(Check::pass(subject, format!("{} lists {} version(s), newest {}", url, versions.len(),
+                newest.as_deref().unwrap_or("none"))));
+            newest
+        }
+        Err(why) => {
+            checks.push(Check::fail(subject, format!("{}: {}", url, why),
+                Some("Check that the URL points at a maven-metadata.xml, not a directory listing".to_string())));
+            None
+        }
+    }
+}
+
+/// Checks that the file `url` expanded to for `version` is there.
+async fn check_download(connection: &Connection<'_>, subject: &str, url: &str, version: &str, checks: &mut Vec<Check>) {
+    match probe(connection, url).await {
+        Ok(()) => checks.push(Check::pass(subject, format!("{} resolves for {}", url, version))),
+        Err(why) => {
+            let missing = matches!(&why, DownloadError::Http(why) if why.status() == Some(reqwest::StatusCode::NOT_FOUND));
+            let hint = if missing {
+                Some(format!("Check the download URL format string, it expanded to this URL for {}", version))
+            } else {
+                download_hint(&why, connection.credentials, url)
+            };
+            checks.push(Check::fail(subject, format!("{}: {}", url, why), hint));
+        }
+    }
+}
+
+/// Checks that a URL responds successfully or its file exists, preferring HEAD so nothing is downloaded.
+async fn probe(connection: &Connection<'_>, url: &str) -> Result<(), DownloadError> {
+    if let Some(path) = local::file_url_path(url).or_else(|| connection.local?.find(url)) {
+        return std::fs::metadata(&path).map(drop).map_err(|source| DownloadError::Local { path, source })
+    }
+
+    let Connection { client, credentials, .. } = *connection;
+    let response = auth::authorize(credentials, client.head(url), url).send().await?;
+    if [reqwest::StatusCode::METHOD_NOT_ALLOWED, reqwest::StatusCode::NOT_IMPLEMENTED].contains(&response.status()) {
+        auth::authorize(credentials, client.get(url), url).send().await?.error_for_status()?;
+    } else {
+        response.error_for_status()?;
+    }
+
+    Ok(())
+}
+
+/// What to do about a request to `url` having failed, if the error tells.
+fn download_hint(why: &DownloadError, credentials: &[auth::RepositoryCredentials], url: &str) -> Option<String> {
+    let DownloadError::Http(why) = why else {
+        return matches!(why, DownloadError::Local { .. }).then(|| "Check the path, and --local-repo".to_string())
+    };
+    let authorized = credentials.iter().any(|repository| url.starts_with(&repository.url_prefix));
+    Some(match why.status().map(|status| status.as_u16()) {
+        Some(401 | 403) if authorized => "The repository refused the credentials, check the --auth matching this URL".to_string(),
+        Some(401 | 403) => "The repository needs credentials, give them with --auth url=PREFIX,...".to_string(),
+        Some(404) => "Nothing is there, check the URL for typos".to_string(),
+        _ if why.is_timeout() => "The server didn't answer in time, check --connect-timeout and --request-timeout".to_string(),
+        _ if why.is_connect() => "Couldn't connect, check the host name, the network and --proxy".to_string(),
+        _ => return None,
+    })
+}
+
+fn rewrite(rules: &[(Regex, String)], url: String) -> String {
+    rules.iter().fold(url, |url, (pattern, replacement)| pattern.replace(&url, replacement.as_str()).into_owned())
+}
+
+/// Checks that the output can be written, by creating a file next to it.
+fn check_output(output_file: &str) -> Check {
+    let path = Path::new(output_file);
+    let directory = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    let probe = directory.join(format!(".{}.doctor", path.file_name().and_then(|name| name.to_str()).unwrap_or("output")));
+    let written = std::fs::OpenOptions::new().write(true).create_new(true).open(&probe)
+        .and_then(|_| std::fs::remove_file(&probe))
+        .and_then(|()| if path.exists() { std::fs::OpenOptions::new().append(true).open(path).map(drop) } else { Ok(()) });
+    match written {
+        Ok(()) => Check::pass("Output", format!("{} is writable", output_file)),
+        Err(why) => Check::fail("Output", format!("{}: {}", output_file, why),
+            Some(format!("Create {} or make it writable, or write the output elsewhere", directory.display()))),
+    }
+}
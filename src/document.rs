@@ -0,0 +1,316 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Working with already generated metadata documents: loading, comparing and verifying them.
+
+use crate::concurrency::{AdaptiveLimiter, DownloadBudget};
+use crate::download::{self, Connection, ResponseLimits, RetryPolicy};
+use crate::error::GeneratorError;
+use crate::exit::ExitStatus;
+use crate::generator::DEFAULT_MAX_CONCURRENT;
+use crate::hashing::{Digests, HashAlgorithm};
+use crate::model::{self, Extra, LibraryEntry, MetadataFile, Overrides};
+use crate::signature;
+use futures::StreamExt;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::time::Duration;
+
+/// Fields of an override entry that are compared between documents
+pub const ENTRY_FIELDS: [&str; 7] = ["name", "url", "sha1", "sha256", "sha512", "size", "minecraftVersions"];
+
+/// Loads a metadata document from a file, or from a URL if the location is one.
+pub async fn load(client: &reqwest::Client, location: &str) -> Result<MetadataFile, GeneratorError> {
+    let contents = read(client, location).await?;
+    MetadataFile::parse(&contents).map_err(|source| GeneratorError::Document { location: location.to_string(), source })
+}
+
+/// Checks the detached Ed25519 signature published next to a document, at its location with `.sig` appended,
+/// against a PEM-encoded public key.
+pub async fn verify_signature(client: &reqwest::Client, location: &str, public_key: &Path) -> Result<(), GeneratorError> {
+    let contents = read(client, location).await?;
+    let signature = read(client, &format!("{}.sig", location)).await?;
+    signature::verify_ed25519(public_key, &signature, &contents)
+        .map_err(|why| GeneratorError::BadSignature { location: location.to_string(), why })
+}
+
+/// Reads a document as it is from a file, or from a URL if the location is one.
+pub async fn read(client: &reqwest::Client, location: &str) -> Result<Vec<u8>, GeneratorError> {
+    if location.starts_with("http://") || location.starts_with("https://") {
+        async { client.get(location).send().await?.error_for_status()?.bytes().await }.await
+            .map(|bytes| bytes.to_vec())
+            .map_err(|source| GeneratorError::Download { what: location.to_string(), source: source.into() })
+    } else {
+        std::fs::read(location).map_err(|source| GeneratorError::Io { action: "read", path: location.into(), source })
+    }
+}
+
+pub enum Difference {
+    Added(String),
+    Removed(String),
+    Changed { version: String, field: &'static str, old: serde_json::Value, new: serde_json::Value },
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difference::Added(version) => write!(f, "+ {}", version),
+            Difference::Removed(version) => write!(f, "- {}", version),
+            Difference::Changed { version, field, old, new } => write!(f, "~ {} {}: {} -> {}", version, field, old, new),
+        }
+    }
+}
+
+impl Difference {
+    /// The differences one per line, their versions aligned, colored with ANSI escapes if `color`: added versions
+    /// green, removed ones red, and for changed ones the field highlighted, its old value red and its new one green.
+    pub fn table(differences: &[Difference], color: bool) -> String {
+        let paint = |code: &str, text: String| if color { format!("\x1b[{}m{}\x1b[0m", code, text) } else { text };
+        let version = |difference: &Difference| match difference {
+            Difference::Added(version) | Difference::Removed(version) | Difference::Changed { version, .. } => version.chars().count(),
+        };
+        let width = differences.iter().map(version).max().unwrap_or(0);
+
+        let mut table = String::new();
+        for difference in differences {
+            let line = match difference {
+                Difference::Added(version) => paint("32", format!("+ {}", version)),
+                Difference::Removed(version) => paint("31", format!("- {}", version)),
+                Difference::Changed { version, field, old, new } => format!("{} {}: {} -> {}",
+                    paint("33", format!("~ {:<1$}", version, width)), paint("1", field.to_string()),
+                    paint("31", old.to_string()), paint("32", new.to_string())),
+            };
+            table.push_str(line.trim_end());
+            table.push('\n');
+        }
+        table
+    }
+
+    /// The difference as an object with a `change` of `added`, `removed` or `changed`, for `diff --json`.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Difference::Added(version) => serde_json::json!({ "change": "added", "version": version }),
+            Difference::Removed(version) => serde_json::json!({ "change": "removed", "version": version }),
+            Difference::Changed { version, field, old, new } => {
+                serde_json::json!({ "change": "changed", "version": version, "field": field, "old": old, "new": new })
+            }
+        }
+    }
+}
+
+/// Compares the overrides of every library in two documents.
+pub fn diff_documents(old: &MetadataFile, new: &MetadataFile) -> Vec<Difference> {
+    let empty = Overrides::default();
+    let libraries = new.overrides.keys().chain(old.overrides.keys().filter(|library| !new.overrides.contains_key(*library)));
+    libraries
+        .flat_map(|library| {
+            let differences = diff_overrides(old.overrides.get(library).unwrap_or(&empty), new.overrides.get(library).unwrap_or(&empty));
+            differences.into_iter().map(move |difference| match difference {
+                Difference::Added(version) => Difference::Added(label(library, &version)),
+                Difference::Removed(version) => Difference::Removed(label(library, &version)),
+                Difference::Changed { version, field, old, new } => {
+                    Difference::Changed { version: label(library, &version), field, old, new }
+                }
+            })
+        })
+        .collect()
+}
+
+/// How an entry is referred to in messages. Only entries of libraries other than authlib name their library.
+fn label(library: &str, version: &str) -> String {
+    if library == model::AUTHLIB { version.to_string() } else { format!("{} {}", library, version) }
+}
+
+/// Compares two override maps entry by entry.
+pub fn diff_overrides(old: &Overrides, new: &Overrides) -> Vec<Difference> {
+    let mut differences = Vec::new();
+    for (version, entry) in &new.0 {
+        let Some(previous) = old.0.get(version) else {
+            differences.push(Difference::Added(version.to_string()));
+            continue
+        };
+
+        let (previous, entry) = (fields(previous), fields(entry));
+        for field in ENTRY_FIELDS {
+            if previous[field] != entry[field] {
+                differences.push(Difference::Changed {
+                    version: version.to_string(),
+                    field,
+                    old: previous[field].clone(),
+                    new: entry[field].clone(),
+                });
+            }
+        }
+    }
+    for version in old.0.keys() {
+        if !new.0.contains_key(version) {
+            differences.push(Difference::Removed(version.to_string()));
+        }
+    }
+
+    differences
+}
+
+/// An entry as its fields, left out ones being null.
+fn fields(entry: &LibraryEntry) -> serde_json::Value {
+    serde_json::to_value(entry).expect("entries are always serializable")
+}
+
+/// Every override entry, the authlib-injector and every extra artifact of a document: what it is, its URL and
+/// its documented digests and size.
+pub fn artifacts(document: &MetadataFile) -> Vec<(String, &str, Digests, usize)> {
+    let mut artifacts: Vec<(String, &str, Digests, usize)> = document.overrides.iter()
+        .flat_map(|(library, overrides)| overrides.0.iter()
+            .map(move |(version, entry)| (label(library, version), entry.url.as_str(), entry.digests.clone(), entry.size)))
+        .collect();
+    if let Some(injector) = &document.extras.authlib_injector {
+        artifacts.push(("authlib-injector".to_string(), &injector.url, injector.digests.clone(), injector.size));
+    }
+    for (channel, injector) in &document.extras.injector_channels {
+        artifacts.push((format!("authlib-injector {}", channel), &injector.url, injector.digests.clone(), injector.size));
+    }
+    for (key, extra) in &document.extras.other {
+        if let Extra::Artifact(artifact) = extra {
+            let digests = Digests { sha1: artifact.sha1.clone(), sha256: None, sha512: None };
+            artifacts.push((format!("extra {}", key), &artifact.url, digests, artifact.size));
+        }
+    }
+    artifacts
+}
+
+/// Downloads every artifact of a document and checks their digests and sizes. With `published`, the checksum files
+/// published next to an artifact and a HEAD request are checked instead, falling back to downloading it if there
+/// are none.
+pub async fn verify(client: &reqwest::Client, document: &MetadataFile, published: bool) -> Result<ExitStatus, GeneratorError> {
+    let artifacts = artifacts(document);
+    let limiter = AdaptiveLimiter::new(DEFAULT_MAX_CONCURRENT, None);
+    let budget = DownloadBudget::new(None);
+    let retry = RetryPolicy { attempts: 0, backoff: Duration::ZERO, jitter: false };
+    let connection = Connection {
+        client,
+        credentials: &[],
+        limiter: &limiter,
+        budget: &budget,
+        cache: None,
+        retry: &retry,
+        local: None,
+        limits: ResponseLimits::default(),
+    };
+    let checks = artifacts.iter().map(|(label, url, documented, size)| {
+        let connection = &connection;
+        async move {
+            let algorithms: Vec<_> = [HashAlgorithm::Sha1, HashAlgorithm::Sha256, HashAlgorithm::Sha512].into_iter()
+                .filter(|algorithm| documented.get(*algorithm).is_some())
+                .collect();
+            let checked = if published { download::published_checksums(connection, url, &algorithms).await } else { None };
+            let (digests, actual_size) = match checked {
+                Some(checked) => checked,
+                None => {
+                    async { download::hash_response(client.get(*url).send().await?.error_for_status()?, &algorithms).await }.await
+                        .map_err(|why| format!("Couldn't download {}: {}", label, why))?
+                }
+            };
+
+            for algorithm in algorithms {
+                let (computed, documented) = (digests.get(algorithm), documented.get(algorithm));
+                if computed != documented {
+                    return Ok(Some(format!("{}: {} is {} but the document says {}",
+                        label, algorithm.name(), computed.unwrap_or_default(), documented.unwrap_or_default())))
+                }
+            }
+            if actual_size != *size {
+                return Ok(Some(format!("{}: size is {} but the document says {}", label, actual_size, size)))
+            }
+            Ok::<_, String>(None)
+        }
+    });
+
+    let mut mismatches = 0;
+    let mut unreachable = 0;
+    let results: Vec<_> = futures::stream::iter(checks).buffered(DEFAULT_MAX_CONCURRENT).collect().await;
+    for result in results {
+        match result {
+            Ok(None) => {}
+            Ok(Some(mismatch)) => {
+                tracing::error!("MISMATCH {}", mismatch);
+                mismatches += 1;
+            }
+            Err(why) => {
+                tracing::warn!("{}", why);
+                unreachable += 1;
+            }
+        }
+    }
+
+    if mismatches > 0 {
+        return Err(GeneratorError::Mismatched(mismatches))
+    }
+    if unreachable > 0 {
+        return Err(GeneratorError::Unverifiable(unreachable))
+    }
+
+    Ok(ExitStatus::Changed)
+}
+
+/// Brings a document written by an older generator up to date, saying what changed. Documents without a
+/// `formatVersion` get one, and the bare URL the first releases wrote as the `authlib-injector` extra is turned
+/// into an entry, with the digests and size of the file it points at.
+pub async fn migrate(client: &reqwest::Client, location: &str, contents: &[u8]) -> Result<(MetadataFile, Vec<String>), GeneratorError> {
+    let invalid = |source| GeneratorError::Document { location: location.to_string(), source };
+    let mut document: serde_json::Value = serde_json::from_slice(contents).map_err(invalid)?;
+    let mut changes = Vec::new();
+    if document.get("formatVersion").is_none() {
+        changes.push("added formatVersion".to_string());
+    }
+
+    if let Some(url) = document.pointer("/extras/authlib-injector").and_then(serde_json::Value::as_str).map(str::to_string) {
+        let limiter = AdaptiveLimiter::new(1, None);
+        let budget = DownloadBudget::new(None);
+        let retry = RetryPolicy { attempts: 0, backoff: Duration::ZERO, jitter: false };
+        let connection = Connection {
+            client,
+            credentials: &[],
+            limiter: &limiter,
+            budget: &budget,
+            cache: None,
+            retry: &retry,
+            local: None,
+            limits: ResponseLimits::default(),
+        };
+        // Hashed with the algorithms the library entries were
+        let entries: Vec<_> = document["overrides"].as_object().into_iter()
+            .flat_map(|libraries| libraries.values())
+            .filter_map(serde_json::Value::as_object)
+            .flat_map(|entries| entries.values())
+            .collect();
+        let algorithms: Vec<_> = [HashAlgorithm::Sha256, HashAlgorithm::Sha512].into_iter()
+            .filter(|algorithm| entries.iter().any(|entry| entry.get(algorithm.name()).is_some()))
+            .collect();
+        let downloaded = download::download_artifact(&connection, &url, &algorithms, false).await
+            .map_err(|source| GeneratorError::Download { what: format!("authlib-injector from {}", url), source })?;
+        let mut entry = serde_json::to_value(&downloaded.digests).expect("digests are always serializable");
+        entry["url"] = url.clone().into();
+        entry["size"] = downloaded.size.into();
+        document["extras"]["authlib-injector"] = entry;
+        changes.push(format!("turned the authlib-injector URL into an entry, hashing {}", url));
+    }
+
+    let contents = serde_json::to_vec(&document).expect("JSON values are always serializable");
+    let document = MetadataFile::parse(&contents).map_err(invalid)?;
+    Ok((document, changes))
+}
@@ -0,0 +1,724 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Downloading artifacts and checking them against what the server announced.
+
+use crate::auth::{self, RepositoryCredentials};
+use crate::cache::HttpCache;
+use crate::concurrency::{self, AdaptiveLimiter, DownloadBudget};
+use crate::crash;
+use crate::hashing::{Digests, HashAlgorithm, Hasher};
+use crate::local::{self, LocalRepository};
+use crate::maven;
+use crate::signature;
+use chrono::{DateTime, Utc};
+use reqwest::Error;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// How failed requests are retried: connection failures, throttling and server errors are
+/// assumed to be transient, anything else isn't.
+#[derive(Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub attempts: u32,
+    /// Delay before the first retry, doubled for every further one
+    pub backoff: Duration,
+    /// Wait a random part of the delay instead, so concurrent downloads don't retry in lockstep
+    pub jitter: bool,
+}
+
+impl RetryPolicy {
+    fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.backoff.saturating_mul(1 << attempt.min(16));
+        if self.jitter { delay.mul_f64(rand::random::<f64>()) } else { delay }
+    }
+
+    /// The longest the policy waits before a retry, which also bounds what a server asks for with `Retry-After`.
+    fn max_delay(&self) -> Duration {
+        self.backoff.saturating_mul(1 << self.attempts.saturating_sub(1).min(16))
+    }
+
+    /// Runs a request until it succeeds, fails permanently or runs out of attempts.
+    pub async fn run<T, F: Future<Output = Result<T, Error>>>(&self, mut request: impl FnMut() -> F) -> Result<T, Error> {
+        let mut attempt = 0;
+        loop {
+            match request().await {
+                Err(why) if attempt < self.attempts && is_transient(&why) => {
+                    tokio::time::sleep(self.delay(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+fn is_transient(why: &Error) -> bool {
+    // A body cut off before its Content-Length is reported as a decoding error
+    why.is_connect() || why.is_timeout() || why.is_request() || why.is_body() || why.is_decode()
+        || why.status().is_some_and(concurrency::is_throttling)
+}
+
+/// What the artifact downloads of a run share.
+#[derive(Clone, Copy)]
+pub struct Connection<'a> {
+    pub client: &'a reqwest::Client,
+    pub credentials: &'a [RepositoryCredentials],
+    pub limiter: &'a AdaptiveLimiter,
+    pub budget: &'a DownloadBudget,
+    pub cache: Option<&'a HttpCache>,
+    pub retry: &'a RetryPolicy,
+    /// Local repository to read files from instead of downloading them, when they're in it
+    pub local: Option<&'a LocalRepository>,
+    pub limits: ResponseLimits,
+}
+
+/// How large responses may be, so that a URL pointing at the wrong file can't fill the memory or the disk.
+#[derive(Clone, Copy)]
+pub struct ResponseLimits {
+    /// Of documents read whole into memory, like Maven metadata, in bytes
+    pub max_document_size: u64,
+    /// Of artifacts, in bytes
+    pub max_artifact_size: u64,
+}
+
+impl Default for ResponseLimits {
+    fn default() -> ResponseLimits {
+        ResponseLimits { max_document_size: 16 << 20, max_artifact_size: 256 << 20 }
+    }
+}
+
+/// Rejects a response that announces itself as a web page, or as larger than `max_size`, before its body is read.
+/// Error pages and splash screens of misconfigured servers are answered with a success status too.
+fn check_response(headers: &reqwest::header::HeaderMap, max_size: u64) -> Result<(), DownloadError> {
+    let content_type = headers.get(reqwest::header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).unwrap_or_default();
+    let media_type = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    if ["text/html", "application/xhtml+xml"].contains(&media_type.as_str()) {
+        return Err(DownloadError::WebPage(media_type))
+    }
+    let length = headers.get(reqwest::header::CONTENT_LENGTH).and_then(|v| v.to_str().ok()).and_then(|v| v.parse::<u64>().ok());
+    if length.is_some_and(|length| length > max_size) {
+        return Err(DownloadError::TooLarge(max_size))
+    }
+
+    Ok(())
+}
+
+/// Reads a body whole, as long as it's no larger than `max_size`, charging it to the run's download budget.
+async fn read_limited(mut response: reqwest::Response, max_size: u64, budget: &DownloadBudget)
+    -> Result<Result<bytes::Bytes, DownloadError>, Error> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if !budget.consume(chunk.len() as u64) {
+            return Ok(Err(DownloadError::BudgetExceeded(budget.max().unwrap_or_default())))
+        }
+        body.extend_from_slice(&chunk);
+        if body.len() as u64 > max_size {
+            return Ok(Err(DownloadError::TooLarge(max_size)))
+        }
+    }
+
+    Ok(Ok(body.into()))
+}
+
+impl Connection<'_> {
+    /// Where to read a URL from instead of downloading it, if anywhere: the path of a `file://` URL,
+    /// or its file in the local repository.
+    fn local_file(&self, url: &str) -> Option<PathBuf> {
+        local::file_url_path(url).or_else(|| self.local?.find(url))
+    }
+}
+
+fn read_local(path: &Path) -> Result<bytes::Bytes, DownloadError> {
+    std::fs::read(path).map(bytes::Bytes::from).map_err(|source| DownloadError::Local { path: path.to_path_buf(), source })
+}
+
+/// Downloads an artifact within the concurrency limit, retrying as configured and backing off further
+/// while upstream is throttling. A cached response is only revalidated, and reused if the server says it's still current.
+///
+/// The body is hashed as it arrives, with SHA-1, the given algorithms, and SHA-256 too if the server announced it,
+/// so artifacts are never held in memory. It's only written to disk with `keep`, or to be cached.
+pub async fn download_artifact(connection: &Connection<'_>, url: &str, algorithms: &[HashAlgorithm], keep: bool)
+    -> Result<Downloaded, DownloadError> {
+    if let Some(path) = connection.local_file(url) {
+        let (digests, size) = Digests::compute_file(&path, algorithms)
+            .map_err(|source| DownloadError::Local { path: path.clone(), source })?;
+        let last_modified = std::fs::metadata(&path).and_then(|metadata| metadata.modified()).ok().map(DateTime::<Utc>::from);
+        let contents = keep.then(|| Contents::existing(path));
+        return Ok(Downloaded { digests, size, checksums: ChecksumHeaders::default(), last_modified, contents })
+    }
+
+    let Connection { client, credentials, limiter, cache, retry, .. } = *connection;
+    let cached = cache.and_then(|cache| cache.get(url));
+    let mut attempt = 0;
+    let mut transfer: Option<Transfer> = None;
+    loop {
+        let permit = limiter.acquire().await;
+        crash::attempting(url);
+        let mut request = auth::authorize(credentials, client.get(url), url);
+        let resume = transfer.as_ref().and_then(|transfer| Some((transfer.size, transfer.validator.clone()?)));
+        if let Some((size, validator)) = resume {
+            tracing::debug!("Resuming {} from byte {}", url, size);
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", size)).header(reqwest::header::IF_RANGE, validator);
+        } else if let Some(cached) = &cached {
+            request = cached.conditional(request);
+        }
+        let retry_after = match request.send().await {
+            Ok(response) if cached.is_some() && response.status() == reqwest::StatusCode::NOT_MODIFIED => {
+                permit.succeeded();
+                let cached = cached.unwrap();
+                let last_modified = cached.last_modified().and_then(parse_http_date);
+                let (digests, size) = Digests::compute_file(&cached.path, algorithms).map_err(DownloadError::Spool)?;
+                let contents = keep.then(|| Contents { path: cached.path, temporary: false });
+                return Ok(Downloaded { digests, size, checksums: ChecksumHeaders::default(), last_modified, contents })
+            }
+            Ok(response) if concurrency::is_throttling(response.status()) => {
+                permit.throttled();
+                if attempt == retry.attempts {
+                    return Err(response.error_for_status().unwrap_err().into())
+                }
+                response.headers().get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse().ok())
+                    .map(|seconds| Duration::from_secs(seconds).min(retry.max_delay()))
+            }
+            Ok(response) => match read_body(response, connection, algorithms, keep || cache.is_some(), &mut transfer).await {
+                Ok((headers, mut downloaded)) => {
+                    permit.succeeded();
+                    if let Some(cache) = cache
+                        && let Some(contents) = &downloaded.contents {
+                        cache.put_file(url, &headers, &contents.path);
+                    }
+                    if !keep {
+                        downloaded.contents = None;
+                    }
+                    return Ok(downloaded)
+                }
+                Err(DownloadError::Http(why)) if attempt < retry.attempts && is_transient(&why) => None,
+                Err(DownloadError::Misplaced) if attempt < retry.attempts => None,
+                Err(why) => return Err(why),
+            },
+            Err(why) if attempt < retry.attempts && is_transient(&why) => None,
+            Err(why) => return Err(why.into()),
+        };
+        drop(permit);
+
+        tokio::time::sleep(retry_after.unwrap_or_else(|| retry.delay(attempt))).await;
+        attempt += 1;
+    }
+}
+
+/// What has arrived of an artifact so far, kept across attempts so that an interrupted download can be continued
+/// with a `Range` request instead of starting over.
+struct Transfer {
+    hasher: Hasher,
+    size: usize,
+    checksums: ChecksumHeaders,
+    /// Of the response the transfer started with
+    headers: reqwest::header::HeaderMap,
+    spooled: Option<(Contents, std::fs::File)>,
+    /// `ETag` or `Last-Modified` to continue the same file with, if the server takes range requests
+    validator: Option<reqwest::header::HeaderValue>,
+}
+
+impl Transfer {
+    fn start(headers: &reqwest::header::HeaderMap, algorithms: &[HashAlgorithm], spool: bool) -> Result<Transfer, DownloadError> {
+        let checksums = ChecksumHeaders::from_headers(headers);
+        let mut algorithms = algorithms.to_vec();
+        if checksums.sha256.is_some() {
+            algorithms.push(HashAlgorithm::Sha256);
+        }
+        let accepts_ranges = headers.get(reqwest::header::ACCEPT_RANGES).is_some_and(|v| v.as_bytes() == b"bytes");
+        // A weak ETag doesn't promise the same bytes, so If-Range can't take one
+        let validator = headers.get(reqwest::header::ETAG).filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+            .or_else(|| headers.get(reqwest::header::LAST_MODIFIED))
+            .filter(|_| accepts_ranges)
+            .cloned();
+
+        Ok(Transfer {
+            hasher: Hasher::new(&algorithms),
+            size: 0,
+            checksums,
+            headers: headers.clone(),
+            spooled: if spool { Some(Contents::temporary().map_err(DownloadError::Spool)?) } else { None },
+            validator,
+        })
+    }
+}
+
+/// Reads a response into `transfer`, continuing it if the response is the rest of it, or starting over otherwise.
+/// What arrived stays in `transfer` if the body is cut off.
+async fn read_body(response: reqwest::Response, connection: &Connection<'_>, algorithms: &[HashAlgorithm], spool: bool,
+    transfer: &mut Option<Transfer>) -> Result<(reqwest::header::HeaderMap, Downloaded), DownloadError> {
+    let Connection { budget, limits, .. } = *connection;
+    let mut response = response.error_for_status()?;
+    if response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+        let start = response.headers().get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes ")?.split('-').next()?.parse::<usize>().ok());
+        if transfer.is_none() || start != transfer.as_ref().map(|transfer| transfer.size) {
+            *transfer = None;
+            return Err(DownloadError::Misplaced)
+        }
+    } else {
+        // The server sent the whole file, because it was asked to or because it changed since
+        check_response(response.headers(), limits.max_artifact_size)?;
+        *transfer = Some(Transfer::start(response.headers(), algorithms, spool)?);
+    }
+    let progress = transfer.as_mut().expect("a transfer was started or is continued");
+
+    while let Some(chunk) = response.chunk().await? {
+        if !budget.consume(chunk.len() as u64) {
+            return Err(DownloadError::BudgetExceeded(budget.max().unwrap_or_default()))
+        }
+        progress.hasher.update(&chunk);
+        progress.size += chunk.len();
+        if progress.size as u64 > limits.max_artifact_size {
+            return Err(DownloadError::TooLarge(limits.max_artifact_size))
+        }
+        if let Some((_, file)) = &mut progress.spooled {
+            file.write_all(&chunk).map_err(DownloadError::Spool)?;
+        }
+    }
+
+    let Transfer { hasher, size, checksums, headers, spooled, .. } = transfer.take().expect("a transfer was started or is continued");
+    let contents = spooled.map(|(contents, _)| contents);
+    let last_modified = headers.get(reqwest::header::LAST_MODIFIED).and_then(|v| v.to_str().ok()).and_then(parse_http_date);
+    Ok((headers, Downloaded { digests: hasher.finish(), size, checksums, last_modified, contents }))
+}
+
+/// Parses an HTTP date, like that of a `Last-Modified` header.
+pub fn parse_http_date(date: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(date).ok().map(|time| time.with_timezone(&Utc))
+}
+
+/// Hashes a response body as it arrives, returning its digests for the given algorithms and its size.
+pub async fn hash_response(mut response: reqwest::Response, algorithms: &[HashAlgorithm]) -> Result<(Digests, usize), Error> {
+    let mut hasher = Hasher::new(algorithms);
+    let mut size = 0;
+    while let Some(chunk) = response.chunk().await? {
+        hasher.update(&chunk);
+        size += chunk.len();
+    }
+
+    Ok((hasher.finish(), size))
+}
+
+/// An artifact that was downloaded, or found in the cache or store.
+pub struct Downloaded {
+    pub digests: Digests,
+    pub size: usize,
+    pub checksums: ChecksumHeaders,
+    /// `Last-Modified` of the response, or the modification time of a local file
+    pub last_modified: Option<DateTime<Utc>>,
+    /// Only if the contents were asked to be kept
+    pub contents: Option<Contents>,
+}
+
+impl Downloaded {
+    /// The digests of the downloaded bytes for `algorithms`, once the checksums the server announced agree with them.
+    pub fn verified_digests(&self, algorithms: &[HashAlgorithm]) -> Result<Digests, DownloadError> {
+        self.checksums.verify(&self.digests)?;
+        Ok(self.digests.clone().only(algorithms))
+    }
+}
+
+/// The contents of an artifact on disk: a temporary file removed once dropped, or a file in the cache or store.
+pub struct Contents {
+    path: PathBuf,
+    temporary: bool,
+}
+
+impl Contents {
+    /// Creates a temporary file to write the contents to.
+    fn temporary() -> std::io::Result<(Contents, std::fs::File)> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let name = format!("{}-{}-{}.part", env!("CARGO_PKG_NAME"), std::process::id(), NEXT.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(name);
+        let file = std::fs::File::create(&path)?;
+        Ok((Contents { path, temporary: true }, file))
+    }
+
+    /// Contents that already are in a file, which is left alone.
+    pub fn existing(path: PathBuf) -> Contents {
+        Contents { path, temporary: false }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for Contents {
+    fn drop(&mut self) {
+        if self.temporary {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Checksums a repository manager announced for a response.
+#[derive(Default)]
+pub struct ChecksumHeaders {
+    pub sha1: Option<String>,
+    pub sha256: Option<String>,
+}
+
+impl ChecksumHeaders {
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> ChecksumHeaders {
+        let header = |name: &str| headers.get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_ascii_lowercase());
+        ChecksumHeaders {
+            sha1: header("X-Checksum-Sha1"),
+            sha256: header("X-Checksum-Sha256"),
+        }
+    }
+
+    /// The announced checksums as digests, if they include every one of the algorithms.
+    pub fn digests(&self, algorithms: &[HashAlgorithm]) -> Option<Digests> {
+        let digests = Digests { sha1: self.sha1.clone()?, sha256: self.sha256.clone(), sha512: None };
+        digests.covers(algorithms).then(|| digests.only(algorithms))
+    }
+
+    /// Checks the announced checksums against the digests of the downloaded bytes, which include SHA-256
+    /// if it was announced.
+    pub fn verify(&self, digests: &Digests) -> Result<(), DownloadError> {
+        if let Some(expected) = &self.sha1
+            && *expected != digests.sha1 {
+            return Err(DownloadError::ChecksumMismatch { algorithm: "SHA-1", expected: expected.clone(), computed: digests.sha1.clone() })
+        }
+        if let (Some(expected), Some(computed)) = (&self.sha256, &digests.sha256)
+            && expected != computed {
+            return Err(DownloadError::ChecksumMismatch { algorithm: "SHA-256", expected: expected.clone(), computed: computed.clone() })
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum DownloadError {
+    Http(Error),
+    /// The run has transferred more than `--max-total-bytes`
+    BudgetExceeded(u64),
+    /// The bytes don't match a checksum header of the response or a published checksum file
+    ChecksumMismatch { algorithm: &'static str, expected: String, computed: String },
+    /// A published checksum file or signature is missing, or the signature is bad
+    Unverified(String),
+    /// The artifact couldn't be copied to the mirror
+    Mirror(std::io::Error),
+    /// The artifact couldn't be written to or read from disk while downloading it
+    Spool(std::io::Error),
+    /// The newest build of a snapshot couldn't be read from its metadata
+    Snapshot(String),
+    /// A file standing in for a URL couldn't be read
+    Local { path: PathBuf, source: std::io::Error },
+    /// The server continued an interrupted download somewhere else than where it stopped
+    Misplaced,
+    /// The artifact isn't a readable jar or lacks the entries it should have
+    BadJar(String),
+    /// The run reached `--deadline` before the artifact was done
+    Deadline,
+    /// The response is larger than the limit, in bytes, for its kind
+    TooLarge(u64),
+    /// The response is a web page of this media type, not the file asked for
+    WebPage(String),
+}
+
+impl std::fmt::Display for DownloadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DownloadError::Http(why) => why.fmt(f),
+            DownloadError::BudgetExceeded(max) => write!(f, "download budget of {} bytes exceeded", max),
+            DownloadError::ChecksumMismatch { algorithm, expected, computed } => {
+                write!(f, "{} is {} but the server announced {}", algorithm, computed, expected)
+            }
+            DownloadError::Unverified(why) => why.fmt(f),
+            DownloadError::Mirror(why) => write!(f, "Couldn't copy to the mirror: {}", why),
+            DownloadError::Spool(why) => write!(f, "Couldn't keep the download on disk: {}", why),
+            DownloadError::Snapshot(why) => write!(f, "Couldn't resolve the snapshot: {}", why),
+            DownloadError::Local { path, source } => write!(f, "Couldn't read {}: {}", path.display(), source),
+            DownloadError::Misplaced => write!(f, "the server resumed the download at the wrong offset"),
+            DownloadError::BadJar(why) => write!(f, "Not a usable jar: {}", why),
+            DownloadError::Deadline => write!(f, "the run deadline was reached"),
+            DownloadError::TooLarge(max) => write!(f, "the response is larger than the limit of {} bytes", max),
+            DownloadError::WebPage(media_type) => {
+                write!(f, "the server answered with a web page ({}) instead of the file, check the URL", media_type)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DownloadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DownloadError::Http(why) => why.source(),
+            DownloadError::Mirror(why) | DownloadError::Spool(why) | DownloadError::Local { source: why, .. } => Some(why),
+            DownloadError::BudgetExceeded(_) | DownloadError::ChecksumMismatch { .. } | DownloadError::Unverified(_)
+                | DownloadError::Snapshot(_) | DownloadError::Misplaced | DownloadError::BadJar(_) | DownloadError::Deadline
+                | DownloadError::TooLarge(_) | DownloadError::WebPage(_) => None,
+        }
+    }
+}
+
+impl From<Error> for DownloadError {
+    fn from(why: Error) -> Self {
+        DownloadError::Http(why)
+    }
+}
+
+/// Looks up the digests of an artifact in the checksum files (`.sha1`, `.sha256`, ...) Maven publishes next to it
+/// and its size with a HEAD request, so it doesn't have to be downloaded. Returns `None` if any of them is
+/// unavailable, or if the HEAD response announces a different checksum.
+pub async fn published_checksums(connection: &Connection<'_>, url: &str, algorithms: &[HashAlgorithm]) -> Option<(Digests, usize)> {
+    let Connection { client, credentials, limiter, .. } = *connection;
+    let permit = limiter.acquire().await;
+    let mut digests = Digests { sha1: published_checksum(connection, url, HashAlgorithm::Sha1).await?, sha256: None, sha512: None };
+    if algorithms.contains(&HashAlgorithm::Sha256) {
+        digests.sha256 = Some(published_checksum(connection, url, HashAlgorithm::Sha256).await?);
+    }
+    if algorithms.contains(&HashAlgorithm::Sha512) {
+        digests.sha512 = Some(published_checksum(connection, url, HashAlgorithm::Sha512).await?);
+    }
+    if let Some(path) = connection.local_file(url) {
+        let size = std::fs::metadata(path).ok()?.len().try_into().ok()?;
+        return Some((digests, size))
+    }
+
+    crash::attempting(url);
+    let head = auth::authorize(credentials, client.head(url), url).send().await.ok()?.error_for_status().ok()?;
+    let announced = ChecksumHeaders::from_headers(head.headers());
+    if announced.sha1.is_some_and(|sha1| sha1 != digests.sha1)
+        || announced.sha256.is_some_and(|sha256| digests.get(HashAlgorithm::Sha256).is_some_and(|d| d != sha256)) {
+        return None
+    }
+    let size = head.headers().get(reqwest::header::CONTENT_LENGTH)?.to_str().ok()?.parse().ok()?;
+    permit.succeeded();
+
+    Some((digests, size))
+}
+
+/// Takes the digests of an artifact from the `X-Checksum-*` headers Nexus and Artifactory answer a HEAD request
+/// with, and its size from `Content-Length`, so it doesn't have to be downloaded. Returns `None` unless they cover
+/// the given algorithms.
+pub async fn announced_checksums(connection: &Connection<'_>, url: &str, algorithms: &[HashAlgorithm]) -> Option<(Digests, usize)> {
+    if connection.local_file(url).is_some() {
+        return None
+    }
+    let Connection { client, credentials, limiter, .. } = *connection;
+    let permit = limiter.acquire().await;
+    crash::attempting(url);
+    let head = auth::authorize(credentials, client.head(url), url).send().await.ok()?.error_for_status().ok()?;
+    let digests = ChecksumHeaders::from_headers(head.headers()).digests(algorithms)?;
+    let size = head.headers().get(reqwest::header::CONTENT_LENGTH)?.to_str().ok()?.parse().ok()?;
+    permit.succeeded();
+
+    Some((digests, size))
+}
+
+/// Cross-checks the SHA-1 of a downloaded artifact against the `.sha1` file published next to it and,
+/// given a keyring, its `.asc` signature. Artifacts without either fail the check.
+/// The contents are only needed, and have to be kept, with a keyring.
+pub async fn verify_published(connection: &Connection<'_>, url: &str, contents: Option<&Contents>, digests: &Digests,
+    keyring: Option<&Path>) -> Result<(), DownloadError> {
+    let published = published_checksum(connection, url, HashAlgorithm::Sha1).await
+        .ok_or_else(|| DownloadError::Unverified(format!("no SHA-1 is published at {}.sha1", url)))?;
+    if published != digests.sha1 {
+        return Err(DownloadError::ChecksumMismatch { algorithm: "SHA-1", expected: published, computed: digests.sha1.clone() })
+    }
+
+    if let Some(keyring) = keyring {
+        let signature_url = format!("{}.asc", url);
+        let signature = fetch(connection, &signature_url, connection.limits.max_document_size).await.map_err(|why| DownloadError::Unverified(format!("Couldn't download signature: {}", why)))?;
+        let contents = contents.expect("contents are kept when checking signatures");
+        signature::verify(keyring, &signature, contents.path()).await
+            .map_err(|why| DownloadError::Unverified(format!("Bad signature: {}", why)))?;
+    }
+
+    Ok(())
+}
+
+async fn published_checksum(connection: &Connection<'_>, url: &str, algorithm: HashAlgorithm) -> Option<String> {
+    let checksum_url = format!("{}.{}", url, algorithm.name());
+    let contents = fetch(connection, &checksum_url, connection.limits.max_document_size).await.ok()?;
+    let contents = std::str::from_utf8(&contents).ok()?;
+    // Some repositories put the file name after the hash
+    let digest = contents.split_whitespace().next()?.to_ascii_lowercase();
+    let length = match algorithm {
+        HashAlgorithm::Sha1 => 40,
+        HashAlgorithm::Sha256 => 64,
+        HashAlgorithm::Sha512 => 128,
+    };
+    (digest.len() == length && digest.chars().all(|c| c.is_ascii_hexdigit())).then_some(digest)
+}
+
+/// Downloads a small document like Maven metadata, revalidating a cached response instead if there is one.
+pub async fn fetch_cached(connection: &Connection<'_>, url: &str) -> Result<bytes::Bytes, DownloadError> {
+    let max_size = connection.limits.max_document_size;
+    if let Some(path) = connection.local_file(url) {
+        let size = std::fs::metadata(&path).map_err(|source| DownloadError::Local { path: path.clone(), source })?.len();
+        return if size > max_size { Err(DownloadError::TooLarge(max_size)) } else { read_local(&path) }
+    }
+
+    let Connection { client, credentials, cache, retry, .. } = *connection;
+    // Small enough to be read up front, so a 304 can always be answered
+    let cached = cache.and_then(|cache| cache.get(url)).and_then(|cached| Some((cached.body()?, cached)));
+    retry.run(|| async {
+        crash::attempting(url);
+        let mut request = auth::authorize(credentials, client.get(url), url);
+        if let Some((_, cached)) = &cached {
+            request = cached.conditional(request);
+        }
+        let response = request.send().await?;
+        if let Some((body, _)) = &cached
+            && response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(Ok(body.clone()))
+        }
+
+        let response = response.error_for_status()?;
+        let headers = response.headers().clone();
+        // Not worth another attempt, the same URL would answer the same
+        if let Err(why) = check_response(&headers, max_size) {
+            return Ok(Err(why))
+        }
+        let body = read_limited(response, max_size, connection.budget).await?;
+        if let (Some(cache), Ok(body)) = (cache, &body) {
+            cache.put(url, &headers, body);
+        }
+        Ok(body)
+    }).await.map_err(DownloadError::Http)?
+}
+
+/// Downloads a file whole that isn't worth caching or retrying, such as a checksum file, as long as it's no larger
+/// than `max_size`, or reads it from disk if it's local.
+pub async fn fetch(connection: &Connection<'_>, url: &str, max_size: u64) -> Result<bytes::Bytes, DownloadError> {
+    if let Some(path) = connection.local_file(url) {
+        let size = std::fs::metadata(&path).map_err(|source| DownloadError::Local { path: path.clone(), source })?.len();
+        return if size > max_size { Err(DownloadError::TooLarge(max_size)) } else { read_local(&path) }
+    }
+
+    crash::attempting(url);
+    let response = auth::authorize(connection.credentials, connection.client.get(url), url).send().await?.error_for_status()?;
+    check_response(response.headers(), max_size)?;
+    read_limited(response, max_size, connection.budget).await?
+}
+
+/// A response whose body is only read once needed, or a local file standing in for one.
+pub enum Response<'a> {
+    /// With the largest body to accept, and the budget to charge it to
+    Remote(reqwest::Response, u64, &'a DownloadBudget),
+    Local { headers: reqwest::header::HeaderMap, body: bytes::Bytes },
+}
+
+impl Response<'_> {
+    pub fn headers(&self) -> &reqwest::header::HeaderMap {
+        match self {
+            Response::Remote(response, ..) => response.headers(),
+            Response::Local { headers, .. } => headers,
+        }
+    }
+
+    pub async fn bytes(self) -> Result<bytes::Bytes, DownloadError> {
+        match self {
+            Response::Remote(response, max_size, budget) => {
+                let response = response.error_for_status()?;
+                check_response(response.headers(), max_size)?;
+                read_limited(response, max_size, budget).await?
+            }
+            Response::Local { body, .. } => Ok(body),
+        }
+    }
+}
+
+/// Requests a file that isn't worth caching, such as the authlib-injector, or reads it from disk if it's local.
+/// The request is retried while upstream is throttling.
+pub async fn request<'a>(connection: &Connection<'a>, url: &str) -> Result<Response<'a>, DownloadError> {
+    if let Some(path) = connection.local_file(url) {
+        return Ok(Response::Local { headers: reqwest::header::HeaderMap::new(), body: read_local(&path)? })
+    }
+
+    let Connection { client, credentials, retry, .. } = *connection;
+    let response = retry.run(|| async {
+        crash::attempting(url);
+        let response = auth::authorize(credentials, client.get(url), url).send().await?;
+        if concurrency::is_throttling(response.status()) {
+            return Err(response.error_for_status().unwrap_err())
+        }
+        Ok(response)
+    }).await?;
+    Ok(Response::Remote(response, connection.limits.max_artifact_size, connection.budget))
+}
+
+/// Points the URL of a snapshot file (`.../1.0-SNAPSHOT/lib-1.0-SNAPSHOT.jar`) to its newest build, as listed
+/// in the `maven-metadata.xml` next to it.
+pub async fn resolve_snapshot(connection: &Connection<'_>, url: &str, version: &str, packaging: &maven::Packaging)
+    -> Result<String, DownloadError> {
+    let (directory, file) = url.rsplit_once('/').unwrap_or(("", url));
+    let metadata_url = format!("{}/maven-metadata.xml", directory);
+    let body = fetch_cached(connection, &metadata_url).await?;
+
+    let metadata = maven::declare_unknown_prefixes(String::from_utf8_lossy(&body).into_owned());
+    let doc = maven::parse(&metadata).map_err(|why| DownloadError::Snapshot(why.to_string()))?;
+    let build = maven::read_snapshot_version(&doc, version, packaging).map_err(|why| DownloadError::Snapshot(why.to_string()))?;
+
+    Ok(format!("{}/{}", directory, file.replace(version, &build)))
+}
+
+/// Checks that a URL responds successfully when requested like any other file, leaving its body unread.
+pub async fn probe_url(connection: &Connection<'_>, url: &str) -> Result<(), DownloadError> {
+    if let Response::Remote(response, ..) = request(connection, url).await? {
+        response.error_for_status()?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn downloaded(body: &[u8], checksums: ChecksumHeaders) -> Downloaded {
+        let digests = Digests::compute(body, &[HashAlgorithm::Sha256]);
+        Downloaded { digests, size: body.len(), checksums, last_modified: None, contents: None }
+    }
+
+    #[test]
+    fn refuses_bytes_not_matching_the_checksum_headers() {
+        let announced = Digests::compute(b"what the repository indexed", &[HashAlgorithm::Sha256]);
+        let sha1 = ChecksumHeaders { sha1: Some(announced.sha1.clone()), sha256: None };
+        let why = downloaded(b"what it served", sha1).verified_digests(&[]).unwrap_err();
+        assert!(matches!(why, DownloadError::ChecksumMismatch { algorithm: "SHA-1", expected, .. } if expected == announced.sha1));
+
+        let sha256 = ChecksumHeaders { sha1: None, sha256: announced.sha256.clone() };
+        let why = downloaded(b"what it served", sha256).verified_digests(&[HashAlgorithm::Sha256]).unwrap_err();
+        assert!(matches!(why, DownloadError::ChecksumMismatch { algorithm: "SHA-256", .. }));
+    }
+
+    #[test]
+    fn takes_the_computed_digests_when_the_headers_agree() {
+        let body = b"authlib";
+        let computed = Digests::compute(body, &[HashAlgorithm::Sha256]);
+        let checksums = ChecksumHeaders { sha1: Some(computed.sha1.clone()), sha256: computed.sha256.clone() };
+        let digests = downloaded(body, checksums).verified_digests(&[]).unwrap();
+        assert_eq!(digests, Digests { sha1: computed.sha1, sha256: None, sha512: None });
+    }
+}
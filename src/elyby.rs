@@ -0,0 +1,67 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Listing the patched authlib builds through an Ely.by builds API instead of Maven metadata.
+//!
+//! The API answers with a JSON array of builds, or an object listing them under `builds`:
+//!
+//! ```json
+//! [{ "version": "5.17.31-ely.2", "url": "https://...", "releasedAt": "2025-01-31T12:00:00Z" }]
+//! ```
+//!
+//! Only `version` is required. Builds without a `url` are downloaded from the authlib URL template.
+
+use crate::download::{self, Connection, DownloadError};
+use chrono::{DateTime, Utc};
+
+/// A build as listed by the API.
+pub struct Build {
+    pub version: String,
+    pub url: Option<String>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BuildsApiError {
+    #[error(transparent)]
+    Download(#[from] DownloadError),
+    #[error("Malformed builds list: {0}")]
+    Malformed(String),
+}
+
+/// Lists the builds announced by the API at `url`.
+pub async fn list_builds(connection: &Connection<'_>, url: &str) -> Result<Vec<Build>, BuildsApiError> {
+    let body = download::fetch_cached(connection, url).await?;
+    let listed: serde_json::Value = serde_json::from_slice(&body).map_err(|why| BuildsApiError::Malformed(why.to_string()))?;
+    let builds = listed.as_array().or_else(|| listed["builds"].as_array())
+        .ok_or_else(|| BuildsApiError::Malformed("expected an array of builds".to_string()))?;
+
+    builds.iter()
+        .map(|build| {
+            let version = build["version"].as_str()
+                .ok_or_else(|| BuildsApiError::Malformed(format!("build without a version: {}", build)))?;
+            let released_at = match build["releasedAt"].as_str() {
+                Some(time) => Some(DateTime::parse_from_rfc3339(time)
+                    .map_err(|why| BuildsApiError::Malformed(format!("releasedAt of {}: {}", version, why)))?
+                    .with_timezone(&Utc)),
+                None => None,
+            };
+            Ok(Build { version: version.to_string(), url: build["url"].as_str().map(str::to_string), released_at })
+        })
+        .collect()
+}
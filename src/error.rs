@@ -0,0 +1,125 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::download::DownloadError;
+use crate::elyby::BuildsApiError;
+use crate::exit::ExitStatus;
+use crate::maven::MetadataError;
+use crate::search::SearchError;
+use crate::upload::UploadError;
+use std::path::PathBuf;
+
+/// The reason a run ended unsuccessfully.
+#[derive(Debug, thiserror::Error)]
+pub enum GeneratorError {
+    #[error("Invalid --rewrite-url pattern: {0}")]
+    RewritePattern(#[from] regex::Error),
+    #[error("Invalid --include or --exclude pattern: {0}")]
+    VersionPattern(#[source] regex::Error),
+    #[error("Invalid --target-version-rule pattern: {0}")]
+    TargetVersionRule(#[source] regex::Error),
+    #[error("Invalid download URL format string `{template}`: {why}")]
+    UrlTemplate { template: String, why: String },
+    #[error("Couldn't create HTTP client: {0}")]
+    HttpClient(#[source] reqwest::Error),
+    #[error("Couldn't read CA certificates from {}: {why}", path.display())]
+    CaCertificates { path: PathBuf, why: String },
+    #[error("Couldn't read exclusions file {}: {source}", path.display())]
+    Exclusions { path: PathBuf, source: std::io::Error },
+    #[error("Couldn't {action} {}: {source}", path.display())]
+    Io { action: &'static str, path: PathBuf, source: std::io::Error },
+    #[error("Couldn't download {what}: {source}")]
+    Download { what: String, source: DownloadError },
+    #[error("Couldn't list versions through the search API: {0}")]
+    Search(#[from] SearchError),
+    #[error("Couldn't list builds through the Ely.by API: {0}")]
+    BuildsApi(#[from] BuildsApiError),
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+    #[error("Couldn't parse {location}: {source}")]
+    Document { location: String, source: serde_json::Error },
+    #[error("Couldn't merge the output into {}: {why}", path.display())]
+    Merge { path: PathBuf, why: String },
+    #[error("Couldn't retrieve authlib-injector, refusing to write output: {0}")]
+    Injector(#[source] DownloadError),
+    #[error("Refusing to write output, authlib-injector SHA-256 is {computed} but its API announced {expected}")]
+    InjectorChecksum { expected: String, computed: String },
+    #[error("Refusing to write output, no SHA-256 is published for authlib-injector {0} (--allow-unverified-injector accepts it)")]
+    UnverifiedInjector(String),
+    #[error("Refusing to write output, download budget of {0} bytes exceeded")]
+    BudgetExceeded(u64),
+    #[error("Refusing to write output, hash drift detected for {} (--allow-repin accepts the new hashes)", .0.join(", "))]
+    HashDrift(Vec<String>),
+    #[error("Couldn't download {}, output is missing them", .0.join(", "))]
+    PartialFailure(Vec<String>),
+    #[error("Refusing to write output, couldn't download {}", .0.join(", "))]
+    FailedDownloads(Vec<String>),
+    #[error("Refusing to write output with only {count} version(s), --min-versions is {min}")]
+    TooFewVersions { count: usize, min: usize },
+    #[error("Entries not matching their artifacts: {0}")]
+    Mismatched(usize),
+    #[error("Entries that couldn't be checked: {0}")]
+    Unverifiable(usize),
+    #[error("Can't upload: {0}")]
+    UploadSetup(String),
+    #[error("Couldn't upload {name}: {source}")]
+    Upload { name: String, source: UploadError },
+    #[error("Hook `{command}` failed: {why}")]
+    Hook { command: String, why: String },
+    #[error("Refusing to write output, it doesn't match the schema: {}", .0.join("; "))]
+    SchemaViolation(Vec<String>),
+    #[error("Couldn't sign output: {0}")]
+    Signing(String),
+    #[error("Bad signature of {location}: {why}")]
+    BadSignature { location: String, why: String },
+    #[error("Refusing to bundle {0}")]
+    Unbundleable(String),
+    #[error("Invalid bundle {}: {why}", path.display())]
+    InvalidBundle { path: PathBuf, why: String },
+    #[error("Can't migrate: {0}")]
+    Migration(String),
+    #[error("Couldn't select the versions to include: {0}")]
+    Selection(String),
+    #[error("OS keyring: {0}")]
+    Keyring(String),
+    #[error("Upstream is stale")]
+    Stale,
+}
+
+impl GeneratorError {
+    pub fn status(&self) -> ExitStatus {
+        match self {
+            GeneratorError::RewritePattern(_) | GeneratorError::VersionPattern(_) | GeneratorError::TargetVersionRule(_)
+                | GeneratorError::UrlTemplate { .. }
+                | GeneratorError::HttpClient(_)
+                | GeneratorError::CaCertificates { .. } | GeneratorError::Exclusions { .. } | GeneratorError::UploadSetup(_)
+                | GeneratorError::Keyring(_) | GeneratorError::Migration(_) => {
+                ExitStatus::Config
+            }
+            GeneratorError::Download { .. } | GeneratorError::Search(_) | GeneratorError::BuildsApi(_) | GeneratorError::Injector(_)
+                | GeneratorError::Unverifiable(_) => ExitStatus::UpstreamUnreachable,
+            GeneratorError::PartialFailure(_) => ExitStatus::PartialFailure,
+            GeneratorError::HashDrift(_) | GeneratorError::Mismatched(_) | GeneratorError::InjectorChecksum { .. }
+                | GeneratorError::UnverifiedInjector(_) | GeneratorError::BadSignature { .. } | GeneratorError::Unbundleable(_)
+                | GeneratorError::InvalidBundle { .. } => {
+                ExitStatus::VerificationFailed
+            }
+            _ => ExitStatus::Failure,
+        }
+    }
+}
@@ -0,0 +1,102 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! How a run ends: exit statuses and the final error.
+
+use crate::logging;
+use serde::Serialize;
+
+/// Exit statuses of a run. These are part of the command line interface and must never be renumbered.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// Success, for generation runs this means output was written
+    Changed = 0,
+    /// Any failure not covered by a more specific status
+    Failure = 1,
+    /// Invalid arguments or configuration files (also used by the argument parser itself)
+    Config = 2,
+    /// The Maven metadata or authlib-injector couldn't be fetched
+    UpstreamUnreachable = 3,
+    /// Output was written, but some versions couldn't be downloaded and are missing from it
+    PartialFailure = 4,
+    /// An artifact doesn't match its recorded or announced hash
+    VerificationFailed = 5,
+    /// Upstream hasn't changed since the previous run or the output came out identical, it was left untouched
+    Unchanged = 6,
+}
+
+/// Exit status of a run that panicked, same as the default for Rust programs.
+pub const PANIC_EXIT_CODE: i32 = 101;
+
+/// Listed in `--help`.
+pub const EXIT_STATUS_HELP: &str = "\
+Exit status:
+  0    success (output written)
+  1    failure
+  2    invalid arguments or configuration
+  3    Maven metadata or authlib-injector unreachable
+  4    output written, but some versions couldn't be downloaded
+  5    an artifact doesn't match its recorded or announced hash
+  6    upstream or output unchanged, output left untouched
+  101  crash";
+
+impl ExitStatus {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// Machine-readable name, used by `--error-format json`.
+    pub fn name(self) -> &'static str {
+        match self {
+            ExitStatus::Changed => "changed",
+            ExitStatus::Failure => "failure",
+            ExitStatus::Config => "config",
+            ExitStatus::UpstreamUnreachable => "upstream_unreachable",
+            ExitStatus::PartialFailure => "partial_failure",
+            ExitStatus::VerificationFailed => "verification_failed",
+            ExitStatus::Unchanged => "unchanged",
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum ErrorFormat {
+    Text,
+    /// A single JSON object with `error`, `message` and `exitCode`
+    Json,
+}
+
+/// Prints the final error of a run to stderr.
+pub fn print_error(format: ErrorFormat, name: &str, message: &str, code: i32) {
+    match format {
+        ErrorFormat::Text => logging::write_line(&logging::redact(message)),
+        ErrorFormat::Json => {
+            let report = ErrorReport { error: name, message: logging::redact(message), exit_code: code };
+            logging::write_line(&serde_json::to_string(&report).expect("reports are always serializable"))
+        }
+    }
+}
+
+/// What `--error-format json` prints.
+#[derive(Serialize)]
+struct ErrorReport<'a> {
+    error: &'a str,
+    message: String,
+    #[serde(rename = "exitCode")]
+    exit_code: i32,
+}
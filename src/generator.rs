@@ -0,0 +1,2644 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The generator itself: resolving versions from upstream, hashing artifacts and writing the metadata document.
+
+use crate::auth::RepositoryCredentials;
+use crate::cache::HttpCache;
+use crate::changelog;
+use crate::concurrency::{AdaptiveLimiter, DownloadBudget};
+use crate::crash;
+use crate::dashboard::{CycleReport, SharedDashboard, VersionReport};
+use crate::document::{self, Difference};
+use crate::download::{self, ChecksumHeaders, Connection, DownloadError, ResponseLimits, RetryPolicy};
+use crate::elyby;
+use crate::local::LocalRepository;
+use crate::logging;
+use crate::error::GeneratorError;
+use crate::hashing::{Digests, HashAlgorithm};
+use crate::exit::ExitStatus;
+use crate::github;
+use crate::hooks;
+use crate::gradle;
+use crate::injector;
+use crate::jar;
+use crate::maven::{self, MavenMetadata, Packaging};
+use crate::merge;
+use crate::metrics::{RunReport, SharedMetrics};
+use crate::minecraft;
+use crate::mirror::{self, Mirror};
+use crate::notify::{self, WebhookFormat};
+use crate::prism;
+use crate::model::{self, ArtifactEntry, ElyEndpoints, Extra, Extras, FormatVersion, InjectorEntry, LibraryEntry, MetadataFile, Overrides};
+use crate::pins::Pins;
+use crate::progress::Progress;
+use crate::reporting::ErrorReporter;
+use crate::schema;
+use crate::search::{self, SearchApi};
+use crate::signature;
+use crate::state::{HashDrift, State};
+use crate::store::ArtifactStore;
+use crate::telemetry::{Span, Tracer};
+use crate::upload::{self, Uploader};
+use crate::version::Version;
+use bytes::Bytes;
+use chrono::{DateTime, SecondsFormat, Utc};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use futures::StreamExt;
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::Instrument;
+
+pub use crate::version::compare_versions;
+use crate::version::newest_first;
+
+pub const DEFAULT_ARTIFACT: &str = "by.ely:authlib";
+pub const DEFAULT_VANILLA_URL_FORMAT: &str = "https://libraries.minecraft.net/com/mojang/authlib/{}/authlib-{}.jar";
+/// Mojang's Maven repository, where the vanilla builds of every overridable library are
+pub const MOJANG_LIBRARIES_URL: &str = "https://libraries.minecraft.net";
+pub const DEFAULT_VERSION_MANIFEST_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+pub const DEFAULT_ELY_AUTHSERVER_URL: &str = "https://authserver.ely.by/auth";
+pub const DEFAULT_ELY_SESSIONSERVER_URL: &str = "https://authserver.ely.by/session";
+pub const DEFAULT_ELY_SKINS_URL: &str = "https://skinsystem.ely.by";
+pub const DEFAULT_MAX_CONCURRENT: usize = 4;
+pub const DEFAULT_RETRIES: u32 = 3;
+pub const DEFAULT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+pub const DEFAULT_QUARANTINE_AFTER: u32 = 3;
+pub const DEFAULT_QUARANTINE_COOLDOWN: Duration = Duration::from_secs(24 * 60 * 60);
+pub const DEFAULT_GITHUB_RELEASE_TAG: &str = "latest";
+pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Everything a run is configured by. The command line options of the binary map onto these fields one to one,
+/// see their descriptions in `--help`.
+pub struct GeneratorConfig {
+    /// Where the versions of the patched authlib are listed
+    pub source: VersionSource,
+    /// Builds API listing the patched authlib with `source` [`VersionSource::ElybyApi`]
+    pub elyby_api_url: Option<String>,
+    /// URL to Maven metadata XML of the provider's patched authlib
+    pub metadata_url: String,
+    /// Patched authlib download URL format string, see [`expand_url_template`]
+    pub authlib_download_url_format: String,
+    /// Metadata URLs and download URL format strings of repositories to try, in order, when the primary one fails
+    pub fallback_repositories: Vec<(String, String)>,
+    /// Read the metadata of every repository and list the versions of all of them, not only of the first one that
+    /// could be read
+    pub union_repositories: bool,
+    /// Which repository's artifact is taken when several list a version, by index, 0 being the primary repository
+    /// and the fallback ones following in order. Those left out come after, in that order
+    pub repository_priority: Vec<usize>,
+    pub injector_download_url: String,
+    /// What to do if authlib-injector can't be retrieved
+    pub injector: InjectorPolicy,
+    /// authlib-injector API to resolve the latest build from, falling back to `injector_download_url`
+    pub injector_api: Option<String>,
+    /// `OWNER/REPO` on GitHub to resolve the latest build from the releases of, through `github_api_url`
+    pub injector_github: Option<String>,
+    /// Accept an authlib-injector resolved through `injector_api` or `injector_github` that no SHA-256 is published
+    /// for, instead of failing
+    pub allow_unverified_injector: bool,
+    /// Further authlib-injector builds to list in the extras, by channel name
+    pub injector_channels: Vec<(String, InjectorChannel)>,
+    /// Where to write the document, `-` for stdout
+    pub output_file: String,
+    /// Markdown file to append what every run changed in the output to
+    pub changelog: Option<PathBuf>,
+    /// Directory to also write the overrides into as PrismLauncher meta components
+    pub meta_dir: Option<PathBuf>,
+    /// Further files to write the document to, each in its own layout
+    pub extra_outputs: Vec<(FormatVersion, PathBuf)>,
+    /// Download URL format strings for specific base versions
+    pub url_template_override: Vec<(String, String)>,
+    /// Only generate entries for these base or full versions, all of them when empty
+    pub versions: Vec<String>,
+    /// Maven `group:artifact` of the patched authlib
+    pub artifact: String,
+    /// Classifier and extension of the patched authlib's file
+    pub packaging: Packaging,
+    /// `group:artifact` of the library the patched authlib overrides
+    pub override_key: String,
+    /// Further libraries to generate overrides for in the same run
+    pub libraries: Vec<LibrarySource>,
+    pub latest_alias: bool,
+    pub search_fallback: Option<SearchApi>,
+    pub search_server: Option<String>,
+    pub search_repository: Option<String>,
+    pub vanilla_fallback: Vec<String>,
+    pub vanilla_url_format: String,
+    /// List the Minecraft versions shipping every overridden version, read from `version_manifest_url`
+    pub minecraft_versions: bool,
+    pub version_manifest_url: String,
+    pub ely_endpoints: bool,
+    pub ely_authserver_url: String,
+    pub ely_sessionserver_url: String,
+    pub ely_skins_url: String,
+    pub extra: Vec<(String, String)>,
+    pub extra_artifact: Vec<(String, String)>,
+    /// Pattern and replacement pairs applied to emitted URLs, in order
+    pub rewrite_url: Vec<(String, String)>,
+    pub parity_reference: Option<String>,
+    pub output_mode: Option<u32>,
+    pub output_owner: Option<(Option<u32>, Option<u32>)>,
+    pub warn_stale: Option<Duration>,
+    pub fail_stale: bool,
+    /// Refuse to write output if any artifact couldn't be downloaded, instead of leaving its version out
+    pub fail_on_error: bool,
+    /// Refuse to write output with fewer versions of the main library
+    pub min_versions: Option<usize>,
+    pub max_concurrent: usize,
+    /// Minimum time between the starts of two artifact downloads, on top of the concurrency limit
+    pub request_interval: Option<Duration>,
+    /// Retries of failed metadata, artifact and authlib-injector requests
+    pub retries: u32,
+    pub retry_backoff: Duration,
+    pub no_retry_jitter: bool,
+    pub trust_checksum_headers: bool,
+    pub checksum_sidecars: bool,
+    /// Take artifact URLs, sizes and digests from the Gradle module metadata published next to them
+    pub gradle_modules: bool,
+    /// Fail artifacts whose SHA-1 doesn't match their published `.sha1` file
+    pub verify_published: bool,
+    /// Keyring to also check published `.asc` signatures against, with `verify_published`
+    pub keyring: Option<PathBuf>,
+    /// Leave out downloaded artifacts that aren't readable jars with entries under the package of their library
+    pub check_jars: bool,
+    /// Entry name prefix the artifacts of the main library must have with `check_jars`, instead of the path of
+    /// `override_key`
+    pub expected_entry: Option<String>,
+    /// Local Maven repository to read metadata, artifacts, checksums and signatures from instead of downloading
+    /// them, where it has them
+    pub local_repo: Option<PathBuf>,
+    /// Sent with metadata, search, artifact, checksum and signature requests to private repositories
+    pub credentials: Vec<RepositoryCredentials>,
+    /// Algorithms to emit digests of, SHA-1 is always included
+    pub hashes: Vec<HashAlgorithm>,
+    pub max_total_bytes: Option<u64>,
+    /// Largest Maven metadata, or other document read whole, to download, in bytes
+    pub max_metadata_size: u64,
+    /// Largest artifact to download, in bytes
+    pub max_artifact_size: u64,
+    /// Stop downloading artifacts this long after the run started, leaving out those that aren't done
+    pub deadline: Option<Duration>,
+    pub quarantine_after: u32,
+    pub quarantine_cooldown: Duration,
+    pub no_resume: bool,
+    pub store_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    /// Directory to copy artifacts into and the URL it is served at, the output pointing there instead of upstream
+    pub mirror: Option<(PathBuf, String)>,
+    /// `s3://bucket/prefix` to upload the output and the mirror to
+    pub upload: Option<String>,
+    /// S3-compatible endpoint to upload to instead of AWS
+    pub upload_endpoint: Option<String>,
+    /// Access key ID and secret access key to upload with, instead of those of the environment or the AWS profile
+    pub upload_credentials: Option<(String, String)>,
+    /// How long the output can point to the uploaded mirror by presigned URLs, for buckets that aren't public
+    pub presign_expiry: Option<Duration>,
+    /// URL to PUT the output to, its companion files going next to it
+    pub put_url: Option<String>,
+    /// `OWNER/REPO` to attach the output to a release of, as assets
+    pub github_repository: Option<String>,
+    /// Tag of the release, created along with it if it doesn't exist
+    pub github_release_tag: String,
+    pub github_token: Option<String>,
+    /// GitHub REST API to publish through, for GitHub Enterprise Server
+    pub github_api_url: String,
+    /// Previously generated document to reuse entries from, `Some(None)` for the output file
+    pub previous: Option<Option<String>>,
+    /// Currently published document to reuse entries from without `previous`, and to compare the output with
+    pub baseline_url: Option<String>,
+    /// Leave the output alone and return [`ExitStatus::Unchanged`] when it's identical to the baseline
+    pub only_if_changed: bool,
+    /// Hand-maintained document to overlay the generated one on, keeping the entries and keys that weren't generated
+    pub merge: Option<PathBuf>,
+    pub exclusions: Option<PathBuf>,
+    pub min_version: Option<String>,
+    /// Patterns full versions must match one of, any version when empty
+    pub include: Vec<String>,
+    /// Patterns of full versions to leave out
+    pub exclude: Vec<String>,
+    /// Patterns of full versions and the version they are an override for, see [`target_version`]
+    pub target_version_rules: Vec<(String, String)>,
+    /// Generate entries for `-SNAPSHOT` versions too, pointing to their newest timestamped build
+    pub include_snapshots: bool,
+    pub state: Option<PathBuf>,
+    /// File pinning the SHA-1 of every version hashed, checked like the hashes of `state`
+    pub pin_file: Option<PathBuf>,
+    /// Accept artifacts whose hash drifted from the recorded one, recording the new hash, instead of failing
+    pub allow_repin: bool,
+    pub alert_webhook: Option<String>,
+    /// Webhook to POST the differences to when the output changes
+    pub notify_webhook: Option<String>,
+    /// Shell commands to run once the output has been written
+    pub post_hooks: Vec<String>,
+    /// Shell commands to run for every added, removed or changed version once the output has been written
+    pub version_hooks: Vec<String>,
+    pub notify_format: WebhookFormat,
+    /// Layout to write the output in
+    pub format_version: FormatVersion,
+    /// Write the output as minified JSON
+    pub compact: bool,
+    /// Also write the output gzipped, next to it
+    pub gzip: bool,
+    /// Print a table of every version the run went through to stderr when it's done
+    pub summary_table: bool,
+    /// Write a JSON report of the run here, for automation
+    pub report: Option<PathBuf>,
+    /// Color the table with ANSI escapes
+    pub color: bool,
+    /// PEM-encoded Ed25519 private key to sign the output with
+    pub signing_key: Option<String>,
+    /// Where the output is served, for notifications
+    pub output_url: Option<String>,
+    pub force: bool,
+}
+
+impl GeneratorConfig {
+    /// Creates a configuration with the same defaults as the command line.
+    pub fn new(metadata_url: impl Into<String>, authlib_download_url_format: impl Into<String>,
+        injector_download_url: impl Into<String>, output_file: impl Into<String>) -> GeneratorConfig {
+        GeneratorConfig {
+            source: VersionSource::Maven,
+            elyby_api_url: None,
+            metadata_url: metadata_url.into(),
+            authlib_download_url_format: authlib_download_url_format.into(),
+            fallback_repositories: Vec::new(),
+            union_repositories: false,
+            repository_priority: Vec::new(),
+            injector_download_url: injector_download_url.into(),
+            injector: InjectorPolicy::Required,
+            injector_api: None,
+            injector_github: None,
+            allow_unverified_injector: false,
+            injector_channels: Vec::new(),
+            output_file: output_file.into(),
+            changelog: None,
+            meta_dir: None,
+            extra_outputs: Vec::new(),
+            url_template_override: Vec::new(),
+            versions: Vec::new(),
+            artifact: DEFAULT_ARTIFACT.to_string(),
+            packaging: Packaging::default(),
+            override_key: model::AUTHLIB.to_string(),
+            libraries: Vec::new(),
+            latest_alias: false,
+            search_fallback: None,
+            search_server: None,
+            search_repository: None,
+            vanilla_fallback: Vec::new(),
+            vanilla_url_format: DEFAULT_VANILLA_URL_FORMAT.to_string(),
+            minecraft_versions: false,
+            version_manifest_url: DEFAULT_VERSION_MANIFEST_URL.to_string(),
+            ely_endpoints: false,
+            ely_authserver_url: DEFAULT_ELY_AUTHSERVER_URL.to_string(),
+            ely_sessionserver_url: DEFAULT_ELY_SESSIONSERVER_URL.to_string(),
+            ely_skins_url: DEFAULT_ELY_SKINS_URL.to_string(),
+            extra: Vec::new(),
+            extra_artifact: Vec::new(),
+            rewrite_url: Vec::new(),
+            parity_reference: None,
+            output_mode: None,
+            output_owner: None,
+            warn_stale: None,
+            fail_stale: false,
+            fail_on_error: false,
+            min_versions: None,
+            max_concurrent: DEFAULT_MAX_CONCURRENT,
+            request_interval: None,
+            retries: DEFAULT_RETRIES,
+            retry_backoff: DEFAULT_RETRY_BACKOFF,
+            no_retry_jitter: false,
+            trust_checksum_headers: false,
+            checksum_sidecars: false,
+            gradle_modules: false,
+            verify_published: false,
+            keyring: None,
+            check_jars: false,
+            expected_entry: None,
+            local_repo: None,
+            credentials: Vec::new(),
+            hashes: vec![HashAlgorithm::Sha1],
+            max_total_bytes: None,
+            max_metadata_size: ResponseLimits::default().max_document_size,
+            max_artifact_size: ResponseLimits::default().max_artifact_size,
+            deadline: None,
+            quarantine_after: DEFAULT_QUARANTINE_AFTER,
+            quarantine_cooldown: DEFAULT_QUARANTINE_COOLDOWN,
+            no_resume: false,
+            store_dir: None,
+            cache_dir: None,
+            mirror: None,
+            upload: None,
+            upload_endpoint: None,
+            upload_credentials: None,
+            presign_expiry: None,
+            put_url: None,
+            github_repository: None,
+            github_release_tag: DEFAULT_GITHUB_RELEASE_TAG.to_string(),
+            github_token: None,
+            github_api_url: DEFAULT_GITHUB_API_URL.to_string(),
+            previous: None,
+            baseline_url: None,
+            only_if_changed: false,
+            merge: None,
+            exclusions: None,
+            min_version: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            target_version_rules: Vec::new(),
+            include_snapshots: false,
+            state: None,
+            pin_file: None,
+            allow_repin: false,
+            alert_webhook: None,
+            notify_webhook: None,
+            post_hooks: Vec::new(),
+            version_hooks: Vec::new(),
+            notify_format: WebhookFormat::default(),
+            format_version: FormatVersion::V1,
+            compact: false,
+            gzip: false,
+            summary_table: false,
+            report: None,
+            color: false,
+            signing_key: None,
+            output_url: None,
+            force: false,
+        }
+    }
+
+    /// Whether the document is written to stdout rather than to a file.
+    pub fn writes_to_stdout(&self) -> bool {
+        self.output_file == "-"
+    }
+
+    /// The signing key and the publishing credentials, which may have been read from files or the OS keyring rather
+    /// than given as arguments, to be redacted.
+    pub fn secrets(&self) -> Vec<String> {
+        let upload_key = self.upload_credentials.as_ref().map(|(_, secret_access_key)| secret_access_key);
+        [self.signing_key.as_ref(), self.github_token.as_ref(), upload_key].into_iter().flatten().cloned().collect()
+    }
+}
+
+/// A library to generate overrides for besides the patched authlib. Only its newest build of every
+/// base version gets an entry, exclusions, version filters, `versions` and the vanilla fallback don't apply to it.
+#[derive(Clone)]
+pub struct LibrarySource {
+    /// `group:artifact` of the library the entries override
+    pub override_key: String,
+    /// URL to Maven metadata XML of the replacement
+    pub metadata_url: String,
+    /// Download URL format string, see [`expand_url_template`]
+    pub url_template: String,
+    /// Maven `group:artifact` of the replacement
+    pub artifact: String,
+}
+
+/// Picks the artifacts to actually download out of everything planned, or says why it couldn't.
+type Selector = dyn Fn(Vec<PlannedArtifact>) -> Result<Vec<PlannedArtifact>, String>;
+
+/// Generates a metadata document according to a [`GeneratorConfig`].
+pub struct MetadataGenerator {
+    config: GeneratorConfig,
+    client: reqwest::Client,
+    tracer: Tracer,
+    reporter: ErrorReporter,
+    selector: Option<Box<Selector>>,
+    metrics: Option<SharedMetrics>,
+    dashboard: Option<SharedDashboard>,
+}
+
+impl MetadataGenerator {
+    pub fn new(config: GeneratorConfig, client: reqwest::Client) -> MetadataGenerator {
+        MetadataGenerator {
+            config,
+            client,
+            tracer: Tracer::new(None),
+            reporter: ErrorReporter::new(None),
+            selector: None,
+            metrics: None,
+            dashboard: None,
+        }
+    }
+
+    /// Records the spans of runs with this tracer instead of discarding them.
+    pub fn with_tracer(mut self, tracer: Tracer) -> MetadataGenerator {
+        self.tracer = tracer;
+        self
+    }
+
+    /// Records the errors of runs with this reporter instead of discarding them.
+    pub fn with_reporter(mut self, reporter: ErrorReporter) -> MetadataGenerator {
+        self.reporter = reporter;
+        self
+    }
+
+    /// Lets the caller narrow down the planned artifacts before anything is downloaded. Runs with a selector
+    /// are considered partial, like runs limited to explicit versions.
+    pub fn with_selector(mut self, selector: impl Fn(Vec<PlannedArtifact>) -> Result<Vec<PlannedArtifact>, String> + 'static)
+        -> MetadataGenerator {
+        self.selector = Some(Box::new(selector));
+        self
+    }
+
+    /// Adds up the outcome, duration, downloads and versions of every run in these metrics.
+    pub fn with_metrics(mut self, metrics: SharedMetrics) -> MetadataGenerator {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Shows the outcome and the versions of every run on this dashboard.
+    pub fn with_dashboard(mut self, dashboard: SharedDashboard) -> MetadataGenerator {
+        self.dashboard = Some(dashboard);
+        self
+    }
+
+    pub fn config(&self) -> &GeneratorConfig {
+        &self.config
+    }
+
+    /// The `<lastUpdated>` of the Maven metadata the last successful run recorded in the state file, if there is one.
+    pub fn upstream_updated(&self) -> Option<DateTime<Utc>> {
+        let state = State::load(self.config.state.as_deref()?).ok()?;
+        state.last_updated.as_deref().and_then(maven::parse_timestamp)
+    }
+
+    /// Replaces the configuration of later runs, such as after the config file was reloaded.
+    pub fn set_config(&mut self, config: GeneratorConfig) {
+        self.config = config;
+    }
+
+    pub fn tracer(&self) -> &Tracer {
+        &self.tracer
+    }
+
+    pub fn reporter(&self) -> &ErrorReporter {
+        &self.reporter
+    }
+
+    /// Generates the metadata document, writing it to the configured output file. Along with the outcome comes the
+    /// document the output holds afterwards, when the run got that far, to be served without reading it back.
+    pub async fn run(&self) -> (Result<ExitStatus, GeneratorError>, Option<String>) {
+        self.tracer.start_trace();
+        // From the config of this run, which a reload may have changed since the last one
+        self.reporter.set_extra("metadata_url", &self.config.metadata_url);
+        self.reporter.set_extra("authlib_download_url_format", &self.config.authlib_download_url_format);
+        self.reporter.set_extra("injector_download_url", &self.config.injector_download_url);
+        self.reporter.set_extra("output_file", &self.config.output_file);
+        let started = std::time::Instant::now();
+        let budget = DownloadBudget::new(self.config.max_total_bytes);
+        let mut summary = Summary::default();
+        let result = generate(&self.config, &self.client, &self.tracer, &self.reporter, self.selector.as_deref(), &budget, &mut summary)
+            .await;
+        if self.config.summary_table && !summary.rows.is_empty() {
+            eprint!("{}", summary.table(self.config.color));
+        }
+        let result = match &self.config.report {
+            Some(path) => {
+                // Whatever it is now, the output is only the run's if the run succeeded
+                let output = result.as_ref().ok()
+                    .filter(|_| !self.config.writes_to_stdout())
+                    .and_then(|_| std::fs::read(&self.config.output_file).ok())
+                    .and_then(|contents| Digests::compute(&contents, &[HashAlgorithm::Sha256]).sha256)
+                    .map(|sha256| (self.config.output_file.as_str(), sha256));
+                let report = summary.report(&result, started.elapsed(), budget.used(), output);
+                let report = serde_json::to_string_pretty(&report).expect("reports are always serializable");
+                match std::fs::write(path, report + "\n") {
+                    Ok(()) => result,
+                    Err(source) => result.and(Err(GeneratorError::Io { action: "write report to", path: path.clone(), source })),
+                }
+            }
+            None => result,
+        };
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record(RunReport {
+                status: result.as_ref().map_or_else(GeneratorError::status, |status| *status),
+                succeeded: result.is_ok(),
+                duration: started.elapsed(),
+                downloaded_bytes: budget.used(),
+                versions_generated: summary.generated,
+                versions_failed: summary.skipped.get("failed").copied().unwrap_or_default()
+                    + summary.skipped.get("over budget").copied().unwrap_or_default(),
+            });
+        }
+        if let Some(dashboard) = &self.dashboard {
+            dashboard.record(CycleReport {
+                status: result.as_ref().map_or_else(GeneratorError::status, |status| *status),
+                error: result.as_ref().err().map(GeneratorError::to_string),
+                versions: summary.rows.iter()
+                    .map(|row| VersionReport {
+                        library: row.library.clone(),
+                        version: row.full_version.clone(),
+                        status: row.status,
+                        error: row.error.clone(),
+                    })
+                    .collect(),
+            });
+        }
+        (result, summary.document)
+    }
+}
+
+/// Performs a single generation run. Returns the reason the run failed, if it did.
+async fn generate(config: &GeneratorConfig, http_client: &reqwest::Client, tracer: &Tracer, reporter: &ErrorReporter,
+    selector: Option<&Selector>, budget: &DownloadBudget, summary: &mut Summary) -> Result<ExitStatus, GeneratorError> {
+    let span = tracer.span("generate");
+    let started = tokio::time::Instant::now();
+    let output_file = &config.output_file;
+
+    let exclusions = match &config.exclusions {
+        Some(path) => parse_exclusions(&std::fs::read_to_string(path)
+            .map_err(|source| GeneratorError::Exclusions { path: path.clone(), source })?),
+        None => HashMap::new(),
+    };
+    let state = match &config.state {
+        Some(path) => State::load(path).map_err(|source| GeneratorError::Io { action: "read state file", path: path.clone(), source })?,
+        None => State::default(),
+    };
+    let pins = match &config.pin_file {
+        Some(path) => Pins::load(path).map_err(|source| GeneratorError::Io { action: "read pin file", path: path.clone(), source })?,
+        None => Pins::default(),
+    };
+    let url_rewriter = UrlRewriter::new(&config.rewrite_url)?;
+    let templates = std::iter::once(&config.authlib_download_url_format)
+        .chain(config.fallback_repositories.iter().map(|(_, template)| template))
+        .chain(config.url_template_override.iter().map(|(_, template)| template))
+        .chain(std::iter::once(&config.vanilla_url_format))
+        .chain(config.libraries.iter().map(|library| &library.url_template));
+    for template in templates {
+        check_url_template(template).map_err(|why| GeneratorError::UrlTemplate { template: template.clone(), why })?;
+    }
+    let compile = |patterns: &[String]| patterns.iter().map(|pattern| Regex::new(pattern)).collect::<Result<Vec<_>, _>>();
+    let filters = Filters {
+        exclusions,
+        include: compile(&config.include).map_err(GeneratorError::VersionPattern)?,
+        exclude: compile(&config.exclude).map_err(GeneratorError::VersionPattern)?,
+        target_version_rules: config.target_version_rules.iter()
+            .map(|(pattern, target)| Ok((Regex::new(pattern)?, target.clone())))
+            .collect::<Result<Vec<_>, regex::Error>>()
+            .map_err(GeneratorError::TargetVersionRule)?,
+    };
+    let cache = config.cache_dir.as_deref().map(HttpCache::open).transpose()
+        .map_err(|source| GeneratorError::Io {
+            action: "open HTTP cache",
+            path: config.cache_dir.clone().unwrap_or_default(),
+            source,
+        })?;
+
+    let uploader = config.upload.as_deref()
+        .map(|target| Uploader::new(http_client, target, config.upload_endpoint.as_deref(), config.upload_credentials.as_ref()))
+        .transpose()
+        .map_err(GeneratorError::UploadSetup)?;
+    let mirror = config.mirror.as_ref().map(|(root, base_url)| {
+        let mirror = Mirror::new(root.clone(), base_url);
+        match (&uploader, config.presign_expiry) {
+            (Some(uploader), Some(expiry)) => {
+                let expires = Utc::now() + chrono::Duration::from_std(expiry).unwrap_or(chrono::Duration::MAX);
+                tracing::warn!("The artifact URLs are presigned and stop working at {}, generate the output again before then",
+                    expires.to_rfc3339_opts(SecondsFormat::Secs, true));
+                mirror.presigned(uploader.clone(), expiry)
+            }
+            _ => mirror,
+        }
+    });
+    let github_release = config.github_repository.as_deref()
+        .map(|repository| github::Release::new(http_client, repository, &config.github_release_tag, config.github_token.as_deref(),
+            &config.github_api_url))
+        .transpose()
+        .map_err(GeneratorError::UploadSetup)?;
+
+    let retry = RetryPolicy { attempts: config.retries, backoff: config.retry_backoff, jitter: !config.no_retry_jitter };
+    let limiter = AdaptiveLimiter::new(config.max_concurrent, config.request_interval);
+    let local = config.local_repo.as_deref().map(LocalRepository::new);
+    let connection = download::Connection {
+        client: http_client,
+        credentials: &config.credentials,
+        limiter: &limiter,
+        budget,
+        cache: cache.as_ref(),
+        retry: &retry,
+        local: local.as_ref(),
+        limits: ResponseLimits { max_document_size: config.max_metadata_size, max_artifact_size: config.max_artifact_size },
+    };
+    let mut run = Run {
+        config,
+        http_client,
+        reporter,
+        connection: &connection,
+        span,
+        started,
+        mirror,
+        url_rewriter,
+        state,
+        pins,
+        warnings: Vec::new(),
+    };
+
+    crash::set_phase("fetching upstream");
+    let skip_injector = matches!(config.injector, InjectorPolicy::Skip);
+    let latest_injector = if skip_injector {
+        None
+    } else {
+        let latest = match (&config.injector_api, &config.injector_github) {
+            (Some(api), _) => Some(injector::latest(http_client, &retry, api).await),
+            (None, Some(repository)) => Some(injector::latest_release(http_client, &retry, &config.github_api_url, repository).await),
+            (None, None) => None,
+        };
+        match latest {
+            Some(Ok(latest)) => {
+                if latest.sha256.is_none() {
+                    tracing::warn!("No checksum is published for authlib-injector {}, it can't be verified", latest.version);
+                }
+                Some(latest)
+            }
+            Some(Err(why)) => {
+                tracing::warn!("Couldn't resolve the latest authlib-injector ({}), using {} instead", why, config.injector_download_url);
+                None
+            }
+            None => None,
+        }
+    };
+    let injector_download_url = latest_injector.as_ref().map_or(&config.injector_download_url, |latest| &latest.download_url);
+    let mut injector_span = run.span.child("fetch injector");
+    injector_span.set_attribute("url.full", injector_download_url.as_str());
+    let injector_download = (!skip_injector).then(|| download::request(&connection, injector_download_url));
+
+    let partial_build = selector.is_some() || !config.versions.is_empty();
+    let mut upstream = run.resolve_versions().await?;
+    run.warnings.append(&mut upstream.warnings);
+
+    let injector_response = match injector_download {
+        Some(download) => Some(download.await),
+        None => None,
+    };
+    if let Some(Err(why)) = &injector_response {
+        injector_span.set_error(why);
+    }
+    drop(injector_span);
+    let injector_etag = injector_response.as_ref().and_then(|r| r.as_ref().ok())
+        .and_then(|r| r.headers().get(reqwest::header::ETAG))
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+
+    let mut stale = false;
+    if let Some(threshold) = config.warn_stale {
+        let injector_updated = injector_response.as_ref().and_then(|r| r.as_ref().ok())
+            .and_then(|r| r.headers().get(reqwest::header::LAST_MODIFIED))
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+            .map(|t| t.with_timezone(&Utc));
+        let metadata_updated = upstream.last_updated.as_deref().and_then(maven::parse_timestamp);
+
+        for (source, updated) in [("Maven metadata", metadata_updated), ("authlib-injector", injector_updated)] {
+            let Some(updated) = updated else {
+                continue
+            };
+            let age = (Utc::now() - updated).to_std().unwrap_or_default();
+            if age > threshold {
+                let message = format!("{} was last updated {} ago ({}), upstream may be abandoned",
+                    source, format_age(age), updated.format("%Y-%m-%d"));
+                tracing::warn!("{}", message);
+                run.warnings.push(message);
+                stale = true;
+            }
+        }
+    }
+
+    let upstream_versions: Vec<String> = upstream.versions.iter().cloned()
+        .chain(upstream.libraries.iter()
+            .flat_map(|listed| listed.versions.iter().map(|version| format!("{}:{}", listed.library.override_key, version))))
+        .collect();
+    // An output changed or removed since is regenerated even if upstream isn't, and one written to stdout is gone
+    let untouched_output = run.state.output_sha256.as_ref().filter(|_| !config.writes_to_stdout())
+        .and_then(|sha256| std::fs::read_to_string(output_file).ok()
+            .filter(|contents| Digests::compute(contents.as_bytes(), &[HashAlgorithm::Sha256]).sha256.as_ref() == Some(sha256)));
+    if config.state.is_some() && !config.force && !partial_build && (upstream.updated.is_some() || !upstream_versions.is_empty())
+        && run.state.last_updated == upstream.updated && run.state.versions == upstream_versions && run.state.injector_etag == injector_etag
+        && untouched_output.is_some() {
+        tracing::info!("Upstream unchanged since the previous run, leaving {} untouched (use --force to regenerate)", output_file);
+        run.span.set_attribute("generation.skipped", "unchanged");
+        summary.document = untouched_output;
+        if stale && config.fail_stale {
+            return Err(GeneratorError::Stale);
+        }
+        return Ok(ExitStatus::Unchanged);
+    }
+
+    crash::set_phase("planning");
+    let mut planned_artifacts = run.plan_artifacts(&upstream, &filters, summary);
+    if let Some(select) = selector {
+        planned_artifacts = select(planned_artifacts).map_err(GeneratorError::Selection)?;
+    }
+
+    // Before the first deployment there's no baseline, which only means nothing can be reused
+    let baseline = match &config.baseline_url {
+        Some(url) => document::read(http_client, url).await
+            .inspect_err(|why| tracing::warn!("Couldn't fetch the baseline, hashing every version: {}", why))
+            .ok(),
+        None => None,
+    };
+    let previous_entries = match &config.previous {
+        Some(Some(location)) => reusable_entries(&document::load(http_client, location).await?),
+        // There's nothing to reuse on the first run
+        Some(None) if !config.writes_to_stdout() && std::path::Path::new(output_file).exists() => {
+            reusable_entries(&document::load(http_client, output_file).await?)
+        }
+        Some(_) => HashMap::new(),
+        None => match baseline.as_deref().map(MetadataFile::parse) {
+            Some(Ok(baseline)) => reusable_entries(&baseline),
+            Some(Err(why)) => {
+                tracing::warn!("Couldn't parse the baseline, hashing every version: {}", why);
+                HashMap::new()
+            }
+            None => HashMap::new(),
+        },
+    };
+
+    crash::set_phase("downloading artifacts");
+    // Without an output file to keep it next to, it's kept where a run writing to stdout again finds it
+    let progress_file = if config.writes_to_stdout() {
+        std::env::temp_dir().join(format!("{}-stdout.progress", env!("CARGO_PKG_NAME")))
+    } else {
+        PathBuf::from(format!("{}.progress", output_file))
+    };
+    let progress = Progress::open(progress_file, !config.no_resume)
+        .map_err(|source| GeneratorError::Io { action: "open progress file for", path: PathBuf::from(output_file), source })?;
+    let store = config.store_dir.as_deref().map(ArtifactStore::open).transpose()
+        .map_err(|source| GeneratorError::Io {
+            action: "open artifact store",
+            path: config.store_dir.clone().unwrap_or_default(),
+            source,
+        })?;
+    let fetched = run.fetch_artifacts(&planned_artifacts, &previous_entries, &progress, store.as_ref()).await;
+
+    crash::set_phase("assembling output");
+    let injector = InjectorDownload { url: injector_download_url, latest: latest_injector.as_ref(), response: injector_response };
+    let (document, failed_versions) = run.build_document(&planned_artifacts, fetched, injector, summary).await?;
+
+    crash::set_phase("writing output");
+    let Some(written) = run.write_outputs(&document, baseline.as_deref(), upstream.last_updated.as_deref()).await? else {
+        // Left alone as it's identical to the baseline, which is what's published then
+        summary.document = baseline.map(|baseline| String::from_utf8_lossy(&baseline).into_owned());
+        if stale && config.fail_stale {
+            return Err(GeneratorError::Stale);
+        }
+        return Ok(ExitStatus::Unchanged);
+    };
+    progress.finish();
+    summary.document = Some(written.contents.clone());
+
+    if let Some(store) = &store
+        && let Err(why) = store.save_index() {
+        tracing::warn!("Couldn't save artifact store index: {}", why);
+    }
+
+    run.publish(&written, uploader.as_ref(), github_release.as_ref()).await?;
+
+    crash::set_phase("saving state");
+    if let Some(path) = &config.state {
+        // A hand-picked build doesn't reflect upstream, so the next regular run must not be skipped
+        if !partial_build {
+            run.state.last_updated = upstream.updated;
+            run.state.injector_etag = injector_etag;
+            run.state.versions = upstream_versions;
+            run.state.output_sha256 = Digests::compute(written.contents.as_bytes(), &[HashAlgorithm::Sha256]).sha256;
+        }
+        run.state.save(path).map_err(|source| GeneratorError::Io { action: "write state file", path: path.clone(), source })?;
+    }
+    if let Some(path) = &config.pin_file {
+        run.pins.save(path).map_err(|source| GeneratorError::Io { action: "write pin file", path: path.clone(), source })?;
+    }
+
+    if !failed_versions.is_empty() {
+        return Err(GeneratorError::PartialFailure(failed_versions));
+    }
+    if stale && config.fail_stale {
+        return Err(GeneratorError::Stale);
+    }
+
+    Ok(if written.identical { ExitStatus::Unchanged } else { ExitStatus::Changed })
+}
+
+/// What the phases of a run share: the configuration, what it downloads with, and what it records along the way, to
+/// be saved once the output has been published.
+struct Run<'a> {
+    config: &'a GeneratorConfig,
+    http_client: &'a reqwest::Client,
+    reporter: &'a ErrorReporter,
+    connection: &'a Connection<'a>,
+    span: Span<'a>,
+    started: tokio::time::Instant,
+    mirror: Option<Mirror>,
+    url_rewriter: UrlRewriter,
+    state: State,
+    pins: Pins,
+    warnings: Vec<String>,
+}
+
+/// What decides which of the listed versions become artifacts.
+struct Filters {
+    exclusions: HashMap<String, Option<String>>,
+    include: Vec<Regex>,
+    exclude: Vec<Regex>,
+    target_version_rules: Vec<(Regex, String)>,
+}
+
+/// What upstream lists, as read by [`Run::resolve_versions`].
+struct Upstream<'a> {
+    /// Full versions of the artifact, with those listed in the configuration
+    versions: Vec<String>,
+    /// The repositories listing each version, as indices into the primary and fallback ones, with
+    /// --union-repositories
+    listing_repositories: HashMap<String, Vec<usize>>,
+    /// `<lastUpdated>` of the artifact's Maven metadata, or what stands in for it
+    last_updated: Option<String>,
+    /// Download URLs of versions given by their source instead of the URL template
+    build_urls: HashMap<String, String>,
+    release_times: HashMap<String, DateTime<Utc>>,
+    libraries: Vec<ListedLibrary<'a>>,
+    /// What tells whether anything upstream changed since the last run
+    updated: Option<String>,
+    warnings: Vec<String>,
+}
+
+/// The versions of one of the further libraries.
+struct ListedLibrary<'a> {
+    library: &'a LibrarySource,
+    versions: Vec<String>,
+    /// The newest version and when it was released, if its Maven metadata says
+    newest_release_time: Option<(String, DateTime<Utc>)>,
+}
+
+/// An artifact's entry, or the key of the version in the state and why it couldn't be downloaded.
+type Fetched = Result<LibraryOverride, (String, DownloadError)>;
+
+/// The authlib-injector build a run retrieves, and where it was resolved from.
+struct InjectorDownload<'a> {
+    url: &'a String,
+    latest: Option<&'a injector::LatestInjector>,
+    response: Option<Result<download::Response<'a>, DownloadError>>,
+}
+
+/// An output that was written, or left alone as it was identical, and its companions.
+struct Written {
+    contents: String,
+    identical: bool,
+    /// Files published next to the output, by their name
+    companions: Vec<(String, Vec<u8>)>,
+    /// What changed since the previous output, if anyone is to be told
+    changes: Vec<Difference>,
+}
+
+impl<'a> Run<'a> {
+    /// Lists the versions of the artifact and of the further libraries upstream.
+    async fn resolve_versions(&self) -> Result<Upstream<'a>, GeneratorError> {
+        let config = self.config;
+        let connection = self.connection;
+        let metadata_url = &config.metadata_url;
+        let (listed_full_versions, listed_base_versions) = listed_versions(config);
+        let mut warnings = Vec::new();
+
+        let mut listing_repositories: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut versions: Vec<String> = Vec::new();
+        let mut last_updated = None;
+        let mut build_urls: HashMap<String, String> = HashMap::new();
+        let mut release_times: HashMap<String, DateTime<Utc>> = HashMap::new();
+        if let VersionSource::ElybyApi = config.source
+            && (config.versions.is_empty() || !listed_base_versions.is_empty()) {
+            let api_url = config.elyby_api_url.as_deref().unwrap_or_default();
+            let mut builds_span = self.span.child("fetch builds");
+            builds_span.set_attribute("url.full", api_url);
+            let builds = elyby::list_builds(connection, api_url).await.inspect_err(|why| builds_span.set_error(why))?;
+            drop(builds_span);
+
+            // Stands in for <lastUpdated>, so unchanged builds are skipped with --state and --warn-stale has a date
+            last_updated = builds.iter().filter_map(|build| build.released_at).max().map(|time| time.format("%Y%m%d%H%M%S").to_string());
+            versions = builds.iter().map(|build| build.version.clone()).collect();
+            release_times = builds.iter().filter_map(|build| Some((build.version.clone(), build.released_at?))).collect();
+            build_urls = builds.into_iter().filter_map(|build| Some((build.version, build.url?))).collect();
+        } else if config.versions.is_empty() || !listed_base_versions.is_empty() {
+            let mut metadata_span = self.span.child("fetch metadata");
+            metadata_span.set_attribute("url.full", metadata_url.as_str());
+            let mut response = download::fetch_cached(connection, metadata_url).await;
+            let mut read_from = 0;
+            for (index, (fallback_url, _)) in config.fallback_repositories.iter().enumerate() {
+                let Err(why) = &response else { break };
+                tracing::warn!("Couldn't download Maven metadata ({}), trying {}", why, fallback_url);
+                metadata_span.set_attribute("url.full", fallback_url.as_str());
+                response = download::fetch_cached(connection, fallback_url).await;
+                read_from = index + 1;
+            }
+            let response = response.map(|body| String::from_utf8_lossy(&body).into_owned());
+
+            match (response, config.search_fallback) {
+                (Err(why), Some(api)) => {
+                    metadata_span.set_error(&why);
+                    drop(metadata_span);
+                    tracing::warn!("Couldn't download Maven metadata ({}), listing versions through the search API instead", why);
+
+                    let (group, artifact) = config.artifact.split_once(':').unwrap_or(("", &config.artifact));
+                    let server = config.search_server.as_deref().unwrap_or_default();
+                    let repository = config.search_repository.as_deref().unwrap_or_default();
+                    versions = search::list_versions(self.connection, api, server, repository, group, artifact).await?;
+                }
+                (response, _) => {
+                    let response = response
+                        .map_err(|source| GeneratorError::Download { what: "Maven metadata".to_string(), source })?;
+                    metadata_span.set_attribute("http.response.body.size", response.len());
+                    drop(metadata_span);
+
+                    let metadata = maven::declare_unknown_prefixes(response);
+                    let metadata_doc = maven::parse(&metadata)?;
+                    let maven_metadata = maven::read_metadata(&metadata_doc)?;
+                    for warning in &maven_metadata.warnings {
+                        tracing::warn!("{}", warning);
+                    }
+                    warnings.extend(maven_metadata.warnings.iter().cloned());
+                    last_updated = maven_metadata.last_updated.map(str::to_string);
+                    release_times.extend(newest_release_time(&maven_metadata));
+                    versions = maven_metadata.versions.iter().map(|version| version.to_string()).collect();
+
+                    if config.union_repositories {
+                        for version in &versions {
+                            listing_repositories.entry(version.clone()).or_default().push(read_from);
+                        }
+                        for (index, (url, _)) in config.fallback_repositories.iter().enumerate().skip(read_from) {
+                            let listed = match fetch_versions(connection, url).await {
+                                Ok(listed) => listed,
+                                Err(why) => {
+                                    tracing::warn!("Couldn't read the Maven metadata at {} ({}), leaving its versions out", url, why);
+                                    continue
+                                }
+                            };
+                            // Any repository changing makes for a new union
+                            last_updated = last_updated.max(listed.last_updated);
+                            release_times.extend(listed.newest_release_time);
+                            for version in listed.versions {
+                                if !versions.contains(&version) {
+                                    versions.push(version.clone());
+                                }
+                                listing_repositories.entry(version).or_default().push(index + 1);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for full_version in &listed_full_versions {
+            if !versions.iter().any(|version| version == full_version) {
+                versions.push(full_version.to_string());
+            }
+        }
+
+        let mut libraries = Vec::new();
+        for library in &config.libraries {
+            let mut metadata_span = self.span.child("fetch metadata");
+            metadata_span.set_attribute("url.full", library.metadata_url.as_str());
+            let response = download::fetch_cached(connection, &library.metadata_url).await
+                .map_err(|source| {
+                    metadata_span.set_error(&source);
+                    GeneratorError::Download { what: format!("Maven metadata of {}", library.override_key), source }
+                })?;
+            metadata_span.set_attribute("http.response.body.size", response.len());
+            drop(metadata_span);
+
+            let metadata = maven::declare_unknown_prefixes(String::from_utf8_lossy(&response).into_owned());
+            let metadata_doc = maven::parse(&metadata)?;
+            let maven_metadata = maven::read_metadata(&metadata_doc)?;
+            for warning in &maven_metadata.warnings {
+                tracing::warn!("{}: {}", library.override_key, warning);
+                warnings.push(format!("{}: {}", library.override_key, warning));
+            }
+            libraries.push((maven_metadata.last_updated.map(str::to_string), ListedLibrary {
+                library,
+                versions: maven_metadata.versions.iter().map(|version| version.to_string()).collect(),
+                newest_release_time: newest_release_time(&maven_metadata),
+            }));
+        }
+        // The run is only skipped when none of the libraries changed either
+        let updated = if config.libraries.is_empty() {
+            last_updated.clone()
+        } else {
+            std::iter::once(&last_updated).chain(libraries.iter().map(|(updated, _)| updated))
+                .map(Option::as_deref)
+                .collect::<Option<Vec<_>>>()
+                .map(|timestamps| timestamps.join(" "))
+        };
+
+        Ok(Upstream {
+            versions,
+            listing_repositories,
+            last_updated,
+            build_urls,
+            release_times,
+            libraries: libraries.into_iter().map(|(_, listed)| listed).collect(),
+            updated,
+            warnings,
+        })
+    }
+
+    /// Works out which artifact to take for every base version that isn't left out, and where to download it from,
+    /// newest first.
+    fn plan_artifacts(&mut self, upstream: &Upstream, filters: &Filters, summary: &mut Summary) -> Vec<PlannedArtifact> {
+        let config = self.config;
+        let (listed_full_versions, listed_base_versions) = listed_versions(config);
+        let mut authlib_versions_to_full_versions: HashMap<String, &str> = HashMap::new();
+        for full_version in upstream.versions.iter().map(String::as_str) {
+            let authlib_version = target_version(&filters.target_version_rules, full_version);
+            let authlib_version = authlib_version.as_str();
+            if !config.versions.is_empty() && !listed_full_versions.contains(&full_version)
+                && !listed_base_versions.contains(&authlib_version) {
+                continue
+            }
+
+            // Filtered out versions are expected to be listed on every run, unlike exclusions they aren't warned about
+            if config.min_version.as_ref().is_some_and(|min| compare_versions(authlib_version, min).is_lt())
+                || (!filters.include.is_empty() && !filters.include.iter().any(|pattern| pattern.is_match(full_version)))
+                || filters.exclude.iter().any(|pattern| pattern.is_match(full_version)) {
+                summary.leave_out(full_version, "filtered");
+                continue
+            }
+            if maven::is_snapshot(full_version) && !config.include_snapshots {
+                summary.leave_out(full_version, "snapshot");
+                continue
+            }
+
+            if let Some(reason) = filters.exclusions.get(full_version) {
+                self.warnings.push(match reason {
+                    Some(reason) => format!("Excluded {}: {}", full_version, reason),
+                    None => format!("Excluded {}", full_version),
+                });
+                summary.leave_out(full_version, "excluded");
+                continue
+            }
+
+            if Version::parse(full_version).is_none() {
+                self.warnings.push(format!("Skipped {}: not a version that can be ordered", full_version));
+                summary.leave_out(full_version, "unorderable");
+                continue
+            }
+            if let Some((_, existing)) = authlib_versions_to_full_versions.get_key_value(authlib_version) {
+                if compare_versions(full_version, existing).is_gt() {
+                    authlib_versions_to_full_versions.remove(authlib_version);
+                } else {
+                    continue
+                }
+            }
+
+            authlib_versions_to_full_versions.insert(authlib_version.to_string(), full_version);
+        }
+
+        let url_template_overrides: HashMap<&str, &str> = config.url_template_override.iter()
+            .map(|(version, template)| (version.as_str(), template.as_str()))
+            .collect();
+        let mut planned_artifacts: Vec<PlannedArtifact> = authlib_versions_to_full_versions.iter()
+            .map(|(authlib_version, full_version)| {
+                let template = url_template_overrides.get(authlib_version.as_str()).copied()
+                    .unwrap_or(&config.authlib_download_url_format);
+                let mut urls: Vec<String> = std::iter::once(upstream.build_urls.get(*full_version).cloned()
+                        .unwrap_or_else(|| expand_url_template(template, &config.artifact, full_version, &config.packaging)))
+                    .chain(config.fallback_repositories.iter()
+                        .map(|(_, template)| expand_url_template(template, &config.artifact, full_version, &config.packaging)))
+                    .collect();
+                // The repositories listing the version are tried first, by priority, the others only in case they have
+                // it anyway
+                if let Some(listing) = upstream.listing_repositories.get(*full_version) {
+                    let priority = |index: usize| config.repository_priority.iter().position(|i| *i == index)
+                        .unwrap_or(config.repository_priority.len() + index);
+                    let mut indexed: Vec<(usize, String)> = urls.into_iter().enumerate().collect();
+                    indexed.sort_by_key(|(index, _)| (!listing.contains(index), priority(*index)));
+                    urls = indexed.into_iter().map(|(_, url)| url).collect();
+                    if listing.len() > 1 {
+                        tracing::debug!(version = full_version, "Listed in {} repositories, taking the artifact from {}", listing.len(),
+                            urls[0]);
+                    }
+                }
+                PlannedArtifact {
+                    target_version: authlib_version.clone(),
+                    full_version: full_version.to_string(),
+                    name: format!("{}:{}{}", config.artifact, full_version, config.packaging.name_suffix()),
+                    url: urls.remove(0),
+                    fallback_urls: urls,
+                    vanilla: false,
+                    library: config.override_key.clone(),
+                    release_time: upstream.release_times.get(*full_version).copied(),
+                }
+            })
+            .collect();
+        for version in &config.vanilla_fallback {
+            if !authlib_versions_to_full_versions.contains_key(version) {
+                planned_artifacts.push(PlannedArtifact {
+                    target_version: version.clone(),
+                    full_version: version.clone(),
+                    name: format!("{}:{}", config.override_key, version),
+                    url: expand_url_template(&config.vanilla_url_format, &config.override_key, version, &Packaging::default()),
+                    fallback_urls: Vec::new(),
+                    vanilla: true,
+                    library: config.override_key.clone(),
+                    release_time: None,
+                });
+            }
+        }
+        for listed in &upstream.libraries {
+            let mut base_versions_to_full_versions: HashMap<String, &str> = HashMap::new();
+            for full_version in &listed.versions {
+                let newest = base_versions_to_full_versions.entry(target_version(&filters.target_version_rules, full_version))
+                    .or_insert(full_version);
+                if compare_versions(full_version, newest).is_gt() {
+                    *newest = full_version;
+                }
+            }
+            let library = listed.library;
+            planned_artifacts.extend(base_versions_to_full_versions.into_iter().map(|(base_version, full_version)| PlannedArtifact {
+                target_version: base_version,
+                full_version: full_version.to_string(),
+                name: format!("{}:{}", library.artifact, full_version),
+                url: expand_url_template(&library.url_template, &library.artifact, full_version, &Packaging::default()),
+                fallback_urls: Vec::new(),
+                vanilla: false,
+                library: library.override_key.clone(),
+                release_time: listed.newest_release_time.as_ref().filter(|(newest, _)| newest == full_version).map(|(_, time)| *time),
+            }));
+        }
+        planned_artifacts.sort_by(|a, b| newest_first(&a.target_version, &b.target_version).then_with(|| a.library.cmp(&b.library)));
+
+        let quarantine_cooldown = chrono::Duration::from_std(config.quarantine_cooldown).unwrap_or(chrono::Duration::MAX);
+        planned_artifacts.retain(|planned| {
+            let key = state_key(config, &planned.library, &planned.full_version, &planned.name);
+            let Some(record) = self.state.quarantined(key, config.quarantine_after, quarantine_cooldown) else {
+                return true
+            };
+            self.warnings.push(format!("Skipped {}: quarantined after {} consecutive failures (last error: {}), will be retried after {}",
+                key, record.count, record.last_error, (record.last_attempt + quarantine_cooldown).format("%Y-%m-%d %H:%M UTC")));
+            summary.leave_out(key, "quarantined");
+            false
+        });
+
+        planned_artifacts
+    }
+
+    /// Works out the digests of every planned artifact, reusing what earlier runs found where it still applies, and
+    /// otherwise downloading them and checking what they are. Results are in planned order, with how long each took.
+    async fn fetch_artifacts(&self, planned_artifacts: &[PlannedArtifact], previous_entries: &HashMap<String, ReusableEntry>,
+        progress: &Progress, store: Option<&ArtifactStore>) -> Vec<(Fetched, Duration)> {
+        let config = self.config;
+        let deadline = config.deadline.map(|deadline| self.started + deadline);
+        let authlib_metadata_futures = planned_artifacts.iter().map(|planned| {
+            let connection = self.connection;
+            let run_span = &self.span;
+            let recorded_hashes = &self.state.hashes;
+            let pins = &self.pins.0;
+            let url_rewriter = &self.url_rewriter;
+            let mirror = self.mirror.as_ref();
+            let span = tracing::info_span!("artifact", version = %planned.full_version, url = %planned.url, bytes = tracing::field::Empty);
+            let artifact_span = span.clone();
+            let future = async move {
+                let mirror_path = mirror::artifact_path(&planned.name);
+                // Without a copy in the mirror, the artifact has to be downloaded even if its hashes are known
+                let mirrored = mirror.is_none_or(|mirror| mirror.contains(&mirror_path));
+                let full_version = &planned.full_version;
+                let key = state_key(config, &planned.library, full_version, &planned.name);
+                // Hashes that weren't computed here are only taken while they match the pin, or the state without one.
+                // A changed hash is only believed after hashing the artifact itself
+                let recorded = pins.get(key).or(recorded_hashes.get(key));
+                let matches_recorded = |digests: &Digests| recorded.is_none_or(|recorded| *recorded == digests.sha1);
+                let metadata = |url: &str, digests: Digests, size: usize, release_time: Option<DateTime<Utc>>, reused: bool| LibraryOverride {
+                    target_version: planned.target_version.clone(),
+                    full_version: full_version.to_string(),
+                    name: planned.name.clone(),
+                    url: url.to_string(),
+                    digests: digests.only(&config.hashes),
+                    size,
+                    vanilla: planned.vanilla,
+                    library: planned.library.clone(),
+                    release_time: release_time.or(planned.release_time),
+                    reused,
+                };
+                let urls: Vec<&String> = std::iter::once(&planned.url).chain(&planned.fallback_urls).collect();
+
+                if mirrored
+                    && let Some((url, (digests, size, release_time))) = urls.iter().find_map(|url| Some((url, progress.completed(url)?)))
+                    && digests.covers(&config.hashes)
+                    && matches_recorded(digests) {
+                    tracing::debug!("Reusing hashes recorded by the interrupted run");
+                    return Ok(metadata(url, digests.clone(), *size, *release_time, true))
+                }
+
+                // Entries are only reused while they still point to the same artifact
+                if mirrored
+                    && let Some((previous_url, digests, size, release_time)) = previous_entries.get(&planned.name)
+                    && digests.covers(&config.hashes)
+                    && matches_recorded(digests)
+                    && let Some(url) = urls.iter()
+                        .find(|url| match mirror {
+                            Some(mirror) => mirror.same_copy(&url_rewriter.rewrite(&mirror.url(&mirror_path)), previous_url),
+                            None => url_rewriter.rewrite(url) == *previous_url,
+                        }) {
+                    tracing::debug!("Reusing the previous entry");
+                    return Ok(metadata(url, digests.clone(), *size, *release_time, true))
+                }
+
+                let packaging = maven::split_name(&planned.name).map(|(_, _, packaging)| packaging).unwrap_or_default();
+                let fetch = async |url: &str| {
+                    let resolved;
+                    let url = if config.include_snapshots && maven::is_snapshot(full_version) {
+                        resolved = download::resolve_snapshot(connection, url, full_version, &packaging).await?;
+                        tracing::debug!(build = resolved.as_str(), "Resolved the snapshot");
+                        resolved.as_str()
+                    } else {
+                        url
+                    };
+                    let mut download_span = run_span.child("download artifact");
+                    download_span.set_attribute("authlib.version", full_version.to_string());
+                    download_span.set_attribute("url.full", url.to_string());
+                    if config.gradle_modules && mirrored
+                        && let Some(file) = gradle::find_artifact(connection, url, &packaging, &config.hashes).await
+                        && matches_recorded(&file.digests) {
+                        download_span.set_attribute("artifact.source", "module");
+                        tracing::debug!("Using the Gradle module metadata");
+                        progress.record(url, &file.digests, file.size, planned.release_time);
+                        return Ok(metadata(&file.url, file.digests, file.size, None, false))
+                    }
+                    if config.checksum_sidecars && mirrored
+                        && let Some((digests, size)) = download::published_checksums(connection, url, &config.hashes).await
+                        && matches_recorded(&digests) {
+                        download_span.set_attribute("artifact.source", "sidecar");
+                        tracing::debug!("Using the published checksums");
+                        progress.record(url, &digests, size, planned.release_time);
+                        return Ok(metadata(url, digests, size, None, false))
+                    }
+                    // Only when nothing needs the bytes themselves, anything downloaded is checked against the headers
+                    if config.trust_checksum_headers && mirrored && !config.check_jars && !config.verify_published
+                        && let Some((digests, size)) = download::announced_checksums(connection, url, &config.hashes).await
+                        && matches_recorded(&digests) {
+                        download_span.set_attribute("artifact.source", "headers");
+                        tracing::debug!("Using the checksum headers");
+                        progress.record(url, &digests, size, planned.release_time);
+                        return Ok(metadata(url, digests, size, None, false))
+                    }
+                    let stored = store.and_then(|store| store.get(url, &config.hashes));
+                    download_span.set_attribute("artifact.source", if stored.is_some() { "store" } else { "download" });
+                    tracing::debug!(source = if stored.is_some() { "store" } else { "download" }, "Hashing the artifact");
+                    // The contents only stay around, on disk, for what still needs them once they're hashed
+                    let keep = mirror.is_some() || store.is_some() || config.check_jars
+                        || (config.verify_published && config.keyring.is_some());
+                    let downloaded = match stored {
+                        Some((path, digests, size)) => Ok(download::Downloaded {
+                            digests,
+                            size,
+                            checksums: ChecksumHeaders::default(),
+                            last_modified: None,
+                            contents: Some(download::Contents::existing(path)),
+                        }),
+                        None => download::download_artifact(connection, url, &config.hashes, keep).await,
+                    };
+                    let verified = downloaded.and_then(|downloaded| {
+                        let digests = downloaded.verified_digests(&config.hashes)?;
+                        Ok((downloaded, digests))
+                    });
+                    let verified = match verified {
+                        Ok((downloaded, digests)) if config.verify_published => {
+                            download::verify_published(connection, url, downloaded.contents.as_ref(), &digests, config.keyring.as_deref()).await
+                                .map(|()| (downloaded, digests))
+                        }
+                        verified => verified,
+                    };
+                    let verified = match verified {
+                        Ok((downloaded, digests)) if config.check_jars => {
+                            let expected_entry = config.expected_entry.clone()
+                                .filter(|_| planned.library == config.override_key)
+                                .unwrap_or_else(|| format!("{}/", planned.library.replace(['.', ':'], "/")));
+                            let checked = downloaded.contents.as_ref().map_or(Ok(()), |contents| jar::check(contents.path(), &expected_entry));
+                            checked.map_err(DownloadError::BadJar).map(|()| (downloaded, digests))
+                        }
+                        verified => verified,
+                    };
+                    let (downloaded, digests) = match verified {
+                        Ok(verified) => verified,
+                        Err(why) => {
+                            download_span.set_error(&why);
+                            tracing::debug!("Failed: {}", why);
+                            return Err(why)
+                        }
+                    };
+                    let size = downloaded.size;
+                    download_span.set_attribute("http.response.body.size", size);
+                    artifact_span.record("bytes", size);
+
+                    if let Some(mirror) = mirror
+                        && let Some(contents) = &downloaded.contents
+                        && let Err(why) = mirror.copy(&mirror_path, contents.path()) {
+                        let why = DownloadError::Mirror(why);
+                        download_span.set_error(&why);
+                        return Err(why)
+                    }
+
+                    if let Some(store) = store
+                        && let Some(contents) = &downloaded.contents {
+                        store.put(url, &digests.sha1, contents.path());
+                    }
+
+                    let release_time = downloaded.last_modified.or(planned.release_time);
+                    progress.record(url, &digests, size, release_time);
+                    Ok(metadata(url, digests, size, release_time, false))
+                };
+
+                // Fallback repositories are tried in order, the error of the last one is what's reported
+                let mut failure = None;
+                for url in urls {
+                    if let Some((_, why)) = &failure {
+                        tracing::warn!("Couldn't download {} ({}), trying {}", full_version, why, url);
+                    }
+                    match fetch(url).await {
+                        Ok(metadata) => return Ok(metadata),
+                        Err(why) => {
+                            let exceeded = matches!(why, DownloadError::BudgetExceeded(_));
+                            failure = Some((url, why));
+                            if exceeded {
+                                break
+                            }
+                        }
+                    }
+                }
+                let (url, why) = failure.expect("there is always a URL to try");
+                self.reporter.record("Couldn't download artifact", &why,
+                    vec![("url", url.to_string()), ("version", full_version.to_string())]);
+                Err::<LibraryOverride, (String, DownloadError)>((key.to_string(), why))
+            }.instrument(span);
+            async move {
+                let started = std::time::Instant::now();
+                let result = match deadline {
+                    // Cut off where it is, what was downloaded so far is discarded
+                    Some(deadline) => tokio::time::timeout_at(deadline, future).await.unwrap_or_else(|_| {
+                        let key = state_key(config, &planned.library, &planned.full_version, &planned.name);
+                        Err((key.to_string(), DownloadError::Deadline))
+                    }),
+                    None => future.await,
+                };
+                (result, started.elapsed())
+            }
+        });
+
+        // Only this many artifacts are in progress at once, the limiter may allow even fewer requests.
+        // Results stay in planned order
+        futures::stream::iter(authlib_metadata_futures)
+            .buffered(config.max_concurrent.max(1))
+            .collect()
+            .await
+    }
+
+    /// Puts the document together from the fetched artifacts, the authlib-injector and the extras, and checks it
+    /// against what earlier runs recorded. Returns it with the versions that couldn't be downloaded.
+    async fn build_document(&mut self, planned_artifacts: &[PlannedArtifact],
+        fetched: Vec<(Fetched, Duration)>, injector: InjectorDownload<'_>, summary: &mut Summary)
+        -> Result<(MetadataFile, Vec<String>), GeneratorError> {
+        let config = self.config;
+        let mut document = MetadataFile::default();
+        // Libraries are listed even when none of their artifacts could be downloaded
+        for library in std::iter::once(&config.override_key).chain(config.libraries.iter().map(|library| &library.override_key)) {
+            document.overrides.insert(library.clone(), Overrides::default());
+        }
+        let mut drifts: Vec<HashDrift> = Vec::new();
+        let mut budget_exceeded = false;
+        let mut failed_versions: Vec<String> = Vec::new();
+        let mut unprocessed: Vec<String> = Vec::new();
+        for (planned, (metadata_result, duration)) in planned_artifacts.iter().zip(fetched) {
+            let outcome = match &metadata_result {
+                Ok(metadata) => (Some(metadata.size), if metadata.reused { "reused" } else { "hashed" }),
+                Err((_, DownloadError::BudgetExceeded(_))) => (None, "over budget"),
+                Err((_, DownloadError::Deadline)) => (None, "not processed"),
+                Err(_) => (None, "failed"),
+            };
+            summary.rows.push(SummaryRow {
+                library: planned.library.clone(),
+                target_version: planned.target_version.clone(),
+                full_version: planned.full_version.clone(),
+                size: outcome.0,
+                status: outcome.1,
+                error: metadata_result.as_ref().err().map(|(_, why)| why.to_string()),
+                duration,
+            });
+            match metadata_result {
+                Ok(metadata) => {
+                    if metadata.reused { summary.reused += 1 } else { summary.generated += 1 }
+                    let key = state_key(config, &metadata.library, &metadata.full_version, &metadata.name).to_string();
+                    self.state.failures.remove(&key);
+                    // A pin outranks the state, it's the one that gets reviewed
+                    match self.pins.0.get(&key).or(self.state.hashes.get(&key)) {
+                        Some(recorded) if *recorded != metadata.digests.sha1 => {
+                            drifts.push(HashDrift {
+                                full_version: key.clone(),
+                                recorded: recorded.clone(),
+                                computed: metadata.digests.sha1.clone(),
+                            });
+                        }
+                        _ => {
+                            self.state.hashes.entry(key.clone()).or_insert_with(|| metadata.digests.sha1.clone());
+                            self.pins.0.entry(key).or_insert_with(|| metadata.digests.sha1.clone());
+                        }
+                    }
+
+                    let url = match &self.mirror {
+                        Some(mirror) => mirror.url(&mirror::artifact_path(&metadata.name)),
+                        None => metadata.url,
+                    };
+                    document.overrides.entry(metadata.library).or_default().0.insert(metadata.target_version, LibraryEntry {
+                        name: metadata.name,
+                        url: self.url_rewriter.rewrite(&url),
+                        digests: metadata.digests,
+                        size: metadata.size,
+                        release_time: metadata.release_time.map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+                        vanilla: metadata.vanilla,
+                        minecraft_versions: Vec::new(),
+                    });
+                }
+                Err((_, DownloadError::BudgetExceeded(_))) => {
+                    summary.skip("over budget");
+                    budget_exceeded = true
+                }
+                // Versions the run didn't get to haven't failed, they don't count towards quarantine
+                Err((full_version, DownloadError::Deadline)) => {
+                    summary.skip("not processed");
+                    unprocessed.push(full_version);
+                }
+                Err((full_version, why)) => {
+                    summary.skip("failed");
+                    tracing::warn!("Couldn't create library metadata: {}", why);
+                    self.state.record_failure(&full_version, why.to_string());
+                    failed_versions.push(full_version);
+                    continue;
+                }
+            }
+        }
+        // However they were planned and picked, entries are always listed in the same order
+        for overrides in document.overrides.values_mut() {
+            overrides.0.sort_by(|a, _, b, _| newest_first(a, b));
+        }
+
+        tracing::info!("{}", summary);
+
+        if budget_exceeded {
+            self.span.set_error("download budget exceeded");
+            return Err(GeneratorError::BudgetExceeded(self.connection.budget.max().unwrap_or_default()));
+        }
+
+        if !unprocessed.is_empty() {
+            let message = format!("Deadline reached, not processed: {}", unprocessed.join(", "));
+            tracing::warn!("{}", message);
+            self.warnings.push(message);
+            // They're missing from the output like failed versions, which --fail-on-error refuses
+            failed_versions.append(&mut unprocessed);
+        }
+        if config.fail_on_error && !failed_versions.is_empty() {
+            self.span.set_error("artifact download failed");
+            return Err(GeneratorError::FailedDownloads(failed_versions));
+        }
+
+        if !drifts.is_empty() {
+            for drift in &drifts {
+                tracing::error!("hash of {} changed from {} to {}, Maven artifacts must never change in place",
+                    drift.full_version, drift.recorded, drift.computed);
+            }
+            if let Some(webhook) = &config.alert_webhook {
+                send_drift_alert(self.http_client, webhook, &drifts).await;
+            }
+            if !config.allow_repin {
+                self.span.set_error("hash drift");
+                return Err(GeneratorError::HashDrift(drifts.into_iter().map(|d| d.full_version).collect()));
+            }
+            for drift in drifts {
+                let message = format!("Repinned {} to {} (was {}) with --allow-repin, make sure upstream really rebuilt it",
+                    drift.full_version, drift.computed, drift.recorded);
+                tracing::warn!("{}", message);
+                self.warnings.push(message);
+                self.state.hashes.insert(drift.full_version.clone(), drift.computed.clone());
+                self.pins.0.insert(drift.full_version, drift.computed);
+            }
+        }
+
+        if let Some(reference_url) = &config.parity_reference {
+            let reference = download::fetch(self.connection, reference_url, self.connection.limits.max_document_size).await;
+            match reference.map(|body| MetadataFile::parse(&body)) {
+                Ok(Ok(reference)) => {
+                    let reference = reference.overrides.get(&config.override_key).cloned().unwrap_or_default();
+                    for disagreement in check_parity(&document.overrides[&config.override_key], &reference) {
+                        tracing::warn!("{}", disagreement);
+                        self.warnings.push(disagreement);
+                    }
+                }
+                Ok(Err(why)) => tracing::warn!("Couldn't parse parity reference: {}", why),
+                Err(why) => tracing::warn!("Couldn't download parity reference: {}", why),
+            }
+        }
+
+        if config.minecraft_versions {
+            let mut minecraft_span = self.span.child("fetch minecraft versions");
+            minecraft_span.set_attribute("url.full", config.version_manifest_url.as_str());
+            match minecraft::shipped_libraries(self.connection, &config.version_manifest_url, config.max_concurrent).await {
+                Ok(shipped) => {
+                    if shipped.unavailable > 0 {
+                        let message = format!("Couldn't read {} Minecraft version(s), they are missing from minecraftVersions",
+                            shipped.unavailable);
+                        tracing::warn!("{}", message);
+                        self.warnings.push(message);
+                    }
+                    for (library, overrides) in document.overrides.iter_mut() {
+                        for (version, entry) in overrides.0.iter_mut() {
+                            entry.minecraft_versions = shipped.minecraft_versions(library, version).to_vec();
+                        }
+                    }
+                }
+                Err(why) => {
+                    minecraft_span.set_error(&why);
+                    let message = format!("Couldn't download the Minecraft version manifest: {}", why);
+                    tracing::warn!("{}", message);
+                    self.warnings.push(message);
+                }
+            }
+        }
+
+        let versions = document.overrides[&config.override_key].0.len();
+        if let Some(min_versions) = config.min_versions
+            && versions < min_versions {
+            self.span.set_error("too few versions");
+            return Err(GeneratorError::TooFewVersions { count: versions, min: min_versions });
+        }
+
+        if config.latest_alias {
+            for overrides in document.overrides.values_mut() {
+                // Entries were inserted newest first
+                let newest = overrides.0.values().find(|entry| !entry.vanilla).cloned();
+                if let Some(newest) = newest {
+                    overrides.0.insert("latest".to_string(), newest);
+                }
+            }
+        }
+
+        self.add_injector(&mut document, injector).await?;
+        self.add_extras(&mut document).await?;
+        document.warnings = std::mem::take(&mut self.warnings);
+        Ok((document, failed_versions))
+    }
+
+    /// Adds the authlib-injector build to the extras, checked against the checksum its source announced, unless the
+    /// run goes without it.
+    async fn add_injector(&mut self, document: &mut MetadataFile, injector: InjectorDownload<'_>) -> Result<(), GeneratorError> {
+        let config = self.config;
+        let bytes = match injector.response {
+            Some(Ok(response)) => Some(response.bytes().await),
+            Some(Err(why)) => Some(Err(why)),
+            None => None,
+        };
+        match bytes {
+            None => tracing::warn!("Leaving authlib-injector out of the output"),
+            Some(Ok(bytes)) => {
+                let mut algorithms = config.hashes.clone();
+                if injector.latest.is_some() {
+                    algorithms.push(HashAlgorithm::Sha256);
+                }
+                let digests = Digests::compute(&bytes, &algorithms);
+                if let Some(latest) = injector.latest
+                    && let Err(why) = verify_injector(latest, &digests, config.allow_unverified_injector) {
+                    self.span.set_error(&why);
+                    return Err(why);
+                }
+                let mut url = injector.url.clone();
+                if let Some((mirror, (root, _))) = self.mirror.as_ref().zip(config.mirror.as_ref()) {
+                    let path = mirror::injector_path(injector.url);
+                    mirror.put(&path, &bytes)
+                        .map_err(|source| GeneratorError::Io { action: "mirror authlib-injector to", path: root.clone(), source })?;
+                    url = mirror.url(&path);
+                }
+                document.extras.authlib_injector = Some(InjectorEntry {
+                    url: self.url_rewriter.rewrite(&url),
+                    version: injector.latest.map(|latest| latest.version.clone()),
+                    digests: digests.only(&config.hashes),
+                    size: bytes.len(),
+                });
+            }
+            Some(Err(why)) => {
+                self.span.set_error(&why);
+                self.reporter.record("Couldn't retrieve authlib-injector", &why, vec![("url", injector.url.clone())]);
+                if matches!(config.injector, InjectorPolicy::Required) {
+                    return Err(GeneratorError::Injector(why));
+                }
+                tracing::warn!("Couldn't retrieve authlib-injector, leaving it out of the output: {}", why);
+                self.warnings.push(format!("Omitted authlib-injector: {}", why));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the authlib-injector channels, the Ely.by endpoints and the extras of the configuration, leaving out and
+    /// warning about those that can't be retrieved.
+    async fn add_extras(&mut self, document: &mut MetadataFile) -> Result<(), GeneratorError> {
+        let config = self.config;
+        let url_rewriter = &self.url_rewriter;
+        let channels = config.injector_channels.iter()
+            .map(async |(name, channel)| (name, fetch_injector_channel(self.connection, channel, &config.hashes).await));
+        for (name, fetched) in futures::future::join_all(channels).await {
+            match fetched {
+                Ok((mut entry, bytes)) => {
+                    if let Some((mirror, (root, _))) = self.mirror.as_ref().zip(config.mirror.as_ref()) {
+                        let path = mirror::injector_channel_path(name, &entry.url);
+                        mirror.put(&path, &bytes)
+                            .map_err(|source| GeneratorError::Io { action: "mirror authlib-injector to", path: root.clone(), source })?;
+                        entry.url = mirror.url(&path);
+                    }
+                    entry.url = url_rewriter.rewrite(&entry.url);
+                    document.extras.injector_channels.insert(name.clone(), entry);
+                }
+                Err(why) => {
+                    tracing::warn!("Couldn't retrieve authlib-injector channel {}: {}", name, why);
+                    self.warnings.push(format!("Omitted authlib-injector channel {}: {}", name, why));
+                }
+            }
+        }
+
+        if config.ely_endpoints {
+            document.extras.ely_by = Some(ElyEndpoints {
+                authserver: url_rewriter.rewrite(&config.ely_authserver_url),
+                sessionserver: url_rewriter.rewrite(&config.ely_sessionserver_url),
+                skins: url_rewriter.rewrite(&config.ely_skins_url),
+            });
+        }
+
+        for (key, _) in config.extra.iter().chain(&config.extra_artifact) {
+            if Extras::is_reserved(key) {
+                self.warnings.push(format!("Omitted extra {}: the key is reserved", key));
+            }
+        }
+        for (key, value) in config.extra.iter().filter(|(key, _)| !Extras::is_reserved(key)) {
+            let is_url = value.starts_with("http://") || value.starts_with("https://");
+            if is_url && let Err(why) = download::probe_url(self.connection, value).await {
+                tracing::warn!("Couldn't reach extra {}: {}", key, why);
+                self.warnings.push(format!("Omitted extra {}: {} is unreachable", key, value));
+                continue
+            }
+            document.extras.other.insert(key.clone(), Extra::Value(if is_url { url_rewriter.rewrite(value) } else { value.clone() }));
+        }
+        for (key, url) in config.extra_artifact.iter().filter(|(key, _)| !Extras::is_reserved(key)) {
+            let response = download::fetch(self.connection, url, self.connection.limits.max_artifact_size).await;
+            match response {
+                Ok(bytes) => {
+                    document.extras.other.insert(key.clone(), Extra::Artifact(ArtifactEntry {
+                        url: url_rewriter.rewrite(url),
+                        sha1: hex::encode(Sha1::digest(&bytes)),
+                        size: bytes.len(),
+                    }));
+                }
+                Err(why) => {
+                    tracing::warn!("Couldn't download extra {}: {}", key, why);
+                    self.warnings.push(format!("Omitted extra {}: {} couldn't be downloaded", key, url));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes the output, unless it is identical to what is there, and the files that go with it. Returns `None`
+    /// when the output is left alone because it is identical to the baseline.
+    async fn write_outputs(&mut self, document: &MetadataFile, baseline: Option<&[u8]>, last_updated: Option<&str>)
+        -> Result<Option<Written>, GeneratorError> {
+        let config = self.config;
+        let output_file = &config.output_file;
+        let mut publish_span = self.span.child("publish");
+        publish_span.set_attribute("file.path", output_file.as_str());
+        let contents = if config.compact {
+            document.to_compact_json(config.format_version)
+        } else {
+            document.to_pretty_json(config.format_version)
+        };
+        let contents = match &config.merge {
+            Some(path) => {
+                let existing = std::fs::read_to_string(path)
+                    .map_err(|source| GeneratorError::Io { action: "read", path: path.clone(), source })?;
+                let merged = merge::merge(&existing, &contents).map_err(|why| GeneratorError::Merge { path: path.clone(), why })?;
+                if config.compact { merged.to_string() } else { serde_json::to_string_pretty(&merged).expect("JSON values are always serializable") }
+            }
+            None => contents,
+        };
+        // Checked after merging, as whatever the hand-maintained document brings in is published too
+        let violations = schema::validate(&serde_json::from_str(&contents).expect("documents are always valid JSON"));
+        if !violations.is_empty() {
+            publish_span.set_error("schema violation");
+            return Err(GeneratorError::SchemaViolation(violations));
+        }
+        if let (Some(url), Some(baseline)) = (&config.baseline_url, baseline) {
+            // Compared as JSON, so a baseline published minified or with its keys in another order still matches
+            let parse = |contents: &[u8]| serde_json::from_slice::<serde_json::Value>(contents).ok();
+            let unchanged = parse(baseline).is_some_and(|baseline| Some(baseline) == parse(contents.as_bytes()));
+            if !unchanged {
+                tracing::info!("Output differs from the baseline at {}", url);
+            } else if config.only_if_changed {
+                tracing::info!("Output is identical to the baseline at {}, leaving {} untouched", url, output_file);
+                publish_span.set_attribute("generation.skipped", "baseline");
+                return Ok(None);
+            } else {
+                tracing::info!("Output is identical to the baseline at {}", url);
+            }
+        }
+        let existing = if config.writes_to_stdout() { None } else { std::fs::read(output_file).ok() };
+        // Leaving an identical file alone keeps its modification time, which is what caches go by
+        let identical = !config.force && existing.as_deref() == Some(contents.as_bytes());
+        // What changed is only worked out for those told about it. A missing or unreadable output counts as empty
+        let hooked = !config.post_hooks.is_empty() || !config.version_hooks.is_empty();
+        let previous_document = (!identical && (config.notify_webhook.is_some() || config.changelog.is_some() || hooked))
+            .then(|| existing.and_then(|existing| MetadataFile::parse(&existing).ok()).unwrap_or_default());
+        let changes = previous_document.as_ref().map(|previous| document::diff_documents(previous, document)).unwrap_or_default();
+        if identical {
+            tracing::info!("Output unchanged, leaving {} untouched (use --force to rewrite it)", output_file);
+            publish_span.set_attribute("generation.skipped", "identical");
+        } else if config.writes_to_stdout() {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(contents.as_bytes()).and_then(|()| stdout.flush())
+                .map_err(|source| GeneratorError::Io { action: "write", path: PathBuf::from("stdout"), source })?;
+        } else {
+            write_replacing(output_file, &contents, config.output_mode, config.output_owner)?;
+        }
+        if let Some(path) = &config.changelog
+            && let Some(previous_document) = &previous_document {
+            changelog::append(path, Utc::now(), previous_document, document, &changes)
+                .map_err(|source| GeneratorError::Io { action: "append to", path: path.clone(), source })?;
+        }
+        let mut companions = Vec::new();
+        if let Some(key) = &config.signing_key {
+            companions.push((format!("{}.sig", output_file), sign_output(key, contents.as_bytes(), output_file)?));
+        }
+        if config.gzip {
+            companions.push((format!("{}.gz", output_file), gzip_output(contents.as_bytes(), output_file)?));
+        }
+        if let Some(meta_dir) = &config.meta_dir {
+            let fallback_time = last_updated.and_then(maven::parse_timestamp).unwrap_or_else(Utc::now);
+            prism::write_components(self.http_client, document, meta_dir, fallback_time).await?;
+        }
+        for (format, path) in &config.extra_outputs {
+            let contents = if config.compact { document.to_compact_json(*format) } else { document.to_pretty_json(*format) };
+            let violations = schema::validate(&serde_json::from_str(&contents).expect("documents are always valid JSON"));
+            if !violations.is_empty() {
+                publish_span.set_error("schema violation");
+                return Err(GeneratorError::SchemaViolation(violations));
+            }
+            let path = path.to_string_lossy();
+            if !config.force && std::fs::read(path.as_ref()).ok().as_deref() == Some(contents.as_bytes()) {
+                tracing::debug!("Format {} output unchanged, leaving {} untouched", format.number(), path);
+            } else {
+                write_replacing(&path, &contents, config.output_mode, config.output_owner)?;
+                tracing::info!("Wrote the format {} document to {}", format.number(), path);
+            }
+        }
+
+        Ok(Some(Written { contents, identical, companions, changes }))
+    }
+
+    /// Uploads the output and its companions wherever the configuration says, then tells the webhook and the hooks
+    /// what changed.
+    async fn publish(&mut self, written: &Written, uploader: Option<&Uploader>, github_release: Option<&github::Release>)
+        -> Result<(), GeneratorError> {
+        let config = self.config;
+        let output_file = &config.output_file;
+        let contents = &written.contents;
+        let companions = &written.companions;
+        if let Some(uploader) = uploader {
+            crash::set_phase("uploading");
+            let mut upload_span = self.span.child("upload");
+            // Artifacts go first, so the uploaded document never points to objects that aren't there yet
+            let mut uploaded = match &config.mirror {
+                Some((root, _)) => uploader.put_directory(root, upload::ARTIFACT_CACHE_CONTROL, &mut self.state.uploaded).await,
+                None => Ok(0),
+            };
+            if let Ok(count) = &mut uploaded {
+                let name = Path::new(output_file).file_name().unwrap_or_default().to_string_lossy();
+                match uploader.put_if_changed(&name, contents.as_bytes(), upload::DOCUMENT_CACHE_CONTROL).await {
+                    Ok(changed) => *count += usize::from(changed),
+                    Err(why) => uploaded = Err((name.into_owned(), why)),
+                }
+            }
+            for (path, companion) in companions {
+                let Ok(count) = &mut uploaded else {
+                    break
+                };
+                let name = Path::new(path).file_name().unwrap_or_default().to_string_lossy();
+                match uploader.put_if_changed(&name, companion, upload::DOCUMENT_CACHE_CONTROL).await {
+                    Ok(changed) => *count += usize::from(changed),
+                    Err(why) => uploaded = Err((name.into_owned(), why)),
+                }
+            }
+            match uploaded {
+                Ok(count) => upload_span.set_attribute("upload.objects", count),
+                Err((name, source)) => {
+                    upload_span.set_error(&source);
+                    // The state isn't saved, so the next run doesn't skip uploading
+                    return Err(GeneratorError::Upload { name, source });
+                }
+            }
+        }
+
+        if let Some(url) = &config.put_url {
+            crash::set_phase("uploading");
+            let mut put_span = self.span.child("put");
+            put_span.set_attribute("url.full", url.as_str());
+            let files = std::iter::once((url.clone(), contents.as_bytes()))
+                .chain(companions.iter().map(|(path, companion)| {
+                    let suffix = path.strip_prefix(output_file.as_str()).unwrap_or_default();
+                    (format!("{}{}", url, suffix), companion.as_slice())
+                }));
+            for (url, body) in files {
+                if let Err(source) = upload::put(self.http_client, &config.credentials, &url, body, upload::DOCUMENT_CACHE_CONTROL).await {
+                    put_span.set_error(&source);
+                    return Err(GeneratorError::Upload { name: url, source });
+                }
+            }
+        }
+
+        if let Some(release) = github_release {
+            crash::set_phase("publishing the release");
+            let mut release_span = self.span.child("publish release");
+            let file_name = |path: &str| Path::new(path).file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let mut assets = vec![(file_name(output_file), contents.as_bytes())];
+            assets.extend(companions.iter().map(|(path, companion)| (file_name(path), companion.as_slice())));
+            match release.publish(&assets).await {
+                Ok(count) => release_span.set_attribute("upload.objects", count),
+                Err((name, source)) => {
+                    release_span.set_error(&source);
+                    return Err(GeneratorError::Upload { name, source });
+                }
+            }
+        }
+
+        if let Some(webhook) = &config.notify_webhook
+            && !written.changes.is_empty() {
+            notify::send_changes(self.http_client, webhook, config.notify_format, output_file, config.output_url.as_deref(),
+                &written.changes).await;
+        }
+
+        let hooked = !config.post_hooks.is_empty() || !config.version_hooks.is_empty();
+        if hooked && !written.identical {
+            crash::set_phase("running hooks");
+            // Like a failed upload, a failed hook leaves the state alone so the next run tries again
+            hooks::run(&config.post_hooks, &config.version_hooks, output_file, config.output_url.as_deref(), &written.changes).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The full and the base versions listed in the configuration. Explicitly listed full versions don't need resolving,
+/// base versions do.
+fn listed_versions(config: &GeneratorConfig) -> (Vec<&str>, Vec<&str>) {
+    config.versions.iter().map(String::as_str).partition(|v| v.contains('-'))
+}
+
+/// Downloads the build of an authlib-injector channel, returning its entry, with the URL it was downloaded from,
+/// and its contents.
+/// Checks a downloaded authlib-injector against the SHA-256 published for the build it was resolved as, which must
+/// be there unless `allow_unverified` is set.
+fn verify_injector(latest: &injector::LatestInjector, digests: &Digests, allow_unverified: bool) -> Result<(), GeneratorError> {
+    let computed = digests.get(HashAlgorithm::Sha256).unwrap_or_default();
+    match &latest.sha256 {
+        Some(expected) if expected != computed => {
+            Err(GeneratorError::InjectorChecksum { expected: expected.clone(), computed: computed.to_string() })
+        }
+        Some(_) => Ok(()),
+        None if allow_unverified => {
+            tracing::warn!("No SHA-256 is published for authlib-injector {}, taking it unverified", latest.version);
+            Ok(())
+        }
+        None => Err(GeneratorError::UnverifiedInjector(latest.version.clone())),
+    }
+}
+
+async fn fetch_injector_channel(connection: &download::Connection<'_>, channel: &InjectorChannel, hashes: &[HashAlgorithm])
+    -> Result<(InjectorEntry, Bytes), String> {
+    let (url, version, expected_sha256) = match channel {
+        InjectorChannel::Download(url) => (url.clone(), injector::version_from_url(url), None),
+        InjectorChannel::Api(api) => {
+            let latest = injector::latest(connection.client, connection.retry, api).await.map_err(|why| why.to_string())?;
+            (latest.download_url, Some(latest.version), latest.sha256)
+        }
+    };
+    let bytes = download::fetch_cached(connection, &url).await.map_err(|why| why.to_string())?;
+
+    let mut algorithms = hashes.to_vec();
+    algorithms.push(HashAlgorithm::Sha256);
+    let digests = Digests::compute(&bytes, &algorithms);
+    if let Some(expected) = &expected_sha256
+        && digests.get(HashAlgorithm::Sha256) != Some(expected) {
+        return Err(format!("its SHA-256 is {} but its API announced {}", digests.get(HashAlgorithm::Sha256).unwrap_or_default(), expected))
+    }
+
+    Ok((InjectorEntry { url, version, digests: digests.only(hashes), size: bytes.len() }, bytes))
+}
+
+/// Signs the written output, putting the signature next to it as `OUTPUT.sig` unless the one there is the same.
+fn sign_output(private_key: &str, contents: &[u8], output_file: &str) -> Result<Vec<u8>, GeneratorError> {
+    let signature = signature::sign_ed25519(private_key, contents).map_err(GeneratorError::Signing)?;
+    let path = PathBuf::from(format!("{}.sig", output_file));
+    // Ed25519 signatures are deterministic, so an unchanged output keeps its signature file untouched too
+    if std::fs::read(&path).ok().as_ref() != Some(&signature) {
+        std::fs::write(&path, &signature).map_err(|source| GeneratorError::Io { action: "write", path, source })?;
+    }
+    Ok(signature)
+}
+
+/// Gzips the output, putting it next to the output as `OUTPUT.gz` unless the one there is the same.
+fn gzip_output(contents: &[u8], output_file: &str) -> Result<Vec<u8>, GeneratorError> {
+    let path = PathBuf::from(format!("{}.gz", output_file));
+    // The header carries no name or modification time, so the same output compresses the same every time
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    let compressed = encoder.write_all(contents).and_then(|()| encoder.finish())
+        .map_err(|source| GeneratorError::Io { action: "compress into", path: path.clone(), source })?;
+
+    if std::fs::read(&path).ok().as_ref() != Some(&compressed) {
+        std::fs::write(&path, &compressed).map_err(|source| GeneratorError::Io { action: "write", path: path.clone(), source })?;
+    }
+    Ok(compressed)
+}
+
+async fn send_drift_alert(client: &reqwest::Client, webhook: &str, drifts: &[HashDrift]) {
+    #[derive(Serialize)]
+    struct Alert<'a> {
+        event: &'static str,
+        severity: &'static str,
+        changed: Vec<Changed<'a>>,
+    }
+    #[derive(Serialize)]
+    struct Changed<'a> {
+        version: &'a str,
+        recorded: &'a str,
+        computed: &'a str,
+    }
+    let body = Alert {
+        event: "hash_drift",
+        severity: "high",
+        changed: drifts.iter()
+            .map(|drift| Changed { version: &drift.full_version, recorded: &drift.recorded, computed: &drift.computed })
+            .collect(),
+    };
+
+    let response = client.post(webhook)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(serde_json::to_string(&body).expect("alerts are always serializable"))
+        .send().await
+        .and_then(|r| r.error_for_status());
+    if let Err(why) = response {
+        tracing::warn!("Couldn't send hash drift alert: {}", why);
+    }
+}
+
+/// URLs, digests and sizes of the complete override entries of a document by artifact name.
+fn reusable_entries(document: &MetadataFile) -> HashMap<String, ReusableEntry> {
+    document.overrides.values().flat_map(|overrides| overrides.0.values())
+        .map(|entry| {
+            let release_time = entry.release_time.as_deref()
+                .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+                .map(|time| time.with_timezone(&Utc));
+            (entry.name.clone(), (entry.url.clone(), entry.digests.clone(), entry.size, release_time))
+        })
+        .collect()
+}
+
+/// The URL, digests, size and release time of an entry of a previous output.
+type ReusableEntry = (String, Digests, usize, Option<DateTime<Utc>>);
+
+/// The versions listed in a repository, when its metadata was last updated, and when its newest version was.
+struct ListedVersions {
+    versions: Vec<String>,
+    last_updated: Option<String>,
+    newest_release_time: Option<(String, DateTime<Utc>)>,
+}
+
+/// Reads the versions listed in the Maven metadata at `url`.
+async fn fetch_versions(connection: &Connection<'_>, url: &str) -> Result<ListedVersions, GeneratorError> {
+    let response = download::fetch_cached(connection, url).await
+        .map_err(|source| GeneratorError::Download { what: format!("Maven metadata from {}", url), source })?;
+    let metadata = maven::declare_unknown_prefixes(String::from_utf8_lossy(&response).into_owned());
+    let metadata_doc = maven::parse(&metadata)?;
+    let maven_metadata = maven::read_metadata(&metadata_doc)?;
+    for warning in &maven_metadata.warnings {
+        tracing::warn!("{}: {}", url, warning);
+    }
+    Ok(ListedVersions {
+        versions: maven_metadata.versions.iter().map(|version| version.to_string()).collect(),
+        last_updated: maven_metadata.last_updated.map(str::to_string),
+        newest_release_time: newest_release_time(&maven_metadata),
+    })
+}
+
+/// The newest version in Maven metadata, with its `<lastUpdated>` as when it was published. Deploying a version
+/// updates the timestamp, so it's only known to be right for the newest one.
+fn newest_release_time(metadata: &MavenMetadata) -> Option<(String, DateTime<Utc>)> {
+    let updated = maven::parse_timestamp(metadata.last_updated?)?;
+    let newest = metadata.versions.iter().copied().max_by(|a, b| compare_versions(a, b))?;
+    Some((newest.to_string(), updated))
+}
+
+/// Compares generated overrides with those of a reference document, returning a description of
+/// every disagreement. Versions only one side covers are reported too.
+fn check_parity(generated: &Overrides, reference: &Overrides) -> Vec<String> {
+    document::diff_overrides(reference, generated).into_iter()
+        .filter_map(|difference| match difference {
+            Difference::Added(version) => Some(format!("Parity: {} is missing from the reference", version)),
+            Difference::Removed(version) => Some(format!("Parity: {} is only in the reference", version)),
+            // Fields the reference leaves out aren't disagreements
+            Difference::Changed { old, .. } if old.is_null() => None,
+            Difference::Changed { version, field, old, new } => {
+                Some(format!("Parity: {} of {} is {} here but {} in the reference", field, version, new, old))
+            }
+        })
+        .collect()
+}
+
+/// The version of the overridden library a full version is an override for: by the first rule whose pattern matches
+/// it, its target with `$1`, `${name}` and so on replaced by what the pattern captured, or else the part before the
+/// first dash, so `5.17.31-ely.2` is an override for `5.17.31`.
+fn target_version(rules: &[(Regex, String)], full_version: &str) -> String {
+    for (pattern, target) in rules {
+        if let Some(captures) = pattern.captures(full_version) {
+            let mut expanded = String::new();
+            captures.expand(target, &mut expanded);
+            return expanded
+        }
+    }
+    full_version.split('-').next().unwrap_or(full_version).to_string()
+}
+
+/// Key of an artifact in the state file. The patched authlib is keyed by its full version, as it
+/// always was, other libraries by their Maven coordinates so their versions can't collide with it.
+fn state_key<'a>(config: &GeneratorConfig, library: &str, full_version: &'a str, name: &'a str) -> &'a str {
+    if library == config.override_key { full_version } else { name }
+}
+
+/// Formats an age in whole days, or whole hours if it is less than a day.
+fn format_age(age: Duration) -> String {
+    match age.as_secs() / 86400 {
+        0 => format!("{} hours", age.as_secs() / 3600),
+        days => format!("{} days", days),
+    }
+}
+
+/// Writes `contents` next to `path`, flushes it to disk and renames it over `path` with the given mode and owner, so
+/// the file never ends up truncated, not even by a crash of the system. What was written is removed if anything fails
+pub fn write_replacing(path: &str, contents: &str, mode: Option<u32>, owner: Option<(Option<u32>, Option<u32>)>)
+    -> Result<(), GeneratorError> {
+    let temporary = format!("{}.tmp", path);
+    let written = std::fs::File::create(&temporary)
+        .and_then(|mut file| file.write_all(contents.as_bytes()).and_then(|()| file.sync_all()))
+        .map_err(|source| GeneratorError::Io { action: "write", path: PathBuf::from(&temporary), source })
+        .and_then(|()| set_output_permissions(&temporary, mode, owner)
+            .map_err(|source| GeneratorError::Io { action: "set permissions of", path: PathBuf::from(path), source }))
+        .and_then(|()| std::fs::rename(&temporary, path)
+            .map_err(|source| GeneratorError::Io { action: "replace", path: PathBuf::from(path), source }));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temporary);
+    }
+    written
+}
+
+#[cfg(unix)]
+fn set_output_permissions(path: &str, mode: Option<u32>, owner: Option<(Option<u32>, Option<u32>)>) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Some(mode) = mode {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    }
+    if let Some((uid, gid)) = owner {
+        std::os::unix::fs::chown(path, uid, gid)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_output_permissions(_path: &str, mode: Option<u32>, owner: Option<(Option<u32>, Option<u32>)>) -> std::io::Result<()> {
+    if mode.is_some() || owner.is_some() {
+        return Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "file modes and owners are only supported on Unix"))
+    }
+
+    Ok(())
+}
+
+/// Placeholders download URL format strings may contain, `{}` being the same as `{version}`
+pub const URL_PLACEHOLDERS: [&str; 5] = ["version", "artifact", "group_path", "classifier", "extension"];
+
+#[derive(Debug, PartialEq)]
+enum TemplatePart<'a> {
+    Text(&'a str),
+    /// The name between the braces, empty for `{}`
+    Placeholder(&'a str),
+}
+
+/// Splits a format string into text and placeholders. As in Rust format strings, `{{` and `}}` stand for
+/// literal braces; braces around anything but a lowercase name are left as they are.
+fn parse_url_template(template: &str) -> Vec<TemplatePart<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(brace) = rest.find(['{', '}']) {
+        parts.push(TemplatePart::Text(&rest[..brace]));
+        rest = &rest[brace..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            parts.push(TemplatePart::Text(&rest[..1]));
+            rest = &rest[2..];
+            continue
+        }
+
+        let name = rest[1..].find('}').map(|end| &rest[1..end + 1])
+            .filter(|name| name.chars().all(|c| c.is_ascii_lowercase() || c == '_'));
+        match name {
+            Some(name) => {
+                parts.push(TemplatePart::Placeholder(name));
+                rest = &rest[name.len() + 2..];
+            }
+            None => {
+                parts.push(TemplatePart::Text(&rest[..1]));
+                rest = &rest[1..];
+            }
+        }
+    }
+    parts.push(TemplatePart::Text(rest));
+
+    parts
+}
+
+/// Checks that a download URL format string only contains known placeholders, and at least one of them.
+pub fn check_url_template(template: &str) -> Result<(), String> {
+    let mut placeholders = 0;
+    for part in parse_url_template(template) {
+        if let TemplatePart::Placeholder(name) = part {
+            if !name.is_empty() && !URL_PLACEHOLDERS.contains(&name) {
+                return Err(format!("unknown placeholder `{{{}}}`, expected {{}} or one of {}", name,
+                    URL_PLACEHOLDERS.map(|known| format!("{{{}}}", known)).join(", ")))
+            }
+            placeholders += 1;
+        }
+    }
+    if placeholders == 0 {
+        return Err("no placeholder, every version would get the same URL".to_string())
+    }
+
+    Ok(())
+}
+
+/// Replaces the placeholders of a download URL format string with what they stand for in a version of the
+/// `group:artifact` artifact: `{}` and `{version}` with the version, `{artifact}` with the artifact, `{group_path}`
+/// with the group as a path (`com/mojang`), and `{classifier}` and `{extension}` with those of its file, the
+/// classifier being empty if there is none.
+pub fn expand_url_template(template: &str, artifact: &str, version: &str, packaging: &Packaging) -> String {
+    let (group, artifact) = artifact.split_once(':').unwrap_or(("", artifact));
+    let mut url = String::with_capacity(template.len() + version.len());
+    for part in parse_url_template(template) {
+        match part {
+            TemplatePart::Text(text) => url.push_str(text),
+            TemplatePart::Placeholder("" | "version") => url.push_str(version),
+            TemplatePart::Placeholder("artifact") => url.push_str(artifact),
+            TemplatePart::Placeholder("group_path") => url.push_str(&group.replace('.', "/")),
+            TemplatePart::Placeholder("classifier") => url.push_str(packaging.classifier.as_deref().unwrap_or_default()),
+            TemplatePart::Placeholder("extension") => url.push_str(&packaging.extension),
+            // Rejected by `check_url_template`, kept as written otherwise
+            TemplatePart::Placeholder(name) => url.push_str(&format!("{{{}}}", name)),
+        }
+    }
+
+    url
+}
+
+/// Parses an exclusions file into a map of full version to the (optional) reason it was excluded.
+/// Blank lines and lines starting with `#` are ignored.
+fn parse_exclusions(contents: &str) -> HashMap<String, Option<String>> {
+    contents.lines().filter_map(|line| {
+        let (version, reason) = match line.split_once('#') {
+            Some((version, reason)) => (version.trim(), Some(reason.trim())),
+            None => (line.trim(), None),
+        };
+        if version.is_empty() {
+            return None
+        }
+
+        let reason = reason.filter(|r| !r.is_empty()).map(str::to_string);
+        Some((version.to_string(), reason))
+    }).collect()
+}
+
+/// Ordered regex replacements applied to every URL written to the output.
+struct UrlRewriter {
+    rules: Vec<(Regex, String)>,
+}
+
+impl UrlRewriter {
+    fn new(rules: &[(String, String)]) -> Result<UrlRewriter, regex::Error> {
+        let rules = rules.iter()
+            .map(|(pattern, replacement)| Ok((Regex::new(pattern)?, replacement.clone())))
+            .collect::<Result<_, regex::Error>>()?;
+
+        Ok(UrlRewriter { rules })
+    }
+
+    fn rewrite(&self, url: &str) -> String {
+        self.rules.iter().fold(url.to_string(), |url, (pattern, replacement)| {
+            pattern.replace(&url, replacement.as_str()).into_owned()
+        })
+    }
+}
+
+/// An artifact that is going to be downloaded and hashed.
+pub struct PlannedArtifact {
+    /// Base version the artifact is an override for, e.g. `5.17.31`
+    pub target_version: String,
+    /// Version as published, e.g. `5.17.31-ely.2`
+    pub full_version: String,
+    /// Maven coordinates written to the output
+    pub name: String,
+    pub url: String,
+    /// Where else to download the artifact if `url` fails, in order
+    pub fallback_urls: Vec<String>,
+    /// Unpatched Mojang authlib standing in for a version with no patched build
+    pub vanilla: bool,
+    /// `group:artifact` of the library the artifact overrides
+    pub library: String,
+    /// When the version was published, if the version listing says
+    pub release_time: Option<DateTime<Utc>>,
+}
+
+/// Where the versions of the patched authlib are listed, see [`GeneratorConfig::source`].
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+pub enum VersionSource {
+    /// The Maven metadata at `metadata_url`, or the search API with `search_fallback`
+    #[default]
+    Maven,
+    /// The builds API at `elyby_api_url`, along with the download URL of every build
+    ElybyApi,
+}
+
+/// What to do about authlib-injector, see [`GeneratorConfig::injector`].
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+pub enum InjectorPolicy {
+    /// Write no output if authlib-injector can't be retrieved
+    #[default]
+    Required,
+    /// Write the output without authlib-injector if it can't be retrieved
+    Optional,
+    /// Don't retrieve authlib-injector, leaving it out of the output
+    Skip,
+}
+
+/// Where the builds of an authlib-injector channel come from.
+pub enum InjectorChannel {
+    /// A fixed download URL, the version taken from its file name if it is in the usual `authlib-injector-VERSION.jar`
+    Download(String),
+    /// An authlib-injector API to take the latest build from, as with `injector_api`
+    Api(String),
+}
+
+/// What became of the versions of a run.
+#[derive(Default)]
+struct Summary {
+    generated: usize,
+    reused: usize,
+    /// Versions left out, by reason
+    skipped: IndexMap<&'static str, usize>,
+    /// Every version that was downloaded or reused, or failed to be, in planned order
+    rows: Vec<SummaryRow>,
+    /// Versions left out while planning, with the reason
+    left_out: Vec<(String, &'static str)>,
+    /// What the output holds once the run is over, if it got to know
+    document: Option<String>,
+}
+
+struct SummaryRow {
+    library: String,
+    target_version: String,
+    full_version: String,
+    size: Option<usize>,
+    /// `hashed`, `reused`, `failed` or `over budget`
+    status: &'static str,
+    /// Why the version failed
+    error: Option<String>,
+    /// Spent downloading and hashing the version, or finding it can be reused
+    duration: Duration,
+}
+
+impl Summary {
+    fn skip(&mut self, reason: &'static str) {
+        *self.skipped.entry(reason).or_default() += 1;
+    }
+
+    /// Skips a version that doesn't even get planned, remembering which one it was.
+    fn leave_out(&mut self, version: &str, reason: &'static str) {
+        self.skip(reason);
+        self.left_out.push((version.to_string(), reason));
+    }
+
+    /// What the run did for `--report`: how it ended, how long it took, what it downloaded, every version and what
+    /// became of it, and the output it left.
+    fn report<'a>(&'a self, result: &Result<ExitStatus, GeneratorError>, duration: Duration, downloaded_bytes: u64,
+        output: Option<(&'a str, String)>) -> Report<'a> {
+        let mut versions = Vec::new();
+        for row in &self.rows {
+            let (status, reason) = match row.status {
+                "hashed" => ("generated", None),
+                "over budget" | "not processed" => ("skipped", Some(row.status)),
+                status => (status, row.error.as_deref()),
+            };
+            versions.push(ReportedVersion::Planned {
+                library: &row.library,
+                version: &row.target_version,
+                full_version: &row.full_version,
+                status,
+                reason,
+                size: row.size,
+                duration_seconds: row.duration.as_secs_f64(),
+            });
+        }
+        for (version, reason) in &self.left_out {
+            versions.push(ReportedVersion::LeftOut { full_version: version, status: "skipped", reason });
+        }
+
+        let status = result.as_ref().map_or_else(GeneratorError::status, |status| *status);
+        Report {
+            status: status.name(),
+            exit_code: status.code(),
+            error: result.as_ref().err().map(|why| logging::redact(&why.to_string())),
+            duration_seconds: duration.as_secs_f64(),
+            downloaded_bytes,
+            generated: self.generated,
+            reused: self.reused,
+            skipped: self.skipped.values().sum(),
+            versions,
+            output: output.map(|(path, sha256)| ReportedOutput { path, sha256 }),
+        }
+    }
+
+    /// The rows as a table with a line of totals, colored with ANSI escapes if `color`. The library column is left
+    /// out when there is only the patched authlib.
+    fn table(&self, color: bool) -> String {
+        let paint = |code: &str, text: String| if color { format!("\x1b[{}m{}\x1b[0m", code, text) } else { text };
+        let with_library = self.rows.iter().any(|row| row.library != self.rows[0].library);
+        let mut lines: Vec<Vec<String>> = vec![
+            ["Library", "Version", "Full version", "Size", "Status"].map(String::from).to_vec(),
+        ];
+        for row in &self.rows {
+            let size = row.size.map_or_else(|| "-".to_string(), |size| format_size(size as u64));
+            lines.push(vec![row.library.clone(), row.target_version.clone(), row.full_version.clone(), size, row.status.to_string()]);
+        }
+        if !with_library {
+            for line in &mut lines {
+                line.remove(0);
+            }
+        }
+        let columns = lines[0].len();
+        let widths: Vec<usize> = (0..columns)
+            .map(|column| lines.iter().map(|line| line[column].chars().count()).max().unwrap_or(0))
+            .collect();
+
+        let mut table = String::new();
+        for (index, line) in lines.iter().enumerate() {
+            let cells: Vec<String> = line.iter().zip(&widths).enumerate()
+                .map(|(column, (cell, width))| {
+                    // Sizes are right aligned, so their units line up
+                    let padded = if column == columns - 2 { format!("{:>1$}", cell, width) } else { format!("{:<1$}", cell, width) };
+                    match (index, cell.as_str()) {
+                        (0, _) => paint("1", padded),
+                        (_, "hashed") if column == columns - 1 => paint("32", padded),
+                        (_, "reused") if column == columns - 1 => paint("36", padded),
+                        (_, "failed" | "over budget" | "not processed") if column == columns - 1 => paint("31", padded),
+                        _ => padded,
+                    }
+                })
+                .collect();
+            table.push_str(cells.join("  ").trim_end());
+            table.push('\n');
+        }
+        let total: usize = self.rows.iter().filter_map(|row| row.size).sum();
+        table.push_str(&format!("{}, {} in total\n", self, format_size(total as u64)));
+        table
+    }
+}
+
+/// Formats a size in bytes, KiB, MiB or GiB, whichever keeps it short.
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{} B", bytes),
+        1024..1_048_576 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        1_048_576..1_073_741_824 => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+        _ => format!("{:.1} GiB", bytes as f64 / 1_073_741_824.0),
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Generated {} version(s), reused {}, skipped {}", self.generated, self.reused, self.skipped.values().sum::<usize>())?;
+        if !self.skipped.is_empty() {
+            let reasons: Vec<_> = self.skipped.iter().map(|(reason, count)| format!("{} {}", count, reason)).collect();
+            write!(f, " ({})", reasons.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// What `--report` writes.
+#[derive(Serialize)]
+struct Report<'a> {
+    status: &'static str,
+    #[serde(rename = "exitCode")]
+    exit_code: i32,
+    error: Option<String>,
+    #[serde(rename = "durationSeconds")]
+    duration_seconds: f64,
+    #[serde(rename = "downloadedBytes")]
+    downloaded_bytes: u64,
+    generated: usize,
+    reused: usize,
+    skipped: usize,
+    versions: Vec<ReportedVersion<'a>>,
+    output: Option<ReportedOutput<'a>>,
+}
+
+/// A version in the report: one the run planned, or one left out before planning.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ReportedVersion<'a> {
+    Planned {
+        library: &'a str,
+        version: &'a str,
+        #[serde(rename = "fullVersion")]
+        full_version: &'a str,
+        status: &'a str,
+        reason: Option<&'a str>,
+        size: Option<usize>,
+        #[serde(rename = "durationSeconds")]
+        duration_seconds: f64,
+    },
+    LeftOut {
+        #[serde(rename = "fullVersion")]
+        full_version: &'a str,
+        status: &'static str,
+        reason: &'static str,
+    },
+}
+
+#[derive(Serialize)]
+struct ReportedOutput<'a> {
+    path: &'a str,
+    sha256: String,
+}
+
+/// A hashed artifact, as written to the output.
+pub struct LibraryOverride {
+    pub target_version: String,
+    pub full_version: String,
+    pub name: String,
+    pub url: String,
+    pub digests: Digests,
+    pub size: usize,
+    pub vanilla: bool,
+    pub library: String,
+    /// `Last-Modified` of the artifact, or when the version listing says it was published
+    pub release_time: Option<DateTime<Utc>>,
+    /// Taken from the interrupted run or the previous output instead of hashed again
+    pub reused: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn latest_injector(sha256: Option<&str>) -> injector::LatestInjector {
+        injector::LatestInjector {
+            version: "1.2.5".to_string(),
+            download_url: "https://authlib-injector.yushi.moe/artifact/53/authlib-injector-1.2.5.jar".to_string(),
+            sha256: sha256.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn refuses_an_injector_not_matching_its_published_checksum() {
+        let digests = Digests::compute(b"what was downloaded", &[HashAlgorithm::Sha256]);
+        let announced = Digests::compute(b"what was released", &[HashAlgorithm::Sha256]).sha256;
+        let why = verify_injector(&latest_injector(announced.as_deref()), &digests, true).unwrap_err();
+        assert!(matches!(why, GeneratorError::InjectorChecksum { expected, computed }
+            if announced.as_ref() == Some(&expected) && digests.sha256.as_ref() == Some(&computed)));
+
+        assert!(verify_injector(&latest_injector(digests.sha256.as_deref()), &digests, false).is_ok());
+    }
+
+    #[test]
+    fn refuses_an_injector_without_a_published_checksum_unless_allowed() {
+        let digests = Digests::compute(b"authlib-injector", &[HashAlgorithm::Sha256]);
+        let why = verify_injector(&latest_injector(None), &digests, false).unwrap_err();
+        assert!(matches!(why, GeneratorError::UnverifiedInjector(version) if version == "1.2.5"));
+
+        assert!(verify_injector(&latest_injector(None), &digests, true).is_ok());
+    }
+
+    #[test]
+    fn reads_excluded_versions_with_their_reasons() {
+        let exclusions = parse_exclusions("5.17.31-ely.1 # broken signature\n3.11.49-ely.2\n");
+        assert_eq!(exclusions.len(), 2);
+        assert_eq!(exclusions["5.17.31-ely.1"].as_deref(), Some("broken signature"));
+        assert_eq!(exclusions["3.11.49-ely.2"], None);
+    }
+
+    #[test]
+    fn ignores_blank_lines_and_comments() {
+        let exclusions = parse_exclusions("\n   \n# pulled from upstream\n  # indented comment\n1.5.21-ely.2\n\n");
+        assert_eq!(exclusions.keys().collect::<Vec<_>>(), ["1.5.21-ely.2"]);
+    }
+
+    #[test]
+    fn treats_an_empty_reason_as_none() {
+        let exclusions = parse_exclusions("  5.17.31-ely.2  #   \n");
+        assert_eq!(exclusions["5.17.31-ely.2"], None);
+    }
+
+    #[test]
+    fn splits_url_templates_into_text_and_placeholders() {
+        let parts = parse_url_template("https://maven.ely.by/{group_path}/{}/authlib-{version}.jar");
+        assert_eq!(parts, [
+            TemplatePart::Text("https://maven.ely.by/"),
+            TemplatePart::Placeholder("group_path"),
+            TemplatePart::Text("/"),
+            TemplatePart::Placeholder(""),
+            TemplatePart::Text("/authlib-"),
+            TemplatePart::Placeholder("version"),
+            TemplatePart::Text(".jar"),
+        ]);
+    }
+
+    #[test]
+    fn reads_doubled_braces_as_literal_ones() {
+        let parts = parse_url_template("a{{b}}{}");
+        assert_eq!(parts, [
+            TemplatePart::Text("a"),
+            TemplatePart::Text("{"),
+            TemplatePart::Text("b"),
+            TemplatePart::Text("}"),
+            TemplatePart::Text(""),
+            TemplatePart::Placeholder(""),
+            TemplatePart::Text(""),
+        ]);
+        assert_eq!(expand_url_template("https://host/{{{}}}.jar", "by.ely:authlib", "1.0", &Packaging::default()),
+            "https://host/{1.0}.jar");
+    }
+
+    #[test]
+    fn leaves_braces_around_anything_but_a_lowercase_name_alone() {
+        let expanded = expand_url_template("https://host/{Version}/{a.b}/{}/{", "by.ely:authlib", "1.0", &Packaging::default());
+        assert_eq!(expanded, "https://host/{Version}/{a.b}/1.0/{");
+    }
+
+    #[test]
+    fn expands_every_known_placeholder() {
+        let packaging = Packaging { classifier: Some("sources".to_string()), extension: "zip".to_string() };
+        let expanded = expand_url_template("https://host/{group_path}/{artifact}/{version}/{artifact}-{}-{classifier}.{extension}",
+            "com.mojang:authlib", "5.17.31", &packaging);
+        assert_eq!(expanded, "https://host/com/mojang/authlib/5.17.31/authlib-5.17.31-sources.zip");
+        assert_eq!(expand_url_template("https://host/{}-{classifier}.jar", "a:b", "1", &Packaging::default()), "https://host/1-.jar");
+    }
+
+    #[test]
+    fn accepts_templates_with_known_placeholders() {
+        assert_eq!(check_url_template("https://host/{}.jar"), Ok(()));
+        assert_eq!(check_url_template("https://host/{group_path}/{artifact}/{version}/{artifact}-{version}.{extension}"), Ok(()));
+        assert_eq!(check_url_template("https://host/{{literal}}/{}-{classifier}.jar"), Ok(()));
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        let why = check_url_template("https://host/{versoin}.jar").unwrap_err();
+        assert!(why.starts_with("unknown placeholder `{versoin}`"), "{}", why);
+        assert!(why.contains("{group_path}"), "{}", why);
+    }
+
+    #[test]
+    fn rejects_templates_without_placeholders() {
+        assert!(check_url_template("https://host/authlib.jar").is_err());
+        // Escaped and malformed braces aren't placeholders
+        assert!(check_url_template("https://host/{{}}/{Version}.jar").is_err());
+    }
+}
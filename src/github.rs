@@ -0,0 +1,132 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Publishing the output as the assets of a GitHub release.
+
+use crate::upload::{content_type, UploadError};
+use reqwest::{Method, Url};
+use sha2::{Digest, Sha256};
+
+/// A release of a GitHub repository, by its tag, with the token to change it.
+pub struct Release {
+    client: reqwest::Client,
+    api: String,
+    repository: String,
+    tag: String,
+    token: String,
+}
+
+impl Release {
+    pub fn new(client: &reqwest::Client, repository: &str, tag: &str, token: Option<&str>, api: &str) -> Result<Release, String> {
+        let valid = repository.split_once('/')
+            .is_some_and(|(owner, name)| !owner.is_empty() && !name.is_empty() && !name.contains('/'));
+        if !valid {
+            return Err(format!("expected OWNER/REPO, got `{}`", repository))
+        }
+        let token = token.ok_or("no GitHub token, set GITHUB_TOKEN")?;
+
+        Ok(Release {
+            client: client.clone(),
+            api: api.trim_end_matches('/').to_string(),
+            repository: repository.to_string(),
+            tag: tag.to_string(),
+            token: token.to_string(),
+        })
+    }
+
+    /// Uploads files as assets of the release, creating the release if the tag has none, and replacing assets of the
+    /// same name unless GitHub reports the same SHA-256 for them. Returns how many were uploaded.
+    pub async fn publish(&self, assets: &[(String, &[u8])]) -> Result<usize, (String, UploadError)> {
+        let release_name = format!("release {}", self.tag);
+        let release = self.release().await.map_err(|why| (release_name, why))?;
+        let upload_url = release["upload_url"].as_str()
+            .and_then(|url| url.split('{').next())
+            .and_then(|url| Url::parse(url).ok())
+            .ok_or_else(|| (format!("release {}", self.tag), malformed("no upload_url")))?;
+        let existing = release["assets"].as_array().cloned().unwrap_or_default();
+
+        let mut uploaded = 0;
+        for (name, body) in assets {
+            let digest = format!("sha256:{}", hex::encode(Sha256::digest(body)));
+            let current = existing.iter().find(|asset| asset["name"].as_str() == Some(name.as_str()));
+            if let Some(current) = current {
+                if current["digest"].as_str() == Some(digest.as_str()) {
+                    continue
+                }
+                // Assets can't be overwritten, only deleted and uploaded again
+                let url = format!("{}/repos/{}/releases/assets/{}", self.api, self.repository, current["id"]);
+                send(self.request(Method::DELETE, &url)).await.map_err(|why| (name.clone(), why))?;
+            }
+
+            let mut url = upload_url.clone();
+            url.query_pairs_mut().append_pair("name", name);
+            let request = self.request(Method::POST, url.as_str())
+                .header(reqwest::header::CONTENT_TYPE, content_type(name))
+                .body(body.to_vec());
+            send(request).await.map_err(|why| (name.clone(), why))?;
+            uploaded += 1;
+        }
+
+        Ok(uploaded)
+    }
+
+    /// The release of the tag, created if there is none.
+    async fn release(&self) -> Result<serde_json::Value, UploadError> {
+        let url = format!("{}/repos/{}/releases/tags/{}", self.api, self.repository, self.tag);
+        let response = self.request(Method::GET, &url).send().await?;
+        if response.status() != reqwest::StatusCode::NOT_FOUND {
+            return parse(check(response).await?).await
+        }
+
+        tracing::info!("Creating release {} of {}", self.tag, self.repository);
+        let body = serde_json::json!({ "tag_name": self.tag, "name": self.tag });
+        let request = self.request(Method::POST, &format!("{}/repos/{}/releases", self.api, self.repository))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string());
+        parse(send(request).await?).await
+    }
+
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.client.request(method, url)
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, UploadError> {
+    check(request.send().await?).await
+}
+
+async fn check(response: reqwest::Response) -> Result<reqwest::Response, UploadError> {
+    if response.status().is_success() {
+        return Ok(response)
+    }
+    let status = response.status();
+    let message = response.text().await.unwrap_or_default();
+    Err(UploadError::Status { status, message: message.trim().to_string() })
+}
+
+async fn parse(response: reqwest::Response) -> Result<serde_json::Value, UploadError> {
+    let body = response.bytes().await?;
+    serde_json::from_slice(&body).map_err(|why| malformed(&why.to_string()))
+}
+
+fn malformed(why: &str) -> UploadError {
+    UploadError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, format!("unexpected GitHub API response: {}", why)))
+}
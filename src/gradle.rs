@@ -0,0 +1,75 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Looking artifacts up in the Gradle module metadata (`.module`) published next to them, which lists the files of
+//! every variant with their size and digests:
+//!
+//! ```json
+//! { "formatVersion": "1.1", "variants": [{ "name": "runtimeElements", "files": [
+//!     { "name": "authlib-5.17.31.jar", "url": "authlib-5.17.31.jar", "size": 123, "sha1": "...", "sha256": "..." }
+//! ] }] }
+//! ```
+
+use crate::download::{self, Connection};
+use crate::hashing::{Digests, HashAlgorithm};
+use crate::maven::Packaging;
+
+/// A file of a variant, as the module metadata describes it.
+pub struct ModuleFile {
+    /// Where the file is, resolved against the module metadata's URL
+    pub url: String,
+    pub digests: Digests,
+    pub size: usize,
+}
+
+/// Looks the artifact at `url` up in the module metadata of its version, `None` if there is none, it doesn't list the
+/// artifact, or it lacks its size or any of the digests of `algorithms`.
+pub async fn find_artifact(connection: &Connection<'_>, url: &str, packaging: &Packaging, algorithms: &[HashAlgorithm])
+    -> Option<ModuleFile> {
+    let (directory, file_name) = url.rsplit_once('/')?;
+    // Classified artifacts are variants of the same module, e.g. authlib-1.0-all.jar of authlib-1.0.module
+    let module_url = format!("{}/{}.module", directory, file_name.strip_suffix(&packaging.file_suffix())?);
+    let body = download::fetch_cached(connection, &module_url).await
+        .inspect_err(|why| tracing::debug!("No module metadata at {}: {}", module_url, why))
+        .ok()?;
+    let module: serde_json::Value = serde_json::from_slice(&body).ok()?;
+
+    let file = module["variants"].as_array()?.iter()
+        .filter_map(|variant| variant["files"].as_array())
+        .flatten()
+        .find(|file| file["name"].as_str() == Some(file_name))?;
+    let digest = |algorithm: HashAlgorithm, length: usize| {
+        file[algorithm.name()].as_str()
+            .map(str::to_ascii_lowercase)
+            .filter(|digest| digest.len() == length && digest.chars().all(|c| c.is_ascii_hexdigit()))
+    };
+    let digests = Digests {
+        sha1: digest(HashAlgorithm::Sha1, 40)?,
+        sha256: digest(HashAlgorithm::Sha256, 64),
+        sha512: digest(HashAlgorithm::Sha512, 128),
+    };
+    if !digests.covers(algorithms) {
+        return None
+    }
+
+    Some(ModuleFile {
+        url: format!("{}/{}", directory, file["url"].as_str().unwrap_or(file_name)),
+        digests: digests.only(algorithms),
+        size: file["size"].as_u64()?.try_into().ok()?,
+    })
+}
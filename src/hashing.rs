@@ -0,0 +1,148 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Digests of artifacts. SHA-1 is what the metadata format requires, others can be added for
+//! consumers that want stronger verification.
+
+use sha1::{Digest, Sha1};
+use serde::{Deserialize, Serialize};
+use sha2::{Sha256, Sha512};
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes hashed at a time, so every hash sees the artifact while it's still in the CPU cache
+const BLOCK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    /// Name of the entry field and the extension of the Maven checksum file holding the digest.
+    pub fn name(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+        }
+    }
+}
+
+/// Computes digests of data arriving in pieces, such as a response body.
+pub struct Hasher {
+    sha1: Sha1,
+    sha256: Option<Sha256>,
+    sha512: Option<Sha512>,
+}
+
+impl Hasher {
+    /// Hashes with SHA-1 and the other given algorithms.
+    pub fn new(algorithms: &[HashAlgorithm]) -> Hasher {
+        Hasher {
+            sha1: Sha1::new(),
+            sha256: algorithms.contains(&HashAlgorithm::Sha256).then(Sha256::new),
+            sha512: algorithms.contains(&HashAlgorithm::Sha512).then(Sha512::new),
+        }
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for block in bytes.chunks(BLOCK_SIZE) {
+            self.sha1.update(block);
+            if let Some(sha256) = &mut self.sha256 {
+                sha256.update(block);
+            }
+            if let Some(sha512) = &mut self.sha512 {
+                sha512.update(block);
+            }
+        }
+    }
+
+    pub fn finish(self) -> Digests {
+        Digests {
+            sha1: hex::encode(self.sha1.finalize()),
+            sha256: self.sha256.map(|sha256| hex::encode(sha256.finalize())),
+            sha512: self.sha512.map(|sha512| hex::encode(sha512.finalize())),
+        }
+    }
+}
+
+/// Digests of an artifact: always SHA-1, and the other algorithms that were asked for. Written to
+/// documents as fields named after their algorithms.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Digests {
+    pub sha1: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha512: Option<String>,
+}
+
+impl Digests {
+    /// Computes the digests of `bytes` for the given algorithms in a single pass.
+    pub fn compute(bytes: &[u8], algorithms: &[HashAlgorithm]) -> Digests {
+        let mut hasher = Hasher::new(algorithms);
+        hasher.update(bytes);
+        hasher.finish()
+    }
+
+    /// Computes the digests of a file for the given algorithms, reading it a block at a time.
+    pub fn compute_file(path: &Path, algorithms: &[HashAlgorithm]) -> std::io::Result<(Digests, usize)> {
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Hasher::new(algorithms);
+        let mut block = vec![0; BLOCK_SIZE];
+        let mut size = 0;
+        loop {
+            match file.read(&mut block)? {
+                0 => break,
+                read => {
+                    hasher.update(&block[..read]);
+                    size += read;
+                }
+            }
+        }
+
+        Ok((hasher.finish(), size))
+    }
+
+    pub fn get(&self, algorithm: HashAlgorithm) -> Option<&str> {
+        match algorithm {
+            HashAlgorithm::Sha1 => Some(&self.sha1),
+            HashAlgorithm::Sha256 => self.sha256.as_deref(),
+            HashAlgorithm::Sha512 => self.sha512.as_deref(),
+        }
+    }
+
+    /// Whether every one of the algorithms has a digest here.
+    pub fn covers(&self, algorithms: &[HashAlgorithm]) -> bool {
+        algorithms.iter().all(|algorithm| self.get(*algorithm).is_some())
+    }
+
+    /// Drops the digests of algorithms that weren't asked for.
+    pub fn only(mut self, algorithms: &[HashAlgorithm]) -> Digests {
+        if !algorithms.contains(&HashAlgorithm::Sha256) {
+            self.sha256 = None;
+        }
+        if !algorithms.contains(&HashAlgorithm::Sha512) {
+            self.sha512 = None;
+        }
+        self
+    }
+}
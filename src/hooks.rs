@@ -0,0 +1,89 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Shell commands run once the output has been written, to publish or announce it.
+//!
+//! Hooks learn about the run from environment variables:
+//!
+//! - `EPL_OUTPUT`: path of the output
+//! - `EPL_OUTPUT_URL`: where the output is served, if known
+//! - `EPL_NEW_VERSIONS`: number of versions added to the output
+//! - `EPL_CHANGED_KEYS`: every version added, removed or changed, one per line, those of other libraries than
+//!   authlib prefixed with their `group:artifact`
+//!
+//! Per-version hooks also get `EPL_VERSION`, one of the changed keys, and `EPL_CHANGE`: `added`, `removed` or
+//! `changed`.
+
+use crate::document::Difference;
+use crate::error::GeneratorError;
+
+/// Runs every hook of `post` once, then every hook of `per_version` once for each changed version, in order.
+/// The first one that fails stops the rest.
+pub async fn run(post: &[String], per_version: &[String], output: &str, url: Option<&str>, differences: &[Difference])
+    -> Result<(), GeneratorError> {
+    let mut changes: Vec<(&str, &'static str)> = Vec::new();
+    for difference in differences {
+        let change = match difference {
+            Difference::Added(version) => (version.as_str(), "added"),
+            Difference::Removed(version) => (version.as_str(), "removed"),
+            Difference::Changed { version, .. } => (version.as_str(), "changed"),
+        };
+        // Every changed field is a difference of its own
+        if !changes.iter().any(|(version, _)| *version == change.0) {
+            changes.push(change);
+        }
+    }
+
+    let mut environment = vec![
+        ("EPL_OUTPUT", output.to_string()),
+        ("EPL_NEW_VERSIONS", changes.iter().filter(|(_, change)| *change == "added").count().to_string()),
+        ("EPL_CHANGED_KEYS", changes.iter().map(|(version, _)| *version).collect::<Vec<_>>().join("\n")),
+    ];
+    if let Some(url) = url {
+        environment.push(("EPL_OUTPUT_URL", url.to_string()));
+    }
+
+    for command in post {
+        execute(command, &environment).await?;
+    }
+    for (version, change) in &changes {
+        let mut environment = environment.clone();
+        environment.extend([("EPL_VERSION", version.to_string()), ("EPL_CHANGE", change.to_string())]);
+        for command in per_version {
+            execute(command, &environment).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Runs `command` with the shell, its output going where ours does.
+async fn execute(command: &str, environment: &[(&str, String)]) -> Result<(), GeneratorError> {
+    tracing::info!("Running hook: {}", command);
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let status = tokio::process::Command::new(shell)
+        .arg(flag)
+        .arg(command)
+        .envs(environment.iter().map(|(name, value)| (name, value)))
+        .status()
+        .await;
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(GeneratorError::Hook { command: command.to_string(), why: status.to_string() }),
+        Err(why) => Err(GeneratorError::Hook { command: command.to_string(), why: why.to_string() }),
+    }
+}
@@ -0,0 +1,108 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Resolving the latest authlib-injector build through its official API or GitHub releases, and telling builds apart.
+
+use crate::crash;
+use crate::download::RetryPolicy;
+use serde::Deserialize;
+
+/// The latest build, as announced by `artifact/latest.json` or a GitHub release.
+pub struct LatestInjector {
+    pub version: String,
+    pub download_url: String,
+    pub sha256: Option<String>,
+}
+
+/// `artifact/latest.json`, as far as it is read.
+#[derive(Deserialize)]
+struct LatestArtifact {
+    version: String,
+    download_url: String,
+    #[serde(default)]
+    checksums: Checksums,
+}
+
+#[derive(Default, Deserialize)]
+struct Checksums {
+    sha256: Option<String>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum InjectorApiError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("Malformed latest.json: {0}")]
+    Malformed(String),
+}
+
+/// Asks the authlib-injector API at `api` (e.g. `https://authlib-injector.yushi.moe`) for its latest build.
+pub async fn latest(client: &reqwest::Client, retry: &RetryPolicy, api: &str) -> Result<LatestInjector, InjectorApiError> {
+    let url = format!("{}/artifact/latest.json", api.trim_end_matches('/'));
+    let text = retry.run(|| async {
+        crash::attempting(&url);
+        client.get(&url).send().await?.error_for_status()?.text().await
+    }).await?;
+    let latest: LatestArtifact = serde_json::from_str(&text).map_err(|why| InjectorApiError::Malformed(why.to_string()))?;
+
+    Ok(LatestInjector {
+        version: latest.version,
+        download_url: latest.download_url,
+        sha256: latest.checksums.sha256.map(|sha256| sha256.to_ascii_lowercase()),
+    })
+}
+
+/// Asks the GitHub REST API at `api` for the latest release of `repository` (e.g. `yushijinhun/authlib-injector`), taking
+/// the build from its `.jar` asset and the SHA-256 from the digest GitHub records for it.
+pub async fn latest_release(client: &reqwest::Client, retry: &RetryPolicy, api: &str, repository: &str)
+    -> Result<LatestInjector, InjectorApiError> {
+    let url = format!("{}/repos/{}/releases/latest", api.trim_end_matches('/'), repository);
+    let text = retry.run(|| async {
+        crash::attempting(&url);
+        client.get(&url)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28")
+            .send().await?.error_for_status()?.text().await
+    }).await?;
+    let release: serde_json::Value = serde_json::from_str(&text).map_err(|why| InjectorApiError::Malformed(why.to_string()))?;
+
+    let assets = release["assets"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let jars = || assets.iter().filter(|asset| asset["name"].as_str().is_some_and(|name| name.ends_with(".jar")));
+    let asset = jars().find(|asset| asset["name"].as_str().is_some_and(|name| name.starts_with("authlib-injector")))
+        .or_else(|| jars().next())
+        .ok_or_else(|| InjectorApiError::Malformed("no .jar asset in the latest release".to_string()))?;
+    let download_url = asset["browser_download_url"].as_str()
+        .ok_or_else(|| InjectorApiError::Malformed("no browser_download_url".to_string()))?;
+    let version = release["tag_name"].as_str().map(|tag| tag.trim_start_matches('v').to_string())
+        .or_else(|| version_from_url(download_url))
+        .ok_or_else(|| InjectorApiError::Malformed("no tag_name".to_string()))?;
+
+    Ok(LatestInjector {
+        version,
+        download_url: download_url.to_string(),
+        // Only assets uploaded since mid-2025 have one
+        sha256: asset["digest"].as_str().and_then(|digest| digest.strip_prefix("sha256:")).map(str::to_ascii_lowercase),
+    })
+}
+
+/// The version in the file name of a build, as in `authlib-injector-1.2.5.jar`.
+pub fn version_from_url(url: &str) -> Option<String> {
+    let file_name = url.split(['?', '#']).next().unwrap_or(url).rsplit('/').next()?;
+    let version = file_name.strip_prefix("authlib-injector-")?.strip_suffix(".jar")?;
+    version.starts_with(|c: char| c.is_ascii_digit()).then(|| version.to_string())
+}
@@ -0,0 +1,81 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Checking that a downloaded artifact is a readable jar holding what it should, by its zip central directory.
+
+use std::path::Path;
+
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x06054b50;
+const CENTRAL_DIRECTORY_ENTRY: u32 = 0x02014b50;
+const LOCAL_FILE_HEADER: u32 = 0x04034b50;
+/// Fixed part of the end of central directory record, followed by a comment of up to 65535 bytes
+const END_RECORD_SIZE: usize = 22;
+
+/// Reads the jar at `path`, failing if its central directory is damaged, an entry it lists isn't where it says, or
+/// no entry's name starts with `expected_entry`.
+pub fn check(path: &Path, expected_entry: &str) -> Result<(), String> {
+    let jar = std::fs::read(path).map_err(|why| why.to_string())?;
+    let names = entry_names(&jar)?;
+    if !names.iter().any(|name| name.starts_with(expected_entry)) {
+        return Err(format!("none of its {} entries is under {}", names.len(), expected_entry))
+    }
+    Ok(())
+}
+
+/// Names of the entries in the central directory of a zip archive, after checking each points to a local header.
+fn entry_names(zip: &[u8]) -> Result<Vec<String>, String> {
+    let u16_at = |offset: usize| zip.get(offset..offset + 2).map(|b| u16::from_le_bytes([b[0], b[1]]) as usize);
+    let u32_at = |offset: usize| zip.get(offset..offset + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    let search_from = zip.len().saturating_sub(END_RECORD_SIZE + u16::MAX as usize);
+    let end = (search_from..=zip.len().saturating_sub(END_RECORD_SIZE)).rev()
+        .find(|&offset| u32_at(offset) == Some(END_OF_CENTRAL_DIRECTORY))
+        .ok_or("not a zip archive")?;
+    let truncated = || "the central directory is truncated".to_string();
+    let entries = u16_at(end + 10).ok_or_else(truncated)?;
+    let directory_size = u32_at(end + 12).ok_or_else(truncated)? as usize;
+    let directory = u32_at(end + 16).ok_or_else(truncated)? as usize;
+    if entries == u16::MAX as usize || directory == u32::MAX as usize {
+        return Err("ZIP64 archives aren't supported".to_string())
+    }
+    if directory + directory_size > end {
+        return Err(truncated())
+    }
+
+    let mut names = Vec::with_capacity(entries);
+    let mut offset = directory;
+    for _ in 0..entries {
+        if u32_at(offset) != Some(CENTRAL_DIRECTORY_ENTRY) {
+            return Err(format!("the central directory is damaged at byte {}", offset))
+        }
+        let compressed_size = u32_at(offset + 20).ok_or_else(truncated)? as usize;
+        let name_length = u16_at(offset + 28).ok_or_else(truncated)?;
+        let extra_length = u16_at(offset + 30).ok_or_else(truncated)?;
+        let comment_length = u16_at(offset + 32).ok_or_else(truncated)?;
+        let local_header = u32_at(offset + 42).ok_or_else(truncated)? as usize;
+        let name = zip.get(offset + 46..offset + 46 + name_length).ok_or_else(truncated)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        if u32_at(local_header) != Some(LOCAL_FILE_HEADER) || local_header + 30 + name_length + compressed_size > directory {
+            return Err(format!("{} isn't where the central directory says", name))
+        }
+        names.push(name);
+        offset += 46 + name_length + extra_length + comment_length;
+    }
+
+    Ok(names)
+}
@@ -0,0 +1,73 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Generates the authlib override metadata ElyPrismLauncher uses to swap Mojang's authlib for a patched one.
+//!
+//! [`MetadataGenerator`] does what the `EPLMetadataGenerator` binary does, for tools that would rather not shell out to it.
+
+pub mod auth;
+pub mod bundle;
+pub mod cache;
+mod changelog;
+pub mod completions;
+mod concurrency;
+pub mod crash;
+pub mod dashboard;
+pub mod doctor;
+pub mod document;
+mod download;
+mod elyby;
+mod injector;
+mod jar;
+pub mod manpage;
+mod merge;
+pub mod error;
+pub mod exit;
+pub mod generator;
+mod github;
+mod gradle;
+mod hooks;
+pub mod hashing;
+pub mod local;
+pub mod logging;
+pub mod maven;
+pub mod metrics;
+mod minecraft;
+pub mod mirror;
+pub mod model;
+pub mod notify;
+mod prism;
+mod pins;
+mod progress;
+pub mod reporting;
+pub mod schedule;
+pub mod schema;
+pub mod search;
+pub mod secrets;
+pub mod server;
+mod signature;
+mod state;
+mod store;
+pub mod systemd;
+pub mod telemetry;
+pub mod upload;
+pub mod version;
+
+pub use error::GeneratorError;
+pub use generator::{GeneratorConfig, InjectorChannel, InjectorPolicy, LibraryOverride, LibrarySource, MetadataGenerator, PlannedArtifact,
+    VersionSource};
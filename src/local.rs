@@ -0,0 +1,68 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Reading what would otherwise be downloaded from disk: `file://` URLs and local Maven repositories,
+//! and keeping everything else off the network when offline.
+
+use std::path::{Path, PathBuf};
+
+/// Fewest path segments a URL has to share with a file of a local repository, as in `artifact/version/file`
+const MIN_SHARED_SEGMENTS: usize = 3;
+
+/// A local Maven repository, like `~/.m2/repository`, standing in for the remote repositories it mirrors.
+pub struct LocalRepository {
+    root: PathBuf,
+}
+
+impl LocalRepository {
+    pub fn new(root: &Path) -> LocalRepository {
+        LocalRepository { root: root.to_path_buf() }
+    }
+
+    /// The local file for a remote URL: the one at the longest end of its path that exists here, so
+    /// `https://host/releases/by/ely/authlib/maven-metadata.xml` is found at `by/ely/authlib/maven-metadata.xml`
+    /// whatever the path of the remote repository is.
+    pub fn find(&self, url: &str) -> Option<PathBuf> {
+        let url = reqwest::Url::parse(url).ok()?;
+        let segments: Vec<&str> = url.path_segments()?.filter(|segment| !segment.is_empty()).collect();
+        if segments.contains(&"..") {
+            return None
+        }
+        (0..=segments.len().saturating_sub(MIN_SHARED_SEGMENTS))
+            .map(|start| segments[start..].iter().fold(self.root.clone(), |path, segment| path.join(segment)))
+            .find(|path| path.is_file())
+    }
+}
+
+/// The path a `file://` URL points to.
+pub fn file_url_path(url: &str) -> Option<PathBuf> {
+    if !url.starts_with("file:") {
+        return None
+    }
+    reqwest::Url::parse(url).ok()?.to_file_path().ok()
+}
+
+/// Refuses to resolve any host name, so a client using it can't reach the network.
+pub struct OfflineResolver;
+
+impl reqwest::dns::Resolve for OfflineResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let message = format!("not resolving {} while offline", name.as_str());
+        Box::pin(async move { Err(message.into()) })
+    }
+}
@@ -0,0 +1,116 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A man page in roff, generated from the command line definition so it never falls behind it.
+
+use clap::{Arg, Command};
+
+/// The man page of `command`, section 1, documenting its options and then every subcommand with its own. `command`
+/// has to be built, so that global options have been propagated to its subcommands.
+pub fn render(command: &Command) -> String {
+    let name = command.get_name();
+    let version = command.get_version().unwrap_or_default();
+    let mut page = format!(".TH {} 1 \"\" \"{} {}\"\n", escape(&name.to_uppercase()), escape(name), escape(version));
+
+    page.push_str(&format!(".SH NAME\n{} \\- {}\n", escape(name), escape(&text(command.get_about()))));
+    page.push_str(&format!(".SH SYNOPSIS\n\\fB{}\\fR [\\fIOPTIONS\\fR]", escape(name)));
+    for positional in positionals(command) {
+        page.push_str(&format!(" [\\fI{}\\fR]", escape(&value_name(positional))));
+    }
+    page.push_str(" [\\fICOMMAND\\fR]\n");
+    if let Some(description) = command.get_long_about() {
+        page.push_str(&format!(".SH DESCRIPTION\n{}\n", paragraphs(&description.to_string())));
+    }
+
+    page.push_str(".SH OPTIONS\n");
+    options(&mut page, command, |_| true);
+    page.push_str(".SH COMMANDS\n");
+    for subcommand in command.get_subcommands().filter(|subcommand| !subcommand.is_hide_set()) {
+        page.push_str(&format!(".SS {} {}\n", escape(name), escape(subcommand.get_name())));
+        page.push_str(&format!("{}\n", paragraphs(&text(subcommand.get_long_about().or(subcommand.get_about())))));
+        // Global options and those of `generate`, which are accepted without a subcommand too, were documented above
+        options(&mut page, subcommand, |arg| command.get_arguments().all(|top| top.get_id() != arg.get_id()));
+    }
+    if let Some(after) = command.get_after_help() {
+        page.push_str(&format!(".SH EXIT STATUS\n.nf\n{}\n.fi\n", escape(after.to_string().trim_start_matches("Exit status:\n"))));
+    }
+    page
+}
+
+/// Documents the positionals and options of `command` that `include` keeps, as a tagged paragraph each.
+fn options(page: &mut String, command: &Command, include: impl Fn(&Arg) -> bool) {
+    for arg in positionals(command).filter(|arg| include(arg)) {
+        page.push_str(&format!(".TP\n\\fI{}\\fR\n{}\n", escape(&value_name(arg)), paragraphs(&help(arg))));
+    }
+    for arg in command.get_arguments().filter(|arg| !arg.is_hide_set() && !arg.is_positional() && include(arg)) {
+        let mut flags: Vec<String> = arg.get_short().map(|short| format!("\\fB\\-{}\\fR", short)).into_iter()
+            .chain(arg.get_long().map(|long| format!("\\fB\\-\\-{}\\fR", escape(long))))
+            .collect();
+        if arg.get_action().takes_values() {
+            let last = flags.pop().unwrap_or_default();
+            flags.push(format!("{} \\fI{}\\fR", last, escape(&value_name(arg))));
+        }
+        page.push_str(&format!(".TP\n{}\n{}\n", flags.join(", "), paragraphs(&help(arg))));
+    }
+}
+
+fn positionals(command: &Command) -> impl Iterator<Item = &Arg> {
+    command.get_positionals().filter(|arg| !arg.is_hide_set())
+}
+
+/// The help of an argument with its possible values and default, as `--help` lists them.
+fn help(arg: &Arg) -> String {
+    let mut help = text(arg.get_long_help().or(arg.get_help()));
+    let values: Vec<String> = arg.get_possible_values().iter()
+        .filter(|value| !value.is_hide_set())
+        .map(|value| value.get_name().to_string())
+        .collect();
+    if !values.is_empty() {
+        help.push_str(&format!(" [possible values: {}]", values.join(", ")));
+    }
+    let defaults: Vec<String> = arg.get_default_values().iter().map(|value| value.to_string_lossy().into_owned()).collect();
+    if !defaults.is_empty() && arg.get_action().takes_values() {
+        help.push_str(&format!(" [default: {}]", defaults.join(", ")));
+    }
+    help
+}
+
+fn value_name(arg: &Arg) -> String {
+    arg.get_value_names().map_or_else(|| arg.get_id().to_string().to_uppercase(), |names| {
+        names.iter().map(ToString::to_string).collect::<Vec<_>>().join(" ")
+    })
+}
+
+fn text(styled: Option<&clap::builder::StyledStr>) -> String {
+    styled.map(ToString::to_string).unwrap_or_default()
+}
+
+/// Escapes text and separates its blank line separated paragraphs with `.PP`.
+fn paragraphs(text: &str) -> String {
+    text.split("\n\n").map(|paragraph| escape(paragraph.trim())).collect::<Vec<_>>().join("\n.PP\n")
+}
+
+/// Escapes text for roff: backslashes and dashes, and lines that would otherwise start a request.
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\e")
+        .replace('-', "\\-")
+        .lines()
+        .map(|line| if line.starts_with('.') || line.starts_with('\'') { format!("\\&{}", line) } else { line.to_string() })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,424 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Reading `maven-metadata.xml` documents and finding them and their artifacts in a repository.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use roxmltree::{Document, Node};
+use std::fmt::{Display, Formatter};
+
+/// The parts of a Maven metadata document the generator cares about.
+pub struct MavenMetadata<'a> {
+    pub versions: Vec<&'a str>,
+    pub last_updated: Option<&'a str>,
+    /// What was wrong with the parts of the document that were skipped
+    pub warnings: Vec<String>,
+}
+
+/// Maven `group:artifact` coordinates, located in a repository with the standard layout.
+pub struct Coordinates<'a> {
+    pub group: &'a str,
+    pub artifact: &'a str,
+}
+
+impl<'a> Coordinates<'a> {
+    /// Splits `group:artifact`, `None` if either is missing.
+    pub fn parse(coordinates: &'a str) -> Option<Coordinates<'a>> {
+        let (group, artifact) = coordinates.split_once(':')?;
+        if group.is_empty() || artifact.is_empty() || artifact.contains(':') {
+            return None
+        }
+        Some(Coordinates { group, artifact })
+    }
+
+    /// URL of the artifact directory, where `maven-metadata.xml` and the version directories are.
+    fn directory(&self, repository: &str) -> String {
+        format!("{}/{}", repository.trim_end_matches('/'), self.path())
+    }
+
+    fn path(&self) -> String {
+        format!("{}/{}", self.group.replace('.', "/"), self.artifact)
+    }
+
+    /// Path of a file of a version relative to the repository root.
+    pub fn file_path(&self, version: &str, packaging: &Packaging) -> String {
+        format!("{}/{}/{}-{}{}", self.path(), version, self.artifact, version, packaging.file_suffix())
+    }
+
+    pub fn metadata_url(&self, repository: &str) -> String {
+        format!("{}/maven-metadata.xml", self.directory(repository))
+    }
+
+    /// Download URL format string of a file of every version, with `{}` standing for the version.
+    pub fn url_template(&self, repository: &str, packaging: &Packaging) -> String {
+        let escape = |s: &str| s.replace('{', "{{").replace('}', "}}");
+        format!("{}/{{}}/{}-{{}}{}", escape(&self.directory(repository)), escape(self.artifact), escape(&packaging.file_suffix()))
+    }
+}
+
+/// Which of the files published for a version an artifact is, the plain jar by default.
+#[derive(Clone, PartialEq)]
+pub struct Packaging {
+    pub classifier: Option<String>,
+    pub extension: String,
+}
+
+impl Default for Packaging {
+    fn default() -> Self {
+        Packaging { classifier: None, extension: "jar".to_string() }
+    }
+}
+
+impl Packaging {
+    /// What follows the version in the file name, e.g. `-sources.jar`.
+    pub fn file_suffix(&self) -> String {
+        match &self.classifier {
+            Some(classifier) => format!("-{}.{}", classifier, self.extension),
+            None => format!(".{}", self.extension),
+        }
+    }
+
+    /// What follows the version in a library name, e.g. `:all@zip`. Plain jars have nothing.
+    pub fn name_suffix(&self) -> String {
+        let classifier = self.classifier.as_ref().map(|classifier| format!(":{}", classifier)).unwrap_or_default();
+        let extension = if self.extension == "jar" { String::new() } else { format!("@{}", self.extension) };
+        classifier + &extension
+    }
+}
+
+/// Splits a library name, `group:artifact:version[:classifier][@extension]`.
+pub fn split_name(name: &str) -> Option<(Coordinates<'_>, &str, Packaging)> {
+    let (name, extension) = name.split_once('@').unwrap_or((name, "jar"));
+    let mut parts = name.splitn(4, ':');
+    let (group, artifact, version) = (parts.next()?, parts.next()?, parts.next()?);
+    if group.is_empty() || artifact.is_empty() || version.is_empty() {
+        return None
+    }
+
+    Some((Coordinates { group, artifact }, version, Packaging { classifier: parts.next().map(str::to_string), extension: extension.to_string() }))
+}
+
+/// Describes which part of the metadata document is missing or malformed.
+#[derive(Debug)]
+pub struct MetadataError {
+    message: String,
+    line: u32,
+    snippet: String,
+}
+
+impl Display for MetadataError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Malformed Maven metadata: {} (line {})\n{}", self.message, self.line, self.snippet)
+    }
+}
+
+impl std::error::Error for MetadataError {}
+
+/// Longest piece of XML quoted in a [`MetadataError`]
+const SNIPPET_LENGTH: usize = 300;
+
+impl MetadataError {
+    fn at(node: Node, message: String) -> MetadataError {
+        MetadataError::quoting(node.document().input_text(), node.range(), message)
+    }
+
+    /// Quotes `range` of `xml`, or as much of it as fits in a snippet.
+    fn quoting(xml: &str, range: std::ops::Range<usize>, message: String) -> MetadataError {
+        let mut snippet_end = range.end.min(range.start + SNIPPET_LENGTH);
+        while !xml.is_char_boundary(snippet_end) {
+            snippet_end -= 1;
+        }
+
+        let mut snippet = xml[range.start..snippet_end].to_string();
+        if snippet_end < range.end {
+            snippet.push_str("...");
+        }
+
+        MetadataError {
+            message,
+            line: xml[..range.start].matches('\n').count() as u32 + 1,
+            snippet,
+        }
+    }
+}
+
+/// Parses a downloaded metadata document, telling an HTML page (a login form or a repository manager's error page
+/// served with 200) and other responses that aren't XML apart from broken XML.
+pub fn parse(xml: &str) -> Result<Document<'_>, MetadataError> {
+    let start = xml.len() - xml.trim_start_matches(['\u{feff}', ' ', '\t', '\r', '\n']).len();
+    let content = &xml[start..];
+    let head = content.get(..content.len().min(100)).unwrap_or(content).to_ascii_lowercase();
+    let whole = start..xml.len();
+    if content.is_empty() {
+        return Err(MetadataError::quoting(xml, whole, "the response is empty".to_string()))
+    }
+    if head.starts_with("<!doctype html") || head.starts_with("<html") {
+        return Err(MetadataError::quoting(xml, whole, "got an HTML page instead of XML".to_string()))
+    }
+    if !content.starts_with('<') {
+        return Err(MetadataError::quoting(xml, whole, "the response isn't XML".to_string()))
+    }
+
+    Document::parse(xml).map_err(|why| {
+        let position = why.pos();
+        // From the start of the line the parser stopped on
+        let line_start = xml.split_inclusive('\n').take(position.row as usize - 1).map(str::len).sum::<usize>().min(xml.len());
+        MetadataError::quoting(xml, line_start..xml.len(), why.to_string())
+    })
+}
+
+/// Walks `metadata/versioning/versions/version`. Malformed `<version>` elements are skipped with a warning, and
+/// without any usable one the versions are taken from `<release>` or `<latest>`. Fails with the element that is
+/// missing or malformed only when no version is left.
+pub fn read_metadata<'a>(doc: &'a Document) -> Result<MavenMetadata<'a>, MetadataError> {
+    let root = doc.root_element();
+    if !has_local_name(&root, "metadata") {
+        return Err(MetadataError::at(root, format!("expected root element <metadata>, found <{}>", root.tag_name().name())))
+    }
+
+    let versioning = child_element(root, "versioning", "metadata")?;
+    let mut warnings = Vec::new();
+    let mut versions = Vec::new();
+    let versions_element = child_element(versioning, "versions", "metadata/versioning");
+    if let Ok(versions_element) = versions_element {
+        for version in versions_element.children().filter(|n| has_local_name(n, "version")) {
+            let line = doc.text_pos_at(version.range().start).row;
+            match version.text().map(str::trim) {
+                Some(text) if is_valid_version(text) => versions.push(text),
+                Some(text) if !text.is_empty() => {
+                    warnings.push(format!("Skipped malformed <version> `{}` on line {} of the Maven metadata", text, line));
+                }
+                _ => warnings.push(format!("Skipped empty <version> on line {} of the Maven metadata", line)),
+            }
+        }
+    }
+    if versions.is_empty() {
+        let newest = ["release", "latest"].into_iter()
+            .find_map(|name| Some((name, child_text(versioning, name).filter(|version| is_valid_version(version))?)));
+        match (newest, versions_element) {
+            (Some((name, newest)), _) => {
+                warnings.push(format!("No usable <version> in the Maven metadata, only listing {} from <{}>", newest, name));
+                versions.push(newest);
+            }
+            (None, Err(why)) => return Err(why),
+            (None, Ok(versions_element)) => {
+                return Err(MetadataError::at(versions_element, "no usable <version> elements in metadata/versioning/versions".to_string()))
+            }
+        }
+    }
+
+    let last_updated = versioning.children().find(|n| has_local_name(n, "lastUpdated"))
+        .and_then(|n| n.text())
+        .map(str::trim);
+
+    Ok(MavenMetadata { versions, last_updated, warnings })
+}
+
+/// Whether a version can be put in a path: not empty, and without whitespace or characters Maven rejects in one.
+fn is_valid_version(version: &str) -> bool {
+    !version.is_empty() && !version.contains(|c: char| c.is_whitespace() || c.is_control() || "\\/:\"<>|?*".contains(c))
+}
+
+/// Whether a version is a snapshot, published as timestamped builds under `VERSION-SNAPSHOT`.
+pub fn is_snapshot(version: &str) -> bool {
+    version.ends_with("-SNAPSHOT")
+}
+
+/// Reads the version of the newest build of a snapshot from its own `maven-metadata.xml`, e.g.
+/// `1.0-20250102.030405-6` for `1.0-SNAPSHOT`. The file listed in `snapshotVersions` wins over the
+/// `snapshot` timestamp and build number. Snapshots deployed without unique versions keep their `-SNAPSHOT` name.
+pub fn read_snapshot_version(doc: &Document, version: &str, packaging: &Packaging) -> Result<String, MetadataError> {
+    let root = doc.root_element();
+    let versioning = child_element(root, "versioning", "metadata")?;
+
+    let jar = versioning.children()
+        .filter(|n| has_local_name(n, "snapshotVersions"))
+        .flat_map(|n| n.children())
+        .filter(|n| has_local_name(n, "snapshotVersion"))
+        .find(|n| child_text(*n, "extension") == Some(packaging.extension.as_str())
+            && child_text(*n, "classifier") == packaging.classifier.as_deref())
+        .and_then(|n| child_text(n, "value"));
+    if let Some(jar) = jar {
+        return Ok(jar.to_string())
+    }
+
+    let snapshot = child_element(versioning, "snapshot", "metadata/versioning")?;
+    match (child_text(snapshot, "timestamp"), child_text(snapshot, "buildNumber")) {
+        (Some(timestamp), Some(build_number)) => {
+            Ok(format!("{}-{}-{}", version.trim_end_matches("-SNAPSHOT"), timestamp, build_number))
+        }
+        _ if child_text(snapshot, "localCopy") == Some("true") => Ok(version.to_string()),
+        _ => Err(MetadataError::at(snapshot, "missing <timestamp> or <buildNumber> in metadata/versioning/snapshot".to_string())),
+    }
+}
+
+/// Parses a `<lastUpdated>` value, which Maven writes as `yyyyMMddHHmmss` in UTC.
+pub fn parse_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(timestamp, "%Y%m%d%H%M%S").ok().map(|t| t.and_utc())
+}
+
+fn child_element<'a, 'input>(parent: Node<'a, 'input>, name: &str, path: &str) -> Result<Node<'a, 'input>, MetadataError> {
+    parent.children().find(|n| has_local_name(n, name))
+        .ok_or_else(|| MetadataError::at(parent, format!("missing <{}> element in {}", name, path)))
+}
+
+/// Trimmed text of a child element, `None` if it's missing or empty.
+fn child_text<'a>(parent: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    parent.children().find(|n| has_local_name(n, name))
+        .and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+}
+
+/// Matches elements by local name only, so that namespaced metadata (whatever the prefix) is understood.
+fn has_local_name(node: &Node, name: &str) -> bool {
+    node.is_element() && node.tag_name().name() == name
+}
+
+/// Some repository managers emit prefixed elements without declaring the prefix, which is
+/// rejected by the XML parser. Declares a placeholder namespace for each such prefix on the root element.
+pub fn declare_unknown_prefixes(mut xml: String) -> String {
+    loop {
+        let prefix = match Document::parse(&xml) {
+            Err(roxmltree::Error::UnknownNamespace(prefix, _)) => prefix,
+            _ => return xml,
+        };
+
+        let Some(root_name_end) = root_element_name_end(&xml) else {
+            return xml
+        };
+        let declaration = format!(" xmlns:{}=\"urn:eplmetadatagenerator:undeclared:{}\"", prefix, prefix);
+        if xml[root_name_end..].starts_with(&declaration) {
+            // Somehow still unknown, give up and let the caller report the parse error
+            return xml
+        }
+        xml.insert_str(root_name_end, &declaration);
+    }
+}
+
+/// Returns the byte offset just past the root element's name in its start tag.
+fn root_element_name_end(xml: &str) -> Option<usize> {
+    let mut offset = 0;
+    while let Some(start) = xml[offset..].find('<') {
+        let tag_start = offset + start + 1;
+        // Skip the XML declaration, processing instructions, comments and the doctype
+        let markup_end = if xml[tag_start..].starts_with('?') {
+            Some("?>")
+        } else if xml[tag_start..].starts_with("!--") {
+            Some("-->")
+        } else if xml[tag_start..].starts_with('!') {
+            Some(">")
+        } else {
+            None
+        };
+        if let Some(markup_end) = markup_end {
+            offset = tag_start + xml[tag_start..].find(markup_end)? + markup_end.len();
+            continue
+        }
+
+        let name_length = xml[tag_start..].find(|c: char| c.is_whitespace() || c == '>' || c == '/')?;
+        return Some(tag_start + name_length)
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/maven-metadata").join(name);
+        std::fs::read_to_string(&path).unwrap_or_else(|why| panic!("couldn't read {}: {}", path.display(), why))
+    }
+
+    fn read_error(xml: &str) -> MetadataError {
+        let doc = parse(xml).unwrap();
+        read_metadata(&doc).err().expect("the metadata should be rejected")
+    }
+
+    #[test]
+    fn tells_pages_that_arent_xml_apart() {
+        let html = fixture("html-error-page.xml");
+        let why = parse(&html).unwrap_err();
+        assert_eq!(why.message, "got an HTML page instead of XML");
+        assert!(why.snippet.starts_with("<!DOCTYPE html>"));
+
+        assert_eq!(parse(&fixture("not-xml.xml")).unwrap_err().message, "the response isn't XML");
+        assert_eq!(parse(" \r\n").unwrap_err().message, "the response is empty");
+    }
+
+    #[test]
+    fn quotes_broken_xml_from_where_parsing_stopped() {
+        let why = parse(&fixture("mismatched-tag.xml")).unwrap_err();
+        assert_eq!(why.line, 7);
+        assert!(why.snippet.starts_with("      <version>1.5.21-ely.1</versoin>"), "{}", why.snippet);
+
+        let why = parse(&fixture("truncated.xml")).unwrap_err();
+        assert!(why.message.contains("never closed"), "{}", why.message);
+    }
+
+    #[test]
+    fn names_the_missing_element() {
+        let why = read_error(&fixture("wrong-root.xml"));
+        assert_eq!(why.message, "expected root element <metadata>, found <project>");
+        assert_eq!(why.line, 2);
+
+        let why = read_error(&fixture("missing-versioning.xml"));
+        assert_eq!(why.message, "missing <versioning> element in metadata");
+        assert!(why.snippet.starts_with("<metadata>"));
+    }
+
+    #[test]
+    fn fails_without_any_usable_version() {
+        let why = read_error(&fixture("empty-versions.xml"));
+        assert_eq!(why.message, "no usable <version> elements in metadata/versioning/versions");
+        assert_eq!(why.line, 6);
+    }
+
+    #[test]
+    fn skips_malformed_versions_with_warnings() {
+        let xml = fixture("malformed-versions.xml");
+        let doc = parse(&xml).unwrap();
+        let metadata = read_metadata(&doc).unwrap();
+        assert_eq!(metadata.versions, ["1.5.21-ely.1", "5.17.31-ely.2"]);
+        assert_eq!(metadata.last_updated, Some("20250101120000"));
+        assert_eq!(metadata.warnings, [
+            "Skipped empty <version> on line 8 of the Maven metadata",
+            "Skipped malformed <version> `3.11.49 ely.1` on line 9 of the Maven metadata",
+        ]);
+    }
+
+    #[test]
+    fn falls_back_to_the_release_version() {
+        let xml = fixture("release-only.xml");
+        let doc = parse(&xml).unwrap();
+        let metadata = read_metadata(&doc).unwrap();
+        assert_eq!(metadata.versions, ["5.17.31-ely.1"]);
+        assert_eq!(metadata.warnings, ["No usable <version> in the Maven metadata, only listing 5.17.31-ely.1 from <release>"]);
+    }
+
+    #[test]
+    fn declares_undeclared_prefixes() {
+        let xml = fixture("undeclared-prefix.xml");
+        assert!(parse(&xml).is_err());
+        let xml = declare_unknown_prefixes(xml);
+        let doc = parse(&xml).unwrap();
+        assert_eq!(read_metadata(&doc).unwrap().versions, ["1.5.21-ely.1", "5.17.31-ely.2"]);
+    }
+}
@@ -0,0 +1,159 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Overlaying a generated document on a hand-maintained one, for `--merge`.
+//!
+//! Works on the JSON itself rather than [`MetadataFile`](crate::model::MetadataFile), so keys the generator doesn't
+//! know about survive, and in the order they were written.
+
+use crate::version::newest_first;
+use serde_json::{Map, Value};
+
+/// Fields the generator writes into override entries. Any other field of an existing entry is kept
+const ENTRY_FIELDS: [&str; 10] = [
+    "name", "url", "sha1", "sha256", "sha512", "hashes", "size", "releaseTime", "vanilla", "minecraftVersions",
+];
+
+/// Overlays `generated` on the `existing` document. Generated entries replace existing ones for the same version,
+/// keeping their unknown fields, unless the existing one is marked `"manual": true`. Entries, libraries, extras and
+/// top-level keys that weren't generated are kept, except for the warnings of earlier runs.
+pub fn merge(existing: &str, generated: &str) -> Result<Value, String> {
+    let existing: Value = serde_json::from_str(existing).map_err(|why| why.to_string())?;
+    let generated: Map<String, Value> = serde_json::from_str(generated).expect("generated documents are always valid JSON");
+    if !existing.is_object() {
+        return Err("not a JSON object".to_string())
+    }
+    let format = |document: &Value| document["formatVersion"].as_u64().unwrap_or(1);
+    let generated_format = generated.get("formatVersion").and_then(Value::as_u64).unwrap_or(1);
+    if format(&existing) != generated_format {
+        return Err(format!("it is in format {}, but the output is in format {}", format(&existing), generated_format))
+    }
+
+    let mut merged = Map::new();
+    for (key, value) in &generated {
+        let value = match key.as_str() {
+            "overrides" => {
+                let overrides = object_entries(value)
+                    .map(|(library, entries)| (library.clone(), Value::Object(merge_entries(&existing["overrides"][library], entries))))
+                    .collect();
+                overlay(&existing["overrides"], overrides)
+            }
+            "extras" => overlay(&existing["extras"], value.as_object().cloned().unwrap_or_default()),
+            _ => value.clone(),
+        };
+        merged.insert(key.clone(), value);
+    }
+    let Value::Object(mut merged) = overlay(&existing, merged) else {
+        unreachable!("overlaying always gives an object")
+    };
+    // Warnings are about the run that gave them
+    if !generated.contains_key("warnings") {
+        merged.shift_remove("warnings");
+    }
+    Ok(Value::Object(merged))
+}
+
+/// Entries of a library: the generated ones, each replaced by an existing manual entry for the same version, and
+/// the existing ones for versions that weren't generated, put in order by version.
+fn merge_entries(existing: &Value, generated: &Value) -> Map<String, Value> {
+    let mut entries: Vec<(String, Value)> = object_entries(generated)
+        .map(|(version, entry)| {
+            let previous = &existing[version];
+            if previous["manual"].as_bool() == Some(true) {
+                return (version.clone(), previous.clone())
+            }
+            let mut entry = entry.clone();
+            if let Some(entry) = entry.as_object_mut() {
+                for (field, value) in object_entries(previous).filter(|(field, _)| !ENTRY_FIELDS.contains(&field.as_str())) {
+                    entry.insert(field.clone(), value.clone());
+                }
+            }
+            (version.clone(), entry)
+        })
+        .collect();
+    entries.extend(object_entries(existing)
+        .filter(|(version, _)| generated.get(version).is_none())
+        .map(|(version, entry)| (version.clone(), entry.clone())));
+    // The `latest` alias stays last, where the generator puts it
+    entries.sort_by(|(a, _), (b, _)| (a == "latest").cmp(&(b == "latest")).then_with(|| newest_first(a, b)));
+
+    entries.into_iter().collect()
+}
+
+/// `overlaid`, followed by the keys of `existing` it doesn't have.
+fn overlay(existing: &Value, mut overlaid: Map<String, Value>) -> Value {
+    for (key, value) in object_entries(existing) {
+        if !overlaid.contains_key(key) {
+            overlaid.insert(key.clone(), value.clone());
+        }
+    }
+    Value::Object(overlaid)
+}
+
+/// The keys and values of an object, in the order they were written, or none for anything else.
+fn object_entries(value: &Value) -> impl Iterator<Item = (&String, &Value)> {
+    value.as_object().into_iter().flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn merged(existing: Value, generated: Value) -> Value {
+        merge(&existing.to_string(), &generated.to_string()).unwrap()
+    }
+
+    #[test]
+    fn generated_fields_override_existing_ones() {
+        let existing = json!({"overrides": {"authlib": {"1.0": {"url": "old", "sha1": "aa", "note": "kept"}}}});
+        let generated = json!({"overrides": {"authlib": {"1.0": {"url": "new", "sha1": "bb"}}}});
+        assert_eq!(merged(existing, generated)["overrides"]["authlib"]["1.0"], json!({"url": "new", "sha1": "bb", "note": "kept"}));
+    }
+
+    #[test]
+    fn keeps_manual_entries() {
+        let entry = json!({"url": "pinned", "sha1": "aa", "manual": true});
+        let existing = json!({"overrides": {"authlib": {"1.0": entry}}});
+        let generated = json!({"overrides": {"authlib": {"1.0": {"url": "new", "sha1": "bb"}}}});
+        assert_eq!(merged(existing, generated)["overrides"]["authlib"]["1.0"], entry);
+    }
+
+    #[test]
+    fn unites_entries_in_version_order_with_latest_last() {
+        let existing = json!({"overrides": {"authlib": {"2.0": {"url": "a"}, "1.0": {"url": "b"}}, "other": {"1.0": {"url": "c"}}}});
+        let generated = json!({"overrides": {"authlib": {"3.0": {"url": "d"}, "latest": {"url": "d"}, "1.5": {"url": "e"}}}});
+        let merged = merged(existing, generated);
+        let versions: Vec<&String> = merged["overrides"]["authlib"].as_object().unwrap().keys().collect();
+        assert_eq!(versions, ["3.0", "2.0", "1.5", "1.0", "latest"]);
+        assert_eq!(merged["overrides"]["other"], json!({"1.0": {"url": "c"}}));
+    }
+
+    #[test]
+    fn unites_extras_and_top_level_keys() {
+        let existing = json!({"formatVersion": 2, "maintainer": "me", "extras": {"a": 1, "b": 2}, "warnings": ["stale"]});
+        let generated = json!({"formatVersion": 2, "extras": {"b": 3, "c": 4}});
+        assert_eq!(merged(existing, generated), json!({"formatVersion": 2, "extras": {"b": 3, "c": 4, "a": 1}, "maintainer": "me"}));
+    }
+
+    #[test]
+    fn rejects_documents_in_another_format() {
+        assert!(merge(&json!({"formatVersion": 1}).to_string(), &json!({"formatVersion": 2}).to_string()).is_err());
+        assert!(merge("[]", "{}").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Counters of the runs of a long-lived process, exposed in the Prometheus text format.
+
+use crate::exit::ExitStatus;
+use indexmap::IndexMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Upper bounds of the run duration histogram buckets, in seconds
+const DURATION_BUCKETS: [f64; 10] = [1.0, 5.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 1800.0, 3600.0];
+
+/// What the runs of a process added up to so far.
+#[derive(Default)]
+pub struct Metrics {
+    counters: Mutex<Counters>,
+}
+
+/// Shared between the generator recording runs and the server exposing them.
+pub type SharedMetrics = Arc<Metrics>;
+
+#[derive(Default)]
+struct Counters {
+    /// Runs by the name of their exit status
+    runs: IndexMap<&'static str, u64>,
+    /// Runs by the first bucket they fit in, the last one counting those longer than every bucket
+    duration_buckets: [u64; DURATION_BUCKETS.len() + 1],
+    duration_sum: f64,
+    downloaded_bytes: u64,
+    versions_generated: u64,
+    versions_failed: u64,
+    last_success: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// What a single run did, for [`Metrics::record`].
+pub struct RunReport {
+    pub status: ExitStatus,
+    /// Whether the run ended without an error, whether or not it wrote output
+    pub succeeded: bool,
+    pub duration: Duration,
+    /// Artifact bytes transferred, as counted against `--max-total-bytes`
+    pub downloaded_bytes: u64,
+    pub versions_generated: usize,
+    pub versions_failed: usize,
+}
+
+impl Metrics {
+    pub fn record(&self, run: RunReport) {
+        let mut counters = self.counters.lock().unwrap();
+        *counters.runs.entry(run.status.name()).or_default() += 1;
+        let seconds = run.duration.as_secs_f64();
+        let bucket = DURATION_BUCKETS.iter().position(|bound| seconds <= *bound).unwrap_or(DURATION_BUCKETS.len());
+        counters.duration_buckets[bucket] += 1;
+        counters.duration_sum += seconds;
+        counters.downloaded_bytes += run.downloaded_bytes;
+        counters.versions_generated += run.versions_generated as u64;
+        counters.versions_failed += run.versions_failed as u64;
+        if run.succeeded {
+            counters.last_success = Some(chrono::Utc::now());
+        }
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let counters = self.counters.lock().unwrap();
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, String)]| {
+            let _ = writeln!(text, "# HELP {} {}\n# TYPE {} {}", name, help, name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(text, "{}{} {}", name, labels, value);
+            }
+        };
+
+        let runs: Vec<_> = counters.runs.iter()
+            .map(|(status, count)| (format!("{{status=\"{}\"}}", status), count.to_string()))
+            .collect();
+        metric("epl_metadata_runs_total", "counter", "Generation runs, by exit status.", &runs);
+
+        let mut cumulative = 0;
+        let mut buckets: Vec<_> = DURATION_BUCKETS.iter().zip(&counters.duration_buckets)
+            .map(|(bound, count)| {
+                cumulative += count;
+                (format!("_bucket{{le=\"{}\"}}", bound), cumulative.to_string())
+            })
+            .collect();
+        let count: u64 = counters.duration_buckets.iter().sum();
+        buckets.push(("_bucket{le=\"+Inf\"}".to_string(), count.to_string()));
+        buckets.push(("_sum".to_string(), counters.duration_sum.to_string()));
+        buckets.push(("_count".to_string(), count.to_string()));
+        metric("epl_metadata_run_duration_seconds", "histogram", "Duration of generation runs.", &buckets);
+
+        metric("epl_metadata_downloaded_bytes_total", "counter", "Artifact bytes downloaded.",
+            &[(String::new(), counters.downloaded_bytes.to_string())]);
+        metric("epl_metadata_versions_generated_total", "counter", "Versions downloaded and hashed for the output.",
+            &[(String::new(), counters.versions_generated.to_string())]);
+        metric("epl_metadata_versions_failed_total", "counter", "Versions left out of the output because they couldn't be downloaded.",
+            &[(String::new(), counters.versions_failed.to_string())]);
+        let last_success: Vec<_> = counters.last_success.iter()
+            .map(|time| (String::new(), time.timestamp().to_string()))
+            .collect();
+        metric("epl_metadata_last_success_timestamp_seconds", "gauge", "Unix time the last successful run ended.", &last_success);
+
+        text
+    }
+}
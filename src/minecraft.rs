@@ -0,0 +1,91 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Finding out which Minecraft versions ship which library versions, through Mojang's version manifest.
+
+use crate::download::{self, Connection, DownloadError};
+use futures::StreamExt;
+use std::collections::HashMap;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ManifestError {
+    #[error(transparent)]
+    Download(#[from] DownloadError),
+    #[error("Malformed version manifest: {0}")]
+    Malformed(String),
+}
+
+/// Minecraft versions by the `group:artifact:version` of the libraries they ship.
+pub struct ShippedLibraries {
+    versions: HashMap<String, Vec<String>>,
+    /// Minecraft versions whose document couldn't be downloaded or read
+    pub unavailable: usize,
+}
+
+impl ShippedLibraries {
+    /// Minecraft versions shipping a version of a `group:artifact` library, newest first.
+    pub fn minecraft_versions(&self, library: &str, version: &str) -> &[String] {
+        self.versions.get(&format!("{}:{}", library, version)).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Reads the libraries of every Minecraft version listed in a `version_manifest_v2.json`, downloading up to
+/// `max_concurrent` version documents at once.
+pub async fn shipped_libraries(connection: &Connection<'_>, manifest_url: &str, max_concurrent: usize)
+    -> Result<ShippedLibraries, ManifestError> {
+    let manifest = download::fetch_cached(connection, manifest_url).await?;
+    let manifest: serde_json::Value = serde_json::from_slice(&manifest).map_err(|why| ManifestError::Malformed(why.to_string()))?;
+    let listed = manifest["versions"].as_array().ok_or_else(|| ManifestError::Malformed("no versions".to_string()))?;
+    let listed: Vec<(&str, &str)> = listed.iter()
+        .filter_map(|version| Some((version["id"].as_str()?, version["url"].as_str()?)))
+        .collect();
+
+    let documents = listed.iter().map(|(id, url)| async move {
+        // Version documents are addressed by their SHA-1, so a cached one is never out of date
+        let cached = connection.cache.and_then(|cache| cache.get(url)).and_then(|cached| cached.body());
+        let body = match cached {
+            Some(body) => body,
+            None => download::fetch_cached(connection, url).await
+                .inspect_err(|why| tracing::debug!("Couldn't download Minecraft {}: {}", id, why)).ok()?,
+        };
+        let document: serde_json::Value = serde_json::from_slice(&body).ok()?;
+        let libraries: Vec<String> = document["libraries"].as_array()?.iter()
+            .filter_map(|library| library["name"].as_str())
+            // Natives are listed as `group:artifact:version:classifier`
+            .map(|name| name.splitn(4, ':').take(3).collect::<Vec<_>>().join(":"))
+            .collect();
+        Some((*id, libraries))
+    });
+    let documents: Vec<_> = futures::stream::iter(documents).buffered(max_concurrent.max(1)).collect().await;
+
+    let mut shipped = ShippedLibraries { versions: HashMap::new(), unavailable: 0 };
+    for document in documents {
+        let Some((id, libraries)) = document else {
+            shipped.unavailable += 1;
+            continue
+        };
+        for library in libraries {
+            let versions = shipped.versions.entry(library).or_default();
+            if versions.last().is_none_or(|last| last != id) {
+                versions.push(id.to_string());
+            }
+        }
+    }
+
+    Ok(shipped)
+}
@@ -0,0 +1,127 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Copies of the artifacts in a directory served elsewhere, so the output doesn't depend on upstream.
+
+use crate::maven;
+use crate::upload::Uploader;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+/// A directory laid out like a Maven repository, served at `base_url`, which may be relative, or uploaded to a
+/// private bucket and pointed to by presigned URLs.
+pub struct Mirror {
+    root: PathBuf,
+    base_url: String,
+    presigned: Option<(Uploader, Duration)>,
+}
+
+impl Mirror {
+    pub fn new(root: PathBuf, base_url: &str) -> Mirror {
+        Mirror { root, base_url: base_url.trim_end_matches('/').to_string(), presigned: None }
+    }
+
+    /// Points to the copies uploaded by `uploader` by URLs that stop working `expiry` after they are made.
+    pub fn presigned(self, uploader: Uploader, expiry: Duration) -> Mirror {
+        Mirror { presigned: Some((uploader, expiry)), ..self }
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.root.join(path).is_file()
+    }
+
+    pub fn put(&self, path: &str, bytes: &[u8]) -> std::io::Result<()> {
+        let path = self.root.join(path);
+        // Write under a temporary name first, so an interrupted write is never served
+        let temporary = path.with_extension("tmp");
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(&temporary, bytes)?;
+        std::fs::rename(&temporary, &path)
+    }
+
+    /// Puts a copy of a file at `path`.
+    pub fn copy(&self, path: &str, source: &Path) -> std::io::Result<()> {
+        let path = self.root.join(path);
+        let temporary = path.with_extension("tmp");
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::copy(source, &temporary)?;
+        std::fs::rename(&temporary, &path)
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        match &self.presigned {
+            Some((uploader, expiry)) => uploader.presign(path, *expiry).to_string(),
+            None if self.base_url.is_empty() => path.to_string(),
+            None => format!("{}/{}", self.base_url, path),
+        }
+    }
+
+    /// Whether two URLs made by [`Mirror::url`] point to the same copy, presigned ones differing by their signature.
+    pub fn same_copy(&self, a: &str, b: &str) -> bool {
+        let unsigned = |url: &str| if self.presigned.is_some() { url.split('?').next().unwrap_or(url).to_string() } else { url.to_string() };
+        unsigned(a) == unsigned(b)
+    }
+}
+
+/// URL of the directory `to` relative to the directory `from`, empty when they are the same one.
+pub fn relative_url(from: &Path, to: &Path) -> std::io::Result<String> {
+    let (from, to) = (std::path::absolute(from)?, std::path::absolute(to)?);
+    let (from, to) = (normalize(&from), normalize(&to));
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let up = from.len() - common;
+    let down = to[common..].iter().map(|component| component.to_string_lossy().into_owned());
+    Ok(std::iter::repeat_n("..".to_string(), up).chain(down).collect::<Vec<_>>().join("/"))
+}
+
+/// The components of an absolute path with `.` and `..` resolved, without following links.
+fn normalize(path: &Path) -> Vec<&std::ffi::OsStr> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name),
+            Component::ParentDir => {
+                components.pop();
+            }
+            Component::Prefix(prefix) => components.push(prefix.as_os_str()),
+            Component::RootDir | Component::CurDir => {}
+        }
+    }
+    components
+}
+
+/// Where an artifact goes in the mirror, following the Maven layout of its `group:artifact:version[:classifier][@extension]` name.
+pub fn artifact_path(name: &str) -> String {
+    maven::split_name(name)
+        .map(|(coordinates, version, packaging)| coordinates.file_path(version, &packaging))
+        .unwrap_or_else(|| format!("{}.jar", name.replace(':', "/")))
+}
+
+/// Where the authlib-injector goes in the mirror, under its file name.
+pub fn injector_path(url: &str) -> String {
+    format!("authlib-injector/{}", injector_file_name(url))
+}
+
+/// Where a build of an authlib-injector channel goes in the mirror, apart from those of other channels.
+pub fn injector_channel_path(channel: &str, url: &str) -> String {
+    format!("authlib-injector/{}/{}", channel, injector_file_name(url))
+}
+
+fn injector_file_name(url: &str) -> &str {
+    let file_name = url.split(['?', '#']).next().unwrap_or(url).rsplit('/').next().unwrap_or_default();
+    if file_name.is_empty() { "authlib-injector.jar" } else { file_name }
+}
@@ -0,0 +1,334 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The metadata document format, as written by the generator and read back by `verify`, `diff` and `--previous`.
+//!
+//! Documents state their layout in `formatVersion`, 1 when it's missing. Format 2 lists the digests of an entry
+//! under `hashes` and the extras given on the command line under `extras.values` and `extras.artifacts`, instead
+//! of next to the fixed fields. [`MetadataFile`] holds either, and is written in the layout asked for.
+
+use crate::hashing::Digests;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Key of the authlib overrides in [`MetadataFile::overrides`]
+pub const AUTHLIB: &str = "com.mojang:authlib";
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct MetadataFile {
+    /// Overrides by the `group:artifact` of the library they replace
+    #[serde(default)]
+    pub overrides: IndexMap<String, Overrides>,
+    #[serde(default)]
+    pub extras: Extras,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl MetadataFile {
+    /// The authlib overrides, empty if there are none.
+    pub fn authlib(&self) -> &Overrides {
+        static EMPTY: LazyLock<Overrides> = LazyLock::new(Overrides::default);
+        self.overrides.get(AUTHLIB).unwrap_or(&EMPTY)
+    }
+
+    /// Pretty-printed in the layout of `format`, the way the generator writes documents.
+    pub fn to_pretty_json(&self, format: FormatVersion) -> String {
+        match format {
+            FormatVersion::V1 => serde_json::to_string_pretty(&Versioned { format_version: FormatVersion::V1.number(), document: self }),
+            FormatVersion::V2 => serde_json::to_string_pretty(&DocumentV2::from(self)),
+        }
+        .expect("documents are always serializable")
+    }
+
+    /// Minified in the layout of `format`, for `--compact`.
+    pub fn to_compact_json(&self, format: FormatVersion) -> String {
+        match format {
+            FormatVersion::V1 => serde_json::to_string(&Versioned { format_version: FormatVersion::V1.number(), document: self }),
+            FormatVersion::V2 => serde_json::to_string(&DocumentV2::from(self)),
+        }
+        .expect("documents are always serializable")
+    }
+
+    /// Reads a document in any layout the generator writes.
+    pub fn parse(contents: &[u8]) -> serde_json::Result<MetadataFile> {
+        #[derive(Deserialize)]
+        struct Probe {
+            #[serde(rename = "formatVersion")]
+            format_version: Option<u64>,
+        }
+
+        match serde_json::from_slice::<Probe>(contents)?.format_version.unwrap_or(1) {
+            1 => serde_json::from_slice(contents),
+            2 => serde_json::from_slice::<DocumentV2>(contents).map(MetadataFile::from),
+            other => Err(serde::de::Error::custom(format!("unsupported formatVersion {}", other))),
+        }
+    }
+}
+
+/// Layout of written documents, see the module documentation.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum FormatVersion {
+    /// Digests and extras next to the fixed fields, as read by every launcher release
+    #[default]
+    #[value(name = "1")]
+    V1,
+    /// Digests under `hashes`, extras from the command line under `extras.values` and `extras.artifacts`
+    #[value(name = "2")]
+    V2,
+}
+
+impl FormatVersion {
+    /// The version as written to `formatVersion`.
+    pub fn number(self) -> u64 {
+        match self {
+            FormatVersion::V1 => 1,
+            FormatVersion::V2 => 2,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Versioned<'a> {
+    #[serde(rename = "formatVersion")]
+    format_version: u64,
+    #[serde(flatten)]
+    document: &'a MetadataFile,
+}
+
+/// Entries by the base version they apply to, in the order they were generated, newest first.
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Overrides(pub IndexMap<String, LibraryEntry>);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LibraryEntry {
+    /// Maven coordinates of the replacement
+    pub name: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub digests: Digests,
+    pub size: usize,
+    /// When the artifact was published, in ISO 8601, if its `Last-Modified` or the version listing says
+    #[serde(rename = "releaseTime", default, skip_serializing_if = "Option::is_none")]
+    pub release_time: Option<String>,
+    /// The entry is Mojang's own authlib, for base versions without a patched build
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub vanilla: bool,
+    /// Minecraft versions shipping the overridden library version, newest first, with `--minecraft-versions`
+    #[serde(rename = "minecraftVersions", default, skip_serializing_if = "Vec::is_empty")]
+    pub minecraft_versions: Vec<String>,
+}
+
+#[derive(Clone, Default, Debug, Serialize, Deserialize)]
+pub struct Extras {
+    #[serde(rename = "authlib-injector", default, skip_serializing_if = "Option::is_none")]
+    pub authlib_injector: Option<InjectorEntry>,
+    #[serde(rename = "ely.by", default, skip_serializing_if = "Option::is_none")]
+    pub ely_by: Option<ElyEndpoints>,
+    /// Further authlib-injector builds, like a snapshot channel or a fork, by channel name
+    #[serde(rename = "authlib-injector-channels", default, skip_serializing_if = "IndexMap::is_empty")]
+    pub injector_channels: IndexMap<String, InjectorEntry>,
+    /// Extras given on the command line, by key
+    #[serde(flatten)]
+    pub other: IndexMap<String, Extra>,
+}
+
+impl Extras {
+    /// Whether a key is one of the fixed extras rather than free for `--extra` and `--extra-artifact`.
+    pub fn is_reserved(key: &str) -> bool {
+        matches!(key, "authlib-injector" | "ely.by" | "authlib-injector-channels")
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InjectorEntry {
+    pub url: String,
+    /// Only known when resolved through the authlib-injector API
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(flatten)]
+    pub digests: Digests,
+    pub size: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ElyEndpoints {
+    pub authserver: String,
+    pub sessionserver: String,
+    pub skins: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Extra {
+    Value(String),
+    Artifact(ArtifactEntry),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ArtifactEntry {
+    pub url: String,
+    pub sha1: String,
+    pub size: usize,
+}
+
+/// A document in format 2. Only used to write and read that layout, everything else works on [`MetadataFile`].
+#[derive(Serialize, Deserialize)]
+struct DocumentV2 {
+    #[serde(rename = "formatVersion")]
+    format_version: u64,
+    #[serde(default)]
+    overrides: IndexMap<String, IndexMap<String, EntryV2>>,
+    #[serde(default)]
+    extras: ExtrasV2,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct EntryV2 {
+    name: String,
+    url: String,
+    hashes: Digests,
+    size: usize,
+    #[serde(rename = "releaseTime", default, skip_serializing_if = "Option::is_none")]
+    release_time: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    vanilla: bool,
+    #[serde(rename = "minecraftVersions", default, skip_serializing_if = "Vec::is_empty")]
+    minecraft_versions: Vec<String>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct ExtrasV2 {
+    #[serde(rename = "authlib-injector", default, skip_serializing_if = "Option::is_none")]
+    authlib_injector: Option<InjectorV2>,
+    #[serde(rename = "ely.by", default, skip_serializing_if = "Option::is_none")]
+    ely_by: Option<ElyEndpoints>,
+    #[serde(rename = "authlib-injector-channels", default, skip_serializing_if = "IndexMap::is_empty")]
+    injector_channels: IndexMap<String, InjectorV2>,
+    /// `--extra` values by key
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    values: IndexMap<String, String>,
+    /// `--extra-artifact` artifacts by key
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    artifacts: IndexMap<String, ArtifactV2>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct InjectorV2 {
+    url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    hashes: Digests,
+    size: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArtifactV2 {
+    url: String,
+    hashes: Digests,
+    size: usize,
+}
+
+impl From<&MetadataFile> for DocumentV2 {
+    fn from(document: &MetadataFile) -> DocumentV2 {
+        let injector = |injector: &InjectorEntry| InjectorV2 {
+            url: injector.url.clone(),
+            version: injector.version.clone(),
+            hashes: injector.digests.clone(),
+            size: injector.size,
+        };
+        let mut extras = ExtrasV2 {
+            authlib_injector: document.extras.authlib_injector.as_ref().map(injector),
+            ely_by: document.extras.ely_by.clone(),
+            injector_channels: document.extras.injector_channels.iter().map(|(name, entry)| (name.clone(), injector(entry))).collect(),
+            ..ExtrasV2::default()
+        };
+        for (key, extra) in &document.extras.other {
+            match extra {
+                Extra::Value(value) => {
+                    extras.values.insert(key.clone(), value.clone());
+                }
+                Extra::Artifact(artifact) => {
+                    let hashes = Digests { sha1: artifact.sha1.clone(), sha256: None, sha512: None };
+                    extras.artifacts.insert(key.clone(), ArtifactV2 { url: artifact.url.clone(), hashes, size: artifact.size });
+                }
+            }
+        }
+
+        DocumentV2 {
+            format_version: FormatVersion::V2.number(),
+            overrides: document.overrides.iter()
+                .map(|(library, overrides)| (library.clone(), overrides.0.iter()
+                    .map(|(version, entry)| (version.clone(), EntryV2 {
+                        name: entry.name.clone(),
+                        url: entry.url.clone(),
+                        hashes: entry.digests.clone(),
+                        size: entry.size,
+                        release_time: entry.release_time.clone(),
+                        vanilla: entry.vanilla,
+                        minecraft_versions: entry.minecraft_versions.clone(),
+                    }))
+                    .collect()))
+                .collect(),
+            extras,
+            warnings: document.warnings.clone(),
+        }
+    }
+}
+
+impl From<DocumentV2> for MetadataFile {
+    fn from(document: DocumentV2) -> MetadataFile {
+        let injector = |injector: InjectorV2| InjectorEntry {
+            url: injector.url,
+            version: injector.version,
+            digests: injector.hashes,
+            size: injector.size,
+        };
+        let values = document.extras.values.into_iter().map(|(key, value)| (key, Extra::Value(value)));
+        let artifacts = document.extras.artifacts.into_iter().map(|(key, artifact)| {
+            (key, Extra::Artifact(ArtifactEntry { url: artifact.url, sha1: artifact.hashes.sha1, size: artifact.size }))
+        });
+
+        MetadataFile {
+            overrides: document.overrides.into_iter()
+                .map(|(library, overrides)| (library, Overrides(overrides.into_iter()
+                    .map(|(version, entry)| (version, LibraryEntry {
+                        name: entry.name,
+                        url: entry.url,
+                        digests: entry.hashes,
+                        size: entry.size,
+                        release_time: entry.release_time,
+                        vanilla: entry.vanilla,
+                        minecraft_versions: entry.minecraft_versions,
+                    }))
+                    .collect())))
+                .collect(),
+            extras: Extras {
+                authlib_injector: document.extras.authlib_injector.map(injector),
+                ely_by: document.extras.ely_by,
+                injector_channels: document.extras.injector_channels.into_iter().map(|(name, entry)| (name, injector(entry))).collect(),
+                other: values.chain(artifacts).collect(),
+            },
+            warnings: document.warnings,
+        }
+    }
+}
@@ -0,0 +1,164 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Telling a webhook what changed in the generated document.
+
+use crate::document::Difference;
+use crate::model::MetadataFile;
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// Longest message Discord accepts in `content`
+const DISCORD_MAX_CONTENT: usize = 2000;
+
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+pub enum WebhookFormat {
+    /// An object with `event`, `output`, `url`, `added`, `removed` and `changed`
+    #[default]
+    Json,
+    /// A message for a Discord webhook, listing the differences as in `diff`
+    Discord,
+}
+
+/// POSTs the differences between the previous and the new document to a webhook. A failure is only logged, the
+/// document has been written by then.
+pub async fn send_changes(client: &reqwest::Client, webhook: &str, format: WebhookFormat, output: &str, url: Option<&str>,
+    differences: &[Difference]) {
+    let body = match format {
+        WebhookFormat::Json => json_payload(output, url, differences),
+        WebhookFormat::Discord => discord_payload(output, url, differences),
+    };
+
+    let response = client.post(webhook)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send().await
+        .and_then(|r| r.error_for_status());
+    if let Err(why) = response {
+        tracing::warn!("Couldn't send change notification: {}", why);
+    }
+}
+
+fn json_payload(output: &str, url: Option<&str>, differences: &[Difference]) -> serde_json::Value {
+    let (mut added, mut removed, mut changed) = (Vec::new(), Vec::new(), Vec::new());
+    for difference in differences {
+        match difference {
+            Difference::Added(version) => added.push(serde_json::json!(version)),
+            Difference::Removed(version) => removed.push(serde_json::json!(version)),
+            Difference::Changed { .. } => changed.push(difference.to_json()),
+        }
+    }
+
+    serde_json::json!({
+        "event": "metadata_changed",
+        "output": output,
+        "url": url,
+        "added": added,
+        "removed": removed,
+        "changed": changed,
+    })
+}
+
+fn discord_payload(output: &str, url: Option<&str>, differences: &[Difference]) -> serde_json::Value {
+    let added = differences.iter().filter(|difference| matches!(difference, Difference::Added(_))).count();
+    let mut content = format!("**{}** changed: {} new version(s), {} other change(s)", output, added, differences.len() - added);
+    if let Some(url) = url {
+        content.push_str(&format!("\n<{}>", url));
+    }
+    content.push_str("\n```diff");
+    // Leaves room for the closing fence and the note about what didn't fit
+    let budget = DISCORD_MAX_CONTENT - 40;
+    let mut listed = 0;
+    for difference in differences {
+        let line = format!("\n{}", difference);
+        if content.chars().count() + line.chars().count() > budget {
+            break
+        }
+        content.push_str(&line);
+        listed += 1;
+    }
+    if listed < differences.len() {
+        content.push_str(&format!("\n... and {} more", differences.len() - listed));
+    }
+    content.push_str("\n```");
+
+    serde_json::json!({ "content": content })
+}
+
+/// Notices when no new version has appeared in the documents of --watch or serve cycles for a while, which is how a
+/// repository that has silently gone away or stopped being published to shows.
+pub struct QuietAlert {
+    threshold: Duration,
+    versions: Option<HashSet<String>>,
+    since: DateTime<Utc>,
+    alerted: bool,
+}
+
+impl QuietAlert {
+    pub fn new(threshold: Duration) -> QuietAlert {
+        QuietAlert { threshold, versions: None, since: Utc::now(), alerted: false }
+    }
+
+    /// Takes in the document of a cycle. Returns when the last new version appeared, once, when that was longer ago
+    /// than the threshold. Until a cycle has added one, that is when the newest version in the first document was
+    /// released, or when watching started if none says.
+    pub fn observe(&mut self, document: &MetadataFile, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let entries = || document.overrides.values().flat_map(|overrides| overrides.0.values());
+        let versions: HashSet<String> = entries().map(|entry| entry.name.clone()).collect();
+        match &self.versions {
+            None => {
+                let newest = entries()
+                    .filter_map(|entry| DateTime::parse_from_rfc3339(entry.release_time.as_deref()?).ok())
+                    .map(|time| time.with_timezone(&Utc))
+                    .max();
+                if let Some(newest) = newest {
+                    self.since = newest;
+                }
+            }
+            Some(known) if !versions.is_subset(known) => {
+                self.since = now;
+                self.alerted = false;
+            }
+            Some(_) => {}
+        }
+        self.versions = Some(versions);
+
+        let quiet = (now - self.since).to_std().is_ok_and(|quiet| quiet > self.threshold);
+        (quiet && !std::mem::replace(&mut self.alerted, true)).then_some(self.since)
+    }
+}
+
+/// POSTs an alert that no new version has appeared in the output since `since` to a webhook. A failure is only logged.
+pub async fn send_quiet_alert(client: &reqwest::Client, webhook: &str, output: &str, since: DateTime<Utc>) {
+    let body = serde_json::json!({
+        "event": "no_new_versions",
+        "severity": "warning",
+        "output": output,
+        "since": since.to_rfc3339_opts(SecondsFormat::Secs, true),
+    });
+
+    let response = client.post(webhook)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body.to_string())
+        .send().await
+        .and_then(|r| r.error_for_status());
+    if let Err(why) = response {
+        tracing::warn!("Couldn't send no new versions alert: {}", why);
+    }
+}
@@ -0,0 +1,59 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The pin file: the SHA-1 every version was first hashed with, kept apart from the state file so it can be committed
+//! and reviewed, and shared by everything generating the same document.
+//!
+//! ```json
+//! { "5.17.31-ely.2": "0a4d55a8d778e5022fab701977c5d840bbc486d0" }
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+/// SHA-1 of full versions, keyed like the hashes of the state file.
+#[derive(Default)]
+pub struct Pins(pub BTreeMap<String, String>);
+
+impl Pins {
+    /// Loads a pin file, or returns no pins if it doesn't exist yet. A pin that isn't a SHA-1 makes the whole file
+    /// invalid.
+    pub fn load(path: &Path) -> std::io::Result<Pins> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(why) if why.kind() == ErrorKind::NotFound => return Ok(Pins::default()),
+            Err(why) => return Err(why),
+        };
+        let pins: BTreeMap<String, String> = serde_json::from_str(&contents).map_err(|why| Error::new(ErrorKind::InvalidData, why))?;
+
+        pins.into_iter()
+            .map(|(version, sha1)| if sha1.len() == 40 && sha1.chars().all(|c| c.is_ascii_hexdigit()) {
+                Ok((version, sha1.to_ascii_lowercase()))
+            } else {
+                Err(Error::new(ErrorKind::InvalidData, format!("the pin of {} isn't a SHA-1", version)))
+            })
+            .collect::<Result<_, _>>()
+            .map(Pins)
+    }
+
+    /// Writes the pins sorted by version, so an unchanged file is written byte for byte the same.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(&self.0).expect("pins are always serializable") + "\n")
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Writing the overrides as PrismLauncher meta components, in the layout of meta.prismlauncher.org.
+
+use crate::crash;
+use crate::error::GeneratorError;
+use crate::hashing::{Digests, HashAlgorithm};
+use crate::injector;
+use crate::model::{LibraryEntry, MetadataFile};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::path::Path;
+
+const FORMAT_VERSION: u32 = 1;
+/// The component of Minecraft itself
+const MINECRAFT_UID: &str = "net.minecraft";
+/// Maven coordinates the authlib-injector is published under
+const INJECTOR_NAME: &str = "moe.yushi:authlibinjector";
+
+/// Writes a component per overridden library into `directory`: `UID/VERSION.json` for every entry and `UID/index.json`
+/// listing them, plus `index.json` listing the components. The UID is the library's `group:artifact` with dots only.
+/// The version the `latest` alias points to, or else the newest, is the recommended one. The authlib-injector, when
+/// the document has one, is a component of its own adding it as a Java agent, for the launcher to pass the
+/// authentication server to.
+///
+/// With `--minecraft-versions`, every version requires the Minecraft version shipping the library version it overrides.
+/// Release times are those of the entries, or else the `Last-Modified` of the artifacts, or `fallback_time` if their
+/// server doesn't announce one.
+pub async fn write_components(client: &reqwest::Client, document: &MetadataFile, directory: &Path, fallback_time: DateTime<Utc>)
+    -> Result<(), GeneratorError> {
+    let mut packages = Vec::new();
+    for (library, overrides) in &document.overrides {
+        let uid = library.replace(':', ".");
+        // The latest alias isn't a version of its own
+        let entries: Vec<(&String, &LibraryEntry)> = overrides.0.iter().filter(|(version, _)| *version != "latest").collect();
+        let recommended = overrides.0.get("latest").or(entries.first().map(|(_, entry)| *entry)).map(|entry| entry.name.as_str());
+        let release_times = futures::future::join_all(entries.iter().map(|(_, entry)| async {
+            match entry.release_time.as_deref().and_then(|time| DateTime::parse_from_rfc3339(time).ok()) {
+                Some(time) => Some(time.with_timezone(&Utc)),
+                None => release_time(client, &entry.url).await,
+            }
+        })).await;
+
+        let versions = entries.into_iter().zip(release_times).map(|((version, entry), released)| ComponentVersion {
+            version: version.clone(),
+            released: released.unwrap_or(fallback_time),
+            recommended: recommended == Some(entry.name.as_str()),
+            requires: requires(&entry.minecraft_versions),
+            contents: serde_json::json!({
+                "libraries": [{
+                    "name": entry.name,
+                    "downloads": { "artifact": { "sha1": entry.digests.sha1, "size": entry.size, "url": entry.url } },
+                }],
+            }),
+        });
+        packages.push(write_package(directory, library, &uid, versions)?);
+    }
+
+    if let Some(injector) = &document.extras.authlib_injector {
+        // Builds named other than by their version only have the one
+        let version = injector.version.clone().or_else(|| injector::version_from_url(&injector.url))
+            .unwrap_or_else(|| "latest".to_string());
+        let released = release_time(client, &injector.url).await.unwrap_or(fallback_time);
+        let agent = ComponentVersion {
+            contents: serde_json::json!({
+                "+agents": [{
+                    "name": format!("{}:{}", INJECTOR_NAME, version),
+                    "downloads": { "artifact": { "sha1": injector.digests.sha1, "size": injector.size, "url": injector.url } },
+                }],
+            }),
+            version,
+            released,
+            recommended: true,
+            requires: None,
+        };
+        packages.push(write_package(directory, INJECTOR_NAME, &INJECTOR_NAME.replace(':', "."), [agent])?);
+    }
+
+    write(&directory.join("index.json"), &serde_json::json!({ "formatVersion": FORMAT_VERSION, "packages": packages }))?;
+    Ok(())
+}
+
+/// A version of a component, `contents` holding what it adds to the game.
+struct ComponentVersion {
+    version: String,
+    released: DateTime<Utc>,
+    recommended: bool,
+    requires: Option<serde_json::Value>,
+    contents: serde_json::Value,
+}
+
+/// Writes the files of the versions of a component and its index, returning its entry in the index of components.
+fn write_package(directory: &Path, name: &str, uid: &str, versions: impl IntoIterator<Item = ComponentVersion>)
+    -> Result<serde_json::Value, GeneratorError> {
+    let package_directory = directory.join(uid);
+    std::fs::create_dir_all(&package_directory)
+        .map_err(|source| GeneratorError::Io { action: "create", path: package_directory.clone(), source })?;
+
+    let mut listed_versions = Vec::new();
+    for version in versions {
+        let released = version.released.to_rfc3339_opts(SecondsFormat::Secs, false);
+        let mut component = serde_json::json!({
+            "formatVersion": FORMAT_VERSION,
+            "name": name,
+            "uid": uid,
+            "version": version.version,
+            "releaseTime": released,
+            "type": "release",
+        });
+        let mut listed = serde_json::json!({
+            "version": version.version,
+            "releaseTime": released,
+            "type": "release",
+            "recommended": version.recommended,
+        });
+        if let Some(requires) = version.requires {
+            component["requires"] = requires.clone();
+            listed["requires"] = requires;
+        }
+        if let (Some(component), serde_json::Value::Object(contents)) = (component.as_object_mut(), version.contents) {
+            component.extend(contents);
+        }
+        listed["sha256"] = write(&package_directory.join(format!("{}.json", version.version)), &component)?.into();
+        listed_versions.push(listed);
+    }
+
+    let index = serde_json::json!({ "formatVersion": FORMAT_VERSION, "name": name, "uid": uid, "versions": listed_versions });
+    let sha256 = write(&package_directory.join("index.json"), &index)?;
+    Ok(serde_json::json!({ "name": name, "uid": uid, "sha256": sha256 }))
+}
+
+/// What a component needs of `net.minecraft`, from the Minecraft versions shipping the library version it overrides,
+/// newest first. One is required exactly; of several, which Prism can't require at once, the newest is suggested.
+fn requires(minecraft_versions: &[String]) -> Option<serde_json::Value> {
+    match minecraft_versions {
+        [] => None,
+        [version] => Some(serde_json::json!([{ "uid": MINECRAFT_UID, "equals": version }])),
+        [newest, ..] => Some(serde_json::json!([{ "uid": MINECRAFT_UID, "suggests": newest }])),
+    }
+}
+
+/// Writes a JSON file, returning the SHA-256 of its contents for the index listing it.
+fn write(path: &Path, value: &serde_json::Value) -> Result<String, GeneratorError> {
+    let contents = serde_json::to_string_pretty(value).expect("JSON values are always serializable");
+    std::fs::write(path, &contents).map_err(|source| GeneratorError::Io { action: "write", path: path.to_path_buf(), source })?;
+
+    Ok(Digests::compute(contents.as_bytes(), &[HashAlgorithm::Sha256]).sha256.unwrap_or_default())
+}
+
+async fn release_time(client: &reqwest::Client, url: &str) -> Option<DateTime<Utc>> {
+    crash::attempting(url);
+    let response = client.head(url).send().await.ok()?.error_for_status().ok()?;
+    let last_modified = response.headers().get(reqwest::header::LAST_MODIFIED)?.to_str().ok()?;
+    DateTime::parse_from_rfc2822(last_modified).ok().map(|time| time.with_timezone(&Utc))
+}
@@ -0,0 +1,101 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::hashing::Digests;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Journal of artifacts hashed during the current run, so that a run that gets killed can be
+/// resumed without downloading everything again. Stored as one JSON object per line.
+pub struct Progress {
+    path: PathBuf,
+    file: Mutex<File>,
+    completed: HashMap<String, (Digests, usize, Option<DateTime<Utc>>)>,
+}
+
+impl Progress {
+    /// Opens the journal at `path`. With `resume`, entries left behind by an interrupted run are
+    /// kept and can be reused; otherwise the journal starts out empty.
+    pub fn open(path: PathBuf, resume: bool) -> std::io::Result<Progress> {
+        let completed = if resume { load(&path) } else { HashMap::new() };
+        if !completed.is_empty() {
+            tracing::info!("Resuming interrupted run, reusing {} already hashed artifact(s)", completed.len());
+        }
+
+        let file = if resume {
+            std::fs::OpenOptions::new().create(true).append(true).open(&path)?
+        } else {
+            File::create(&path)?
+        };
+
+        Ok(Progress { path, file: Mutex::new(file), completed })
+    }
+
+    /// Returns the digests and size recorded for the URL by an interrupted run.
+    pub fn completed(&self, url: &str) -> Option<&(Digests, usize, Option<DateTime<Utc>>)> {
+        self.completed.get(url)
+    }
+
+    pub fn record(&self, url: &str, digests: &Digests, size: usize, release_time: Option<DateTime<Utc>>) {
+        let release_time = release_time.map(|time| time.to_rfc3339_opts(SecondsFormat::Secs, true));
+        let line = serde_json::to_string(&Entry { url: url.to_string(), digests: digests.clone(), size, release_time }).unwrap();
+        let mut file = self.file.lock().unwrap();
+        if let Err(why) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+            tracing::warn!("Couldn't record progress in {}: {}", self.path.display(), why);
+        }
+    }
+
+    /// Removes the journal once the run has completed.
+    pub fn finish(self) {
+        drop(self.file);
+        if let Err(why) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Couldn't remove {}: {}", self.path.display(), why);
+        }
+    }
+}
+
+fn load(path: &Path) -> HashMap<String, (Digests, usize, Option<DateTime<Utc>>)> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return HashMap::new()
+    };
+
+    // The last line may have been cut off when the process was killed
+    contents.lines()
+        .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+        .map(|entry| {
+            let release_time = entry.release_time.and_then(|time| DateTime::parse_from_rfc3339(&time).ok())
+                .map(|time| time.with_timezone(&Utc));
+            (entry.url, (entry.digests, entry.size, release_time))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    url: String,
+    #[serde(flatten)]
+    digests: Digests,
+    size: usize,
+    #[serde(rename = "releaseTime", default, skip_serializing_if = "Option::is_none")]
+    release_time: Option<String>,
+}
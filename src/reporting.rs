@@ -0,0 +1,216 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Opt-in error reporting to Sentry (or anything speaking its store API).
+
+use crate::logging;
+use indexmap::IndexMap;
+use reqwest::Url;
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Collects errors during a run and sends them as a single Sentry event when the run is over.
+pub struct ErrorReporter {
+    target: Option<SentryTarget>,
+    extra: Mutex<Vec<(&'static str, String)>>,
+    errors: Mutex<Vec<ReportedError>>,
+}
+
+struct SentryTarget {
+    store_url: Url,
+    public_key: String,
+}
+
+struct ReportedError {
+    message: String,
+    /// The error followed by each of its sources
+    chain: Vec<String>,
+    context: Vec<(&'static str, String)>,
+}
+
+impl ErrorReporter {
+    /// Creates a reporter for the given DSN. Without one (or with an invalid one), nothing is ever sent.
+    pub fn new(dsn: Option<&str>) -> ErrorReporter {
+        let target = dsn.and_then(|dsn| {
+            let target = SentryTarget::parse(dsn);
+            if target.is_none() {
+                tracing::warn!("Ignoring invalid Sentry DSN, error reporting is disabled");
+            }
+            target
+        });
+
+        ErrorReporter {
+            target,
+            extra: Mutex::new(Vec::new()),
+            errors: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Attaches a piece of run-wide context (such as a configured URL) to the report, replacing what was attached
+    /// under the same key by an earlier run.
+    pub fn set_extra(&self, key: &'static str, value: impl ToString) {
+        let mut extra = self.extra.lock().unwrap();
+        match extra.iter_mut().find(|(existing, _)| *existing == key) {
+            Some((_, existing)) => *existing = value.to_string(),
+            None => extra.push((key, value.to_string())),
+        }
+    }
+
+    /// Records a non-fatal error along with the context it happened in.
+    pub fn record(&self, message: impl ToString, error: &dyn std::error::Error, context: Vec<(&'static str, String)>) {
+        let mut chain = vec![error.to_string()];
+        let mut source = error.source();
+        while let Some(cause) = source {
+            chain.push(cause.to_string());
+            source = cause.source();
+        }
+
+        self.errors.lock().unwrap().push(ReportedError {
+            message: message.to_string(),
+            chain,
+            context,
+        });
+    }
+
+    /// Sends an event if the run failed (`failure` is set) or any errors were recorded along the way.
+    pub async fn finish(&self, client: &reqwest::Client, failure: Option<String>) {
+        let Some(target) = &self.target else {
+            return
+        };
+        let errors = std::mem::take(&mut *self.errors.lock().unwrap());
+        if failure.is_none() && errors.is_empty() {
+            return
+        }
+
+        let (level, message) = match &failure {
+            Some(failure) => ("error", failure.clone()),
+            None => ("warning", format!("Run completed with {} error(s)", errors.len())),
+        };
+
+        let mut exceptions = Vec::new();
+        for error in &errors {
+            // Sentry expects the innermost cause first
+            for (i, cause) in error.chain.iter().enumerate().rev() {
+                exceptions.push(Exception {
+                    kind: if i == 0 { &error.message } else { "caused by" },
+                    value: cause,
+                    mechanism: (!error.context.is_empty()).then(|| Mechanism {
+                        kind: "generic",
+                        // Recorded errors didn't end the run
+                        handled: true,
+                        data: error.context.iter().map(|(key, value)| (*key, value.as_str())).collect(),
+                    }),
+                });
+            }
+        }
+
+        let extra = self.extra.lock().unwrap().clone();
+        let event = Event {
+            event_id: format!("{:032x}", rand::random::<u128>()),
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs_f64(),
+            platform: "other",
+            level,
+            logger: env!("CARGO_PKG_NAME"),
+            release: concat!(env!("CARGO_PKG_NAME"), "@", env!("CARGO_PKG_VERSION")),
+            message: Message { formatted: message },
+            exception: Exceptions { values: exceptions },
+            extra: extra.iter().map(|(key, value)| (*key, value.as_str())).collect(),
+        };
+        let event = serde_json::to_string(&event).expect("events are always serializable");
+
+        let auth = format!("Sentry sentry_version=7, sentry_key={}, sentry_client={}/{}",
+            target.public_key, env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        let response = client.post(target.store_url.clone())
+            .header("X-Sentry-Auth", auth)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(logging::redact(&event))
+            .send().await
+            .and_then(|r| r.error_for_status());
+        if let Err(why) = response {
+            tracing::warn!("Couldn't send error report: {}", why);
+        }
+    }
+}
+
+impl SentryTarget {
+    /// Parses a DSN of the form `https://<public key>@<host>[/<path>]/<project id>`.
+    fn parse(dsn: &str) -> Option<SentryTarget> {
+        let dsn = Url::parse(dsn).ok()?;
+        let public_key = dsn.username();
+        if public_key.is_empty() {
+            return None
+        }
+
+        let path = dsn.path().trim_end_matches('/');
+        let (prefix, project_id) = path.rsplit_once('/')?;
+        if project_id.is_empty() {
+            return None
+        }
+
+        let mut store_url = dsn.clone();
+        store_url.set_username("").ok()?;
+        store_url.set_password(None).ok()?;
+        store_url.set_path(&format!("{}/api/{}/store/", prefix, project_id));
+
+        Some(SentryTarget { store_url, public_key: public_key.to_string() })
+    }
+}
+
+/// An event of the store API, as far as the reporter fills it in.
+#[derive(Serialize)]
+struct Event<'a> {
+    event_id: String,
+    timestamp: f64,
+    platform: &'static str,
+    level: &'static str,
+    logger: &'static str,
+    release: &'static str,
+    message: Message,
+    exception: Exceptions<'a>,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    extra: IndexMap<&'static str, &'a str>,
+}
+
+#[derive(Serialize)]
+struct Message {
+    formatted: String,
+}
+
+#[derive(Serialize)]
+struct Exceptions<'a> {
+    values: Vec<Exception<'a>>,
+}
+
+#[derive(Serialize)]
+struct Exception<'a> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    value: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mechanism: Option<Mechanism<'a>>,
+}
+
+/// Where the error happened, such as the URL that was being downloaded.
+#[derive(Serialize)]
+struct Mechanism<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    handled: bool,
+    data: IndexMap<&'static str, &'a str>,
+}
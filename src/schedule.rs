@@ -0,0 +1,120 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! When --watch and serve cycles run, following how often upstream changes with --min-interval and --max-interval.
+
+use chrono::{DateTime, Utc};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How many of the latest upstream changes the cadence is worked out from
+const HISTORY: usize = 8;
+
+/// An interval between cycles that drops to the minimum when the Maven metadata's `<lastUpdated>` changes, as more
+/// releases tend to follow, then doubles with every unchanged cycle up to a quarter of the usual time between changes,
+/// or the maximum until that is known.
+pub struct AdaptiveInterval {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    last_updated: Option<DateTime<Utc>>,
+    changes: VecDeque<DateTime<Utc>>,
+}
+
+impl AdaptiveInterval {
+    /// Starts at `initial`, kept within `min` and `max`.
+    pub fn new(initial: Duration, min: Duration, max: Duration) -> AdaptiveInterval {
+        let max = max.max(min);
+        AdaptiveInterval { min, max, current: initial.clamp(min, max), last_updated: None, changes: VecDeque::new() }
+    }
+
+    /// Takes in the `<lastUpdated>` a cycle saw, if it knows, and returns the interval until the next one.
+    pub fn observe(&mut self, last_updated: Option<DateTime<Utc>>) -> Duration {
+        let Some(last_updated) = last_updated else {
+            return self.current
+        };
+        match self.last_updated.replace(last_updated) {
+            Some(previous) if previous != last_updated => {
+                if self.changes.len() == HISTORY {
+                    self.changes.pop_front();
+                }
+                self.changes.push_back(last_updated);
+                self.current = self.min;
+            }
+            _ => self.current = (self.current * 2).min(self.target()),
+        }
+        self.current
+    }
+
+    /// The longest the interval grows while upstream stays unchanged.
+    fn target(&self) -> Duration {
+        let mut gaps: Vec<Duration> = self.changes.iter().zip(self.changes.iter().skip(1))
+            .filter_map(|(earlier, later)| (*later - *earlier).to_std().ok())
+            .collect();
+        if gaps.is_empty() {
+            return self.max
+        }
+        gaps.sort();
+        (gaps[gaps.len() / 2] / 4).clamp(self.min, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn at(hours: i64) -> Option<DateTime<Utc>> {
+        Some(Utc.timestamp_opt(hours * 3600, 0).unwrap())
+    }
+
+    #[test]
+    fn backs_off_to_the_maximum_while_unchanged() {
+        let mut interval = AdaptiveInterval::new(10 * MINUTE, MINUTE, 60 * MINUTE);
+        assert_eq!(interval.observe(at(0)), 20 * MINUTE);
+        assert_eq!(interval.observe(at(0)), 40 * MINUTE);
+        assert_eq!(interval.observe(at(0)), 60 * MINUTE);
+        assert_eq!(interval.observe(at(0)), 60 * MINUTE);
+    }
+
+    #[test]
+    fn drops_to_the_minimum_when_upstream_changes() {
+        let mut interval = AdaptiveInterval::new(60 * MINUTE, MINUTE, 60 * MINUTE);
+        interval.observe(at(0));
+        assert_eq!(interval.observe(at(1)), MINUTE);
+        assert_eq!(interval.observe(at(1)), 2 * MINUTE);
+    }
+
+    #[test]
+    fn grows_up_to_a_quarter_of_the_usual_time_between_changes() {
+        let mut interval = AdaptiveInterval::new(MINUTE, MINUTE, 24 * 60 * MINUTE);
+        for hour in [0, 1, 2, 3] {
+            interval.observe(at(hour));
+        }
+        let grown = (0..10).map(|_| interval.observe(at(3))).last();
+        assert_eq!(grown, Some(15 * MINUTE));
+    }
+
+    #[test]
+    fn keeps_the_interval_without_a_last_updated() {
+        let mut interval = AdaptiveInterval::new(10 * MINUTE, MINUTE, 60 * MINUTE);
+        assert_eq!(interval.observe(None), 10 * MINUTE);
+    }
+}
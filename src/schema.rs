@@ -0,0 +1,256 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! The JSON Schema of generated documents, and checking documents against it.
+
+use crate::model::FormatVersion;
+use regex::Regex;
+use serde_json::{json, Value};
+
+/// The JSON Schema (draft 2020-12) every generated document in the layout of `format` conforms to.
+pub fn schema(format: FormatVersion) -> Value {
+    let digest = |length: usize| json!({ "type": "string", "pattern": format!("^[0-9a-f]{{{}}}$", length) });
+    let extras = match format {
+        FormatVersion::V1 => json!({
+            "type": "object",
+            "properties": {
+                "authlib-injector": { "$ref": "#/$defs/injector" },
+                "ely.by": { "$ref": "#/$defs/elyEndpoints" },
+                "authlib-injector-channels": { "$ref": "#/$defs/injectorChannels" },
+            },
+            "additionalProperties": {
+                "description": "Extras given with --extra and --extra-artifact",
+                "oneOf": [{ "type": "string" }, { "$ref": "#/$defs/artifact" }],
+            },
+        }),
+        FormatVersion::V2 => json!({
+            "type": "object",
+            "properties": {
+                "authlib-injector": { "$ref": "#/$defs/injector" },
+                "ely.by": { "$ref": "#/$defs/elyEndpoints" },
+                "authlib-injector-channels": { "$ref": "#/$defs/injectorChannels" },
+                "values": {
+                    "description": "Extras given with --extra",
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                },
+                "artifacts": {
+                    "description": "Extras given with --extra-artifact",
+                    "type": "object",
+                    "additionalProperties": { "$ref": "#/$defs/artifact" },
+                },
+            },
+            "additionalProperties": false,
+        }),
+    };
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "ElyPrismLauncher library override metadata",
+        "type": "object",
+        "properties": {
+            "formatVersion": {
+                "description": "Layout of the document, 1 when missing",
+                "const": format.number(),
+            },
+            "overrides": {
+                "description": "Overrides by the `group:artifact` of the library they replace",
+                "type": "object",
+                "additionalProperties": { "$ref": "#/$defs/overrides" },
+            },
+            "extras": { "$ref": "#/$defs/extras" },
+            "warnings": {
+                "description": "What the generator left out or couldn't check",
+                "type": "array",
+                "items": { "type": "string" },
+            },
+        },
+        "required": match format {
+            FormatVersion::V1 => json!(["overrides", "extras"]),
+            FormatVersion::V2 => json!(["formatVersion", "overrides", "extras"]),
+        },
+        "additionalProperties": false,
+        "$defs": {
+            "overrides": {
+                "description": "Entries by the base version they apply to, newest first, and `latest` for the newest one \
+                    with --latest-alias",
+                "type": "object",
+                "additionalProperties": { "$ref": "#/$defs/libraryEntry" },
+            },
+            "libraryEntry": with_digests(format, json!({
+                "name": { "description": "Maven coordinates of the replacement", "type": "string" },
+                "url": { "type": "string" },
+                "size": { "type": "integer", "minimum": 0 },
+                "releaseTime": {
+                    "description": "When the artifact was published, in ISO 8601",
+                    "type": "string",
+                    "pattern": "^\\d{4}-\\d{2}-\\d{2}T\\d{2}:\\d{2}:\\d{2}(\\.\\d+)?(Z|[+-]\\d{2}:\\d{2})$",
+                },
+                "vanilla": { "description": "The entry is the unpatched library, only present when true", "type": "boolean" },
+                "manual": { "description": "Hand-maintained entry that --merge leaves as it is", "type": "boolean" },
+                "minecraftVersions": {
+                    "description": "Minecraft versions shipping the overridden library version, newest first",
+                    "type": "array",
+                    "items": { "type": "string" },
+                },
+            }), &["name", "url", "size"]),
+            "extras": extras,
+            "injector": with_digests(format, json!({
+                "url": { "type": "string" },
+                "version": {
+                    "description": "Only present when known from the authlib-injector API or the file name",
+                    "type": "string",
+                },
+                "size": { "type": "integer", "minimum": 0 },
+            }), &["url", "size"]),
+            "injectorChannels": {
+                "description": "Further authlib-injector builds, by channel name",
+                "type": "object",
+                "additionalProperties": { "$ref": "#/$defs/injector" },
+            },
+            "elyEndpoints": {
+                "type": "object",
+                "properties": {
+                    "authserver": { "type": "string" },
+                    "sessionserver": { "type": "string" },
+                    "skins": { "type": "string" },
+                },
+                "required": ["authserver", "sessionserver", "skins"],
+                "additionalProperties": false,
+            },
+            "artifact": with_digests(format, json!({
+                "url": { "type": "string" },
+                "size": { "type": "integer", "minimum": 0 },
+            }), &["url", "size"]),
+            "hashes": {
+                "type": "object",
+                "properties": {
+                    "sha1": { "$ref": "#/$defs/sha1" },
+                    "sha256": { "$ref": "#/$defs/sha256" },
+                    "sha512": { "$ref": "#/$defs/sha512" },
+                },
+                "required": ["sha1"],
+                "additionalProperties": false,
+            },
+            "sha1": digest(40),
+            "sha256": digest(64),
+            "sha512": digest(128),
+        },
+    })
+}
+
+/// A closed object schema of `properties` and the digests, next to them in format 1 and under `hashes` in format 2.
+fn with_digests(format: FormatVersion, mut properties: Value, required: &[&str]) -> Value {
+    let mut required = required.to_vec();
+    match format {
+        FormatVersion::V1 => {
+            for algorithm in ["sha1", "sha256", "sha512"] {
+                properties[algorithm] = json!({ "$ref": format!("#/$defs/{}", algorithm) });
+            }
+            required.insert(required.len() - 1, "sha1");
+        }
+        FormatVersion::V2 => {
+            properties["hashes"] = json!({ "$ref": "#/$defs/hashes" });
+            required.insert(required.len() - 1, "hashes");
+        }
+    }
+    json!({ "type": "object", "properties": properties, "required": required, "additionalProperties": false })
+}
+
+/// Checks a document against the [`schema`] of the layout it states, returning where and how it doesn't conform.
+pub fn validate(document: &Value) -> Vec<String> {
+    let format = match document["formatVersion"].as_u64() {
+        Some(2) => FormatVersion::V2,
+        _ => FormatVersion::V1,
+    };
+    let schema = schema(format);
+    let mut violations = Vec::new();
+    check(&schema, &schema, document, "$", &mut violations);
+    violations
+}
+
+/// Checks `value` at `path` against `schema`, supporting the keywords [`schema`] uses.
+fn check(root: &Value, schema: &Value, value: &Value, path: &str, violations: &mut Vec<String>) {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let definition = reference.strip_prefix("#/$defs/").map_or(&Value::Null, |name| &root["$defs"][name]);
+        return check(root, definition, value, path, violations)
+    }
+    if let Some(alternatives) = schema["oneOf"].as_array() {
+        let matching = alternatives.iter()
+            .filter(|alternative| {
+                let mut ignored = Vec::new();
+                check(root, alternative, value, path, &mut ignored);
+                ignored.is_empty()
+            })
+            .count();
+        if matching != 1 {
+            violations.push(format!("{}: matches {} of the {} allowed shapes instead of one", path, matching, alternatives.len()));
+        }
+        return
+    }
+
+    if let Some(expected) = schema["type"].as_str() {
+        let matches = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "integer" => value.is_u64() || value.is_i64(),
+            "boolean" => value.is_boolean(),
+            _ => true,
+        };
+        if !matches {
+            violations.push(format!("{}: expected {}, got {}", path, expected, value));
+            return
+        }
+    }
+    if let Some(expected) = schema.get("const")
+        && value != expected {
+        violations.push(format!("{}: expected {}, got {}", path, expected, value));
+    }
+    if let (Some(pattern), Some(string)) = (schema["pattern"].as_str(), value.as_str())
+        && !Regex::new(pattern).is_ok_and(|pattern| pattern.is_match(string)) {
+        violations.push(format!("{}: `{}` doesn't match {}", path, string, pattern));
+    }
+    if let (Some(minimum), Some(number)) = (schema["minimum"].as_i64(), value.as_i64())
+        && number < minimum {
+        violations.push(format!("{}: {} is less than {}", path, number, minimum));
+    }
+    if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+        for (index, item) in array.iter().enumerate() {
+            check(root, items, item, &format!("{}[{}]", path, index), violations);
+        }
+    }
+
+    let Some(object) = value.as_object() else {
+        return
+    };
+    for required in schema["required"].as_array().into_iter().flatten().filter_map(Value::as_str) {
+        if !object.contains_key(required) {
+            violations.push(format!("{}: missing {}", path, required));
+        }
+    }
+    for (key, field) in object {
+        let field_path = format!("{}.{}", path, key);
+        match (schema["properties"].get(key), schema.get("additionalProperties")) {
+            (Some(property), _) => check(root, property, field, &field_path, violations),
+            (None, Some(Value::Bool(false))) => violations.push(format!("{}: not allowed", field_path)),
+            (None, Some(additional)) => check(root, additional, field, &field_path, violations),
+            (None, None) => {}
+        }
+    }
+}
@@ -0,0 +1,132 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Listing artifact versions through repository manager search APIs, for repositories
+//! that don't serve `maven-metadata.xml`.
+
+use crate::download::{self, Connection, DownloadError};
+use reqwest::Url;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+pub enum SearchApi {
+    /// Sonatype Nexus 3 (`/service/rest/v1/search`)
+    Nexus,
+    /// JFrog Artifactory (`/api/search/versions`)
+    Artifactory,
+}
+
+#[derive(Debug)]
+pub enum SearchError {
+    Download(DownloadError),
+    InvalidServer(String),
+    Malformed(String),
+}
+
+impl Display for SearchError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchError::Download(why) => why.fmt(f),
+            SearchError::InvalidServer(why) => write!(f, "Invalid search server URL: {}", why),
+            SearchError::Malformed(why) => write!(f, "Malformed search response: {}", why),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SearchError::Download(why) => Some(why),
+            _ => None,
+        }
+    }
+}
+
+impl From<DownloadError> for SearchError {
+    fn from(why: DownloadError) -> Self {
+        SearchError::Download(why)
+    }
+}
+
+/// Lists every version of `group:artifact` in `repository` on the repository manager at `server`. Responses are
+/// fetched like Maven metadata, so credentials, retries, size limits and the HTTP cache apply to them too.
+pub async fn list_versions(connection: &Connection<'_>, api: SearchApi, server: &str, repository: &str, group: &str,
+    artifact: &str) -> Result<Vec<String>, SearchError> {
+    let server = server.trim_end_matches('/');
+    let mut versions: Vec<String> = Vec::new();
+    match api {
+        SearchApi::Nexus => {
+            let url = format!("{}/service/rest/v1/search", server);
+            let mut continuation_token: Option<String> = None;
+            loop {
+                let mut query = vec![("repository", repository), ("group", group), ("name", artifact)];
+                if let Some(token) = &continuation_token {
+                    query.push(("continuationToken", token));
+                }
+                let page: NexusPage = fetch_json(connection, &url, &query).await?;
+                for item in page.items {
+                    if !versions.contains(&item.version) {
+                        versions.push(item.version);
+                    }
+                }
+
+                continuation_token = page.continuation_token;
+                if continuation_token.is_none() {
+                    break
+                }
+            }
+        }
+        SearchApi::Artifactory => {
+            let url = format!("{}/api/search/versions", server);
+            let response: ArtifactoryVersions =
+                fetch_json(connection, &url, &[("g", group), ("a", artifact), ("repos", repository)]).await?;
+            versions.extend(response.results.into_iter().map(|result| result.version));
+        }
+    }
+
+    Ok(versions)
+}
+
+/// A page of Nexus search results, followed by more while it has a continuation token.
+#[derive(Deserialize)]
+struct NexusPage {
+    #[serde(default)]
+    items: Vec<Versioned>,
+    #[serde(rename = "continuationToken")]
+    continuation_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ArtifactoryVersions {
+    #[serde(default)]
+    results: Vec<Versioned>,
+}
+
+/// A search result, of which only the version is read.
+#[derive(Deserialize)]
+struct Versioned {
+    version: String,
+}
+
+async fn fetch_json<T: DeserializeOwned>(connection: &Connection<'_>, url: &str, query: &[(&str, &str)]) -> Result<T, SearchError> {
+    let url = Url::parse_with_params(url, query).map_err(|why| SearchError::InvalidServer(why.to_string()))?;
+    let body = download::fetch_cached(connection, url.as_str()).await?;
+    serde_json::from_slice(&body).map_err(|why| SearchError::Malformed(why.to_string()))
+}
@@ -0,0 +1,54 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Secrets kept in the OS keyring rather than in config files or the environment, by name: the Keychain on macOS,
+//! the Credential Manager on Windows and the kernel keyring of the user on Linux, whose entries don't survive a
+//! reboot.
+
+use keyring::Entry;
+
+/// Service the entries are stored under, next to the name they are selected by
+const SERVICE: &str = env!("CARGO_PKG_NAME");
+
+/// The secret stored under `name`.
+pub fn read(name: &str) -> Result<String, String> {
+    entry(name)?.get_password().map_err(|why| match why {
+        keyring::Error::NoEntry => format!("no secret named `{}`", name),
+        why => format!("couldn't read `{}`: {}", name, why),
+    })
+}
+
+/// Stores `secret` under `name`, replacing the secret stored there before.
+pub fn store(name: &str, secret: &str) -> Result<(), String> {
+    entry(name)?.set_password(secret).map_err(|why| format!("couldn't store `{}`: {}", name, why))
+}
+
+/// Removes the secret stored under `name`.
+pub fn delete(name: &str) -> Result<(), String> {
+    entry(name)?.delete_credential().map_err(|why| match why {
+        keyring::Error::NoEntry => format!("no secret named `{}`", name),
+        why => format!("couldn't delete `{}`: {}", name, why),
+    })
+}
+
+fn entry(name: &str) -> Result<Entry, String> {
+    if name.is_empty() {
+        return Err("secret names can't be empty".to_string())
+    }
+    Entry::new(SERVICE, name).map_err(|why| format!("invalid secret name `{}`: {}", name, why))
+}
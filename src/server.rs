@@ -0,0 +1,195 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! A minimal HTTP server for the generated document, so launchers can be pointed directly at the generator, and for
+//! the metrics of the process.
+
+use crate::metrics::SharedMetrics;
+use sha1::{Digest, Sha1};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::Semaphore;
+
+/// Requests larger than this are rejected, the server only ever needs a request line and a few headers
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+/// How long a client has to send its headers, so ones that never finish don't hold on to their connection
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Connections answered at once, further ones wait to be accepted
+const MAX_CONNECTIONS: usize = 256;
+
+/// The document currently being served, with the validators sent along with it.
+pub struct ServedDocument {
+    body: Vec<u8>,
+    etag: String,
+    last_modified: String,
+}
+
+/// The served document, shared between the server and whatever regenerates it. `None` until the first run completes.
+pub type SharedDocument = Arc<RwLock<Option<ServedDocument>>>;
+
+/// Replaces the served document, unless its contents didn't change, so that `Last-Modified` stays accurate.
+pub fn update(document: &SharedDocument, body: Vec<u8>) {
+    let etag = format!("\"{}\"", hex::encode(Sha1::digest(&body)));
+    let mut document = document.write().unwrap();
+    if document.as_ref().is_some_and(|current| current.etag == etag) {
+        return
+    }
+
+    *document = Some(ServedDocument {
+        body,
+        etag,
+        last_modified: chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+    });
+}
+
+/// What a server answers requests for.
+pub struct Endpoints {
+    /// Path to serve the document at, and the document
+    pub document: Option<(String, SharedDocument)>,
+    /// Served at `/metrics`, unless the document is
+    pub metrics: Option<SharedMetrics>,
+    /// Directory whose files are served at their path below it, for every other path
+    pub files: Option<PathBuf>,
+}
+
+/// Answers every connection accepted on the listener, forever.
+pub async fn serve(listener: TcpListener, endpoints: Endpoints) {
+    let endpoints = Arc::new(endpoints);
+    let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+    loop {
+        let permit = connections.clone().acquire_owned().await.expect("the semaphore is never closed");
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(why) => {
+                tracing::warn!("Couldn't accept connection: {}", why);
+                continue
+            }
+        };
+
+        let endpoints = endpoints.clone();
+        tokio::spawn(async move {
+            if let Err(why) = respond(stream, &endpoints).await {
+                tracing::warn!("Couldn't answer request: {}", why);
+            }
+            drop(permit);
+        });
+    }
+}
+
+/// Answers a single request and closes the connection.
+async fn respond(mut stream: TcpStream, endpoints: &Endpoints) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    let deadline = tokio::time::Instant::now() + REQUEST_TIMEOUT;
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        let Ok(read) = tokio::time::timeout_at(deadline, stream.read(&mut buffer)).await else {
+            return stream.write_all(&response("408 Request Timeout", &[], b"", true)).await
+        };
+        let read = read?;
+        if read == 0 {
+            return Ok(())
+        }
+        request.extend_from_slice(&buffer[..read]);
+        if request.len() > MAX_REQUEST_SIZE {
+            return stream.write_all(&response("431 Request Header Fields Too Large", &[], b"", true)).await
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut lines = request.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (method, target) = (request_line.next().unwrap_or_default(), request_line.next().unwrap_or_default());
+    let if_none_match = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("If-None-Match"))
+        .map(|(_, value)| value.trim().to_string());
+
+    // HEAD gets the headers GET would, without the body
+    let send_body = method != "HEAD";
+    let target = target.split('?').next().unwrap_or_default();
+    let document = endpoints.document.as_ref().filter(|(path, _)| path == target).map(|(_, document)| document);
+    let metrics = endpoints.metrics.as_ref().filter(|_| target == "/metrics");
+    let file = match &endpoints.files {
+        Some(root) if document.is_none() && metrics.is_none() => read_file(root, target).await,
+        _ => None,
+    };
+    let reply = if document.is_none() && metrics.is_none() && file.is_none() {
+        response("404 Not Found", &[], b"", send_body)
+    } else if method != "GET" && method != "HEAD" {
+        response("405 Method Not Allowed", &[("Allow", "GET, HEAD")], b"", send_body)
+    } else if let Some(document) = document {
+        match document.read().unwrap().as_ref() {
+            None => response("503 Service Unavailable", &[("Retry-After", "10")], b"", send_body),
+            Some(served) => {
+                let validators = [("ETag", served.etag.as_str()), ("Last-Modified", served.last_modified.as_str())];
+                let current = if_none_match.as_deref()
+                    .is_some_and(|tags| tags.split(',').any(|tag| tag.trim() == served.etag || tag.trim() == "*"));
+                if current {
+                    response("304 Not Modified", &validators, b"", false)
+                } else {
+                    response("200 OK", &[[("Content-Type", "application/json")].as_slice(), &validators].concat(), &served.body, send_body)
+                }
+            }
+        }
+    } else if let Some((body, content_type)) = file {
+        response("200 OK", &[("Content-Type", content_type)], &body, send_body)
+    } else {
+        let metrics = metrics.expect("either the document or the metrics were requested").render();
+        response("200 OK", &[("Content-Type", "text/plain; version=0.0.4")], metrics.as_bytes(), send_body)
+    };
+
+    stream.write_all(&reply).await?;
+    stream.shutdown().await
+}
+
+/// Reads the file at `target` below `root` and its content type, unless there is none or the path leads elsewhere.
+async fn read_file(root: &Path, target: &str) -> Option<(Vec<u8>, &'static str)> {
+    let segments: Vec<_> = target.strip_prefix('/')?.split('/').collect();
+    if segments.iter().any(|segment| segment.is_empty() || *segment == "." || *segment == ".." || segment.contains('\\')) {
+        return None
+    }
+
+    let path = segments.iter().fold(root.to_path_buf(), |path, segment| path.join(segment));
+    let body = tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || std::fs::read(path)
+    }).await.ok()?.ok()?;
+    let content_type = match path.extension().and_then(|extension| extension.to_str()) {
+        Some("json") => "application/json",
+        Some("jar") => "application/java-archive",
+        _ => "application/octet-stream",
+    };
+    Some((body, content_type))
+}
+
+fn response(status: &str, headers: &[(&str, &str)], body: &[u8], send_body: bool) -> Vec<u8> {
+    let mut response = format!("HTTP/1.1 {}\r\nConnection: close\r\n", status);
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+
+    let mut response = response.into_bytes();
+    if send_body {
+        response.extend_from_slice(body);
+    }
+    response
+}
@@ -0,0 +1,66 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Checking PGP signatures with `gpgv`, which has to be on the `PATH`, and making and checking Ed25519 signatures of
+//! documents.
+
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use sha1::{Digest, Sha1};
+use std::path::Path;
+use std::process::Stdio;
+
+/// Checks a detached signature of the file at `data` against the public keys in `keyring`. Returns what `gpgv`
+/// complained about if the signature is bad or by an unknown key.
+pub async fn verify(keyring: &Path, signature: &[u8], data: &Path) -> Result<(), String> {
+    // gpgv looks relative keyring paths up in its home directory
+    let keyring = std::path::absolute(keyring).map_err(|why| format!("Couldn't resolve {}: {}", keyring.display(), why))?;
+    let signature_path = std::env::temp_dir().join(format!("{}-{}.asc", env!("CARGO_PKG_NAME"), hex::encode(Sha1::digest(signature))));
+    std::fs::write(&signature_path, signature).map_err(|why| format!("Couldn't write {}: {}", signature_path.display(), why))?;
+
+    let output = tokio::process::Command::new("gpgv")
+        .arg("--keyring").arg(&keyring)
+        .arg(&signature_path).arg(data)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .await;
+    let _ = std::fs::remove_file(&signature_path);
+
+    let output = output.map_err(|why| format!("Couldn't run gpgv: {}", why))?;
+    if output.status.success() {
+        return Ok(())
+    }
+    let complaint = String::from_utf8_lossy(&output.stderr);
+    Err(complaint.lines().rfind(|line| !line.trim().is_empty()).unwrap_or("gpgv failed").to_string())
+}
+
+/// Signs `data` with a PEM-encoded Ed25519 private key, returning the 64 byte signature.
+pub fn sign_ed25519(private_key: &str, data: &[u8]) -> Result<Vec<u8>, String> {
+    let key = SigningKey::from_pkcs8_pem(private_key).map_err(|why| format!("Couldn't read the private key: {}", why))?;
+    Ok(key.sign(data).to_bytes().to_vec())
+}
+
+/// Checks a detached Ed25519 signature of `data` against a PEM-encoded public key.
+pub fn verify_ed25519(public_key: &Path, signature: &[u8], data: &[u8]) -> Result<(), String> {
+    let pem = std::fs::read_to_string(public_key).map_err(|why| format!("Couldn't read {}: {}", public_key.display(), why))?;
+    let key = VerifyingKey::from_public_key_pem(&pem).map_err(|why| format!("Couldn't read {}: {}", public_key.display(), why))?;
+    let signature = Signature::from_slice(signature).map_err(|_| format!("expected a {} byte signature", Signature::BYTE_SIZE))?;
+    key.verify(data, &signature).map_err(|_| "the signature doesn't match".to_string())
+}
@@ -0,0 +1,97 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Information remembered between runs, stored as JSON next to the output. Its maps are ordered, so an unchanged
+/// state is written byte for byte the same.
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    /// SHA-1 of every full version that has ever been hashed
+    pub hashes: BTreeMap<String, String>,
+    /// `<lastUpdated>` of the Maven metadata used by the last successful run
+    #[serde(rename = "lastUpdated", skip_serializing_if = "Option::is_none")]
+    pub last_updated: Option<String>,
+    /// `ETag` of the authlib-injector response seen by the last successful run
+    #[serde(rename = "injectorETag", skip_serializing_if = "Option::is_none")]
+    pub injector_etag: Option<String>,
+    /// Versions listed upstream for the last successful run, those of other libraries prefixed with their `group:artifact`
+    pub versions: Vec<String>,
+    /// SHA-256 of the output the last successful run left
+    #[serde(rename = "outputSha256", skip_serializing_if = "Option::is_none")]
+    pub output_sha256: Option<String>,
+    /// Consecutive download failures of full versions that haven't succeeded since
+    pub failures: BTreeMap<String, FailureRecord>,
+    /// SHA-256 of every object uploaded from the mirror directory, by its URL, so unchanged ones aren't checked again
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub uploaded: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FailureRecord {
+    pub count: u32,
+    #[serde(rename = "lastAttempt")]
+    pub last_attempt: DateTime<Utc>,
+    #[serde(rename = "lastError", default)]
+    pub last_error: String,
+}
+
+impl State {
+    /// Loads the state file, or returns an empty state if it doesn't exist yet.
+    pub fn load(path: &Path) -> std::io::Result<State> {
+        let contents = match std::fs::read(path) {
+            Ok(contents) => contents,
+            Err(why) if why.kind() == std::io::ErrorKind::NotFound => return Ok(State::default()),
+            Err(why) => return Err(why),
+        };
+        serde_json::from_slice(&contents).map_err(|why| std::io::Error::new(std::io::ErrorKind::InvalidData, why))
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self).expect("the state is always serializable"))
+    }
+
+    pub fn record_failure(&mut self, full_version: &str, error: String) {
+        let record = self.failures.entry(full_version.to_string()).or_insert(FailureRecord {
+            count: 0,
+            last_attempt: Utc::now(),
+            last_error: String::new(),
+        });
+        record.count += 1;
+        record.last_attempt = Utc::now();
+        record.last_error = error;
+    }
+
+    /// Returns the failure record if the version has failed at least `threshold` times in a row
+    /// and was last attempted less than `cooldown` ago.
+    pub fn quarantined(&self, full_version: &str, threshold: u32, cooldown: chrono::Duration) -> Option<&FailureRecord> {
+        self.failures.get(full_version)
+            .filter(|record| record.count >= threshold && Utc::now() - record.last_attempt < cooldown)
+    }
+}
+
+/// An artifact whose hash no longer matches the one recorded in a previous run.
+pub struct HashDrift {
+    pub full_version: String,
+    pub recorded: String,
+    pub computed: String,
+}
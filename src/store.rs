@@ -0,0 +1,89 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+use crate::hashing::{Digests, HashAlgorithm};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Local store of downloaded artifacts, addressed by their SHA-1 (`sha1/ab/abcdef...`),
+/// with an index of the URLs they were downloaded from. Maven artifacts never change,
+/// so bytes found here are reused instead of downloading the URL again.
+pub struct ArtifactStore {
+    root: PathBuf,
+    index: Mutex<BTreeMap<String, String>>,
+}
+
+impl ArtifactStore {
+    pub fn open(root: &Path) -> std::io::Result<ArtifactStore> {
+        std::fs::create_dir_all(root.join("sha1"))?;
+
+        let index = match std::fs::read_to_string(root.join("index.json")) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|why| std::io::Error::new(std::io::ErrorKind::InvalidData, why))?,
+            Err(why) if why.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(why) => return Err(why),
+        };
+
+        Ok(ArtifactStore { root: root.to_path_buf(), index: Mutex::new(index) })
+    }
+
+    /// Returns the stored file for a URL with its digests for the given algorithms and its size, if it's
+    /// present and still matches its hash.
+    pub fn get(&self, url: &str, algorithms: &[HashAlgorithm]) -> Option<(PathBuf, Digests, usize)> {
+        let sha1 = self.index.lock().unwrap().get(url)?.clone();
+        let path = self.blob_path(&sha1);
+        let (digests, size) = Digests::compute_file(&path, algorithms).ok()?;
+
+        if digests.sha1 != sha1 {
+            tracing::warn!("Discarding corrupted stored artifact {}", path.display());
+            let _ = std::fs::remove_file(&path);
+            self.index.lock().unwrap().remove(url);
+            return None
+        }
+
+        Some((path, digests, size))
+    }
+
+    /// Stores a copy of the file an artifact was downloaded to.
+    pub fn put(&self, url: &str, sha1: &str, contents: &Path) {
+        let path = self.blob_path(sha1);
+        if !path.exists() {
+            // Write under a temporary name first, so an interrupted write is never mistaken for the blob
+            let temporary = path.with_extension("tmp");
+            let write = std::fs::create_dir_all(path.parent().unwrap())
+                .and_then(|_| std::fs::copy(contents, &temporary))
+                .and_then(|_| std::fs::rename(&temporary, &path));
+            if let Err(why) = write {
+                tracing::warn!("Couldn't store artifact {}: {}", path.display(), why);
+                return
+            }
+        }
+
+        self.index.lock().unwrap().insert(url.to_string(), sha1.to_string());
+    }
+
+    pub fn save_index(&self) -> std::io::Result<()> {
+        // Ordered by URL, so an unchanged index is written byte for byte the same
+        let index = serde_json::to_string_pretty(&*self.index.lock().unwrap()).expect("the index is always serializable");
+        std::fs::write(self.root.join("index.json"), index)
+    }
+
+    fn blob_path(&self, sha1: &str) -> PathBuf {
+        self.root.join("sha1").join(&sha1[..2.min(sha1.len())]).join(sha1)
+    }
+}
@@ -0,0 +1,73 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Telling systemd how a service is doing, through the socket it passes in `NOTIFY_SOCKET` to units with
+//! `Type=notify`. Outside of such a unit there is no socket and nothing is sent.
+
+use std::time::Duration;
+
+/// Sends `state`, newline-separated `KEY=VALUE` assignments such as `READY=1`, to the service manager.
+pub fn notify(state: &str) {
+    let Some(socket) = std::env::var_os("NOTIFY_SOCKET") else {
+        return
+    };
+    if let Err(why) = send(&socket, state) {
+        tracing::debug!("Couldn't notify the service manager of {}: {}", state, why);
+    }
+}
+
+#[cfg(unix)]
+fn send(socket: &std::ffi::OsStr, state: &str) -> std::io::Result<()> {
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::net::UnixDatagram;
+
+    let datagram = UnixDatagram::unbound()?;
+    // Sockets in the abstract namespace are named with a leading @
+    #[cfg(target_os = "linux")]
+    if let Some(name) = socket.as_bytes().strip_prefix(b"@") {
+        use std::os::linux::net::SocketAddrExt;
+        let address = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+        return datagram.send_to_addr(state.as_bytes(), &address).map(drop)
+    }
+    datagram.send_to(state.as_bytes(), socket).map(drop)
+}
+
+#[cfg(not(unix))]
+fn send(_: &std::ffi::OsStr, _: &str) -> std::io::Result<()> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "notification sockets are only supported on Unix"))
+}
+
+/// How often the service manager expects to hear from the process, if the unit has `WatchdogSec=` set.
+pub fn watchdog_interval() -> Option<Duration> {
+    // Meant for another process if it names one, e.g. the shell the generator was started from
+    if let Ok(pid) = std::env::var("WATCHDOG_PID")
+        && pid.parse() != Ok(std::process::id()) {
+        return None
+    }
+    let microseconds: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    (microseconds > 0).then(|| Duration::from_micros(microseconds))
+}
+
+/// Pings the watchdog at half its interval, for as long as the process runs.
+pub async fn keep_alive(interval: Duration) {
+    let mut ticks = tokio::time::interval(interval / 2);
+    loop {
+        ticks.tick().await;
+        notify("WATCHDOG=1");
+    }
+}
@@ -0,0 +1,271 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Minimal OpenTelemetry trace export over OTLP/HTTP with JSON encoding.
+
+use serde::{Serialize, Serializer};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SERVICE_NAME: &str = "eplmetadatagenerator";
+
+/// Collects the spans of runs and ships them to an OTLP collector once a run is over, each run a trace of its own.
+/// Without an endpoint, spans are still created but never recorded.
+pub struct Tracer {
+    endpoint: Option<String>,
+    trace_id: Mutex<String>,
+    spans: Mutex<Vec<RecordedSpan>>,
+}
+
+impl Tracer {
+    pub fn new(endpoint: Option<String>) -> Tracer {
+        Tracer {
+            endpoint,
+            trace_id: Mutex::new(new_trace_id()),
+            spans: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Puts the spans created from now on into a new trace, for the next run.
+    pub fn start_trace(&self) {
+        *self.trace_id.lock().unwrap() = new_trace_id();
+    }
+
+    pub fn span(&self, name: &'static str) -> Span<'_> {
+        Span::new(self, name, None)
+    }
+
+    /// Sends every recorded span to `{endpoint}/v1/traces`. Failures are reported but never fatal.
+    pub async fn export(&self, client: &reqwest::Client) {
+        let Some(endpoint) = &self.endpoint else {
+            return
+        };
+        let spans = std::mem::take(&mut *self.spans.lock().unwrap());
+        if spans.is_empty() {
+            return
+        }
+
+        let body = ExportRequest {
+            resource_spans: [ResourceSpans {
+                resource: Resource { attributes: vec![Attribute { key: "service.name", value: AttributeValue::from(SERVICE_NAME) }] },
+                scope_spans: [ScopeSpans { scope: Scope { name: SERVICE_NAME, version: env!("CARGO_PKG_VERSION") }, spans }],
+            }],
+        };
+
+        let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+        let response = client.post(&url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_string(&body).expect("spans are always serializable"))
+            .send().await
+            .and_then(|r| r.error_for_status());
+        if let Err(why) = response {
+            tracing::warn!("Couldn't export traces to {}: {}", url, why);
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub enum AttributeValue {
+    #[serde(rename = "stringValue")]
+    String(String),
+    // OTLP/JSON encodes 64-bit integers as strings
+    #[serde(rename = "intValue", serialize_with = "as_string")]
+    Int(i64),
+}
+
+impl From<&str> for AttributeValue {
+    fn from(value: &str) -> Self {
+        AttributeValue::String(value.to_string())
+    }
+}
+
+impl From<String> for AttributeValue {
+    fn from(value: String) -> Self {
+        AttributeValue::String(value)
+    }
+}
+
+impl From<usize> for AttributeValue {
+    fn from(value: usize) -> Self {
+        AttributeValue::Int(value as i64)
+    }
+}
+
+/// A timed operation. The span is recorded when dropped.
+pub struct Span<'a> {
+    tracer: &'a Tracer,
+    name: &'static str,
+    span_id: String,
+    parent_span_id: Option<String>,
+    start: SystemTime,
+    attributes: Vec<(&'static str, AttributeValue)>,
+    error: Option<String>,
+}
+
+impl<'a> Span<'a> {
+    fn new(tracer: &'a Tracer, name: &'static str, parent_span_id: Option<String>) -> Span<'a> {
+        Span {
+            tracer,
+            name,
+            span_id: format!("{:016x}", rand::random::<u64>()),
+            parent_span_id,
+            start: SystemTime::now(),
+            attributes: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn child(&self, name: &'static str) -> Span<'a> {
+        Span::new(self.tracer, name, Some(self.span_id.clone()))
+    }
+
+    pub fn set_attribute(&mut self, key: &'static str, value: impl Into<AttributeValue>) {
+        self.attributes.push((key, value.into()));
+    }
+
+    pub fn set_error(&mut self, message: impl ToString) {
+        self.error = Some(message.to_string());
+    }
+}
+
+impl Drop for Span<'_> {
+    fn drop(&mut self) {
+        if self.tracer.endpoint.is_none() {
+            return
+        }
+
+        let span = RecordedSpan {
+            trace_id: self.tracer.trace_id.lock().unwrap().clone(),
+            span_id: std::mem::take(&mut self.span_id),
+            name: self.name,
+            kind: 1,
+            start_time_unix_nano: unix_nanos(self.start),
+            end_time_unix_nano: unix_nanos(SystemTime::now()),
+            attributes: std::mem::take(&mut self.attributes).into_iter().map(|(key, value)| Attribute { key, value }).collect(),
+            parent_span_id: self.parent_span_id.take(),
+            status: match self.error.take() {
+                Some(message) => Status { code: 2, message: Some(message) },
+                None => Status { code: 1, message: None },
+            },
+        };
+
+        self.tracer.spans.lock().unwrap().push(span);
+    }
+}
+
+fn new_trace_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+/// The body of an OTLP/HTTP export, with the spans of a single service and scope.
+#[derive(Serialize)]
+struct ExportRequest {
+    #[serde(rename = "resourceSpans")]
+    resource_spans: [ResourceSpans; 1],
+}
+
+#[derive(Serialize)]
+struct ResourceSpans {
+    resource: Resource,
+    #[serde(rename = "scopeSpans")]
+    scope_spans: [ScopeSpans; 1],
+}
+
+#[derive(Serialize)]
+struct Resource {
+    attributes: Vec<Attribute>,
+}
+
+#[derive(Serialize)]
+struct ScopeSpans {
+    scope: Scope,
+    spans: Vec<RecordedSpan>,
+}
+
+#[derive(Serialize)]
+struct Scope {
+    name: &'static str,
+    version: &'static str,
+}
+
+/// A span that ended, as it is exported.
+#[derive(Serialize)]
+struct RecordedSpan {
+    #[serde(rename = "traceId")]
+    trace_id: String,
+    #[serde(rename = "spanId")]
+    span_id: String,
+    name: &'static str,
+    kind: u8,
+    #[serde(rename = "startTimeUnixNano")]
+    start_time_unix_nano: String,
+    #[serde(rename = "endTimeUnixNano")]
+    end_time_unix_nano: String,
+    attributes: Vec<Attribute>,
+    #[serde(rename = "parentSpanId", skip_serializing_if = "Option::is_none")]
+    parent_span_id: Option<String>,
+    status: Status,
+}
+
+#[derive(Serialize)]
+struct Attribute {
+    key: &'static str,
+    value: AttributeValue,
+}
+
+#[derive(Serialize)]
+struct Status {
+    code: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+fn as_string<S: Serializer>(value: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The trace ids of the spans recorded so far, taken like an export takes them.
+    fn exported_trace_ids(tracer: &Tracer) -> Vec<String> {
+        std::mem::take(&mut *tracer.spans.lock().unwrap()).into_iter().map(|span| span.trace_id).collect()
+    }
+
+    #[test]
+    fn exports_every_run_as_a_trace_of_its_own() {
+        let tracer = Tracer::new(Some("http://collector.invalid".to_string()));
+        let mut runs = Vec::new();
+        for _ in 0..2 {
+            tracer.start_trace();
+            let run = tracer.span("generate");
+            drop(run.child("download artifact"));
+            drop(run);
+            let trace_ids = exported_trace_ids(&tracer);
+            assert_eq!(trace_ids.len(), 2);
+            assert_eq!(trace_ids[0], trace_ids[1], "the spans of a run share its trace");
+            runs.push(trace_ids[0].clone());
+        }
+        assert_ne!(runs[0], runs[1]);
+    }
+}
@@ -0,0 +1,406 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Uploading the output to S3-compatible object storage, signed with AWS Signature Version 4, or with plain HTTP PUT
+//! requests as to WebDAV.
+
+use crate::auth::{self, RepositoryCredentials};
+use crate::hashing::{Digests, HashAlgorithm};
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, Url};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+/// Cache-Control of the metadata document, which changes in place and has to be revalidated
+pub const DOCUMENT_CACHE_CONTROL: &str = "no-cache";
+/// Cache-Control of artifacts, which never change once published
+pub const ARTIFACT_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+/// User metadata holding the SHA-256 of an object's content, to tell whether it changed without downloading it
+const CONTENT_HASH_HEADER: &str = "x-amz-meta-sha256";
+/// SHA-256 of the empty payload of HEAD requests
+const EMPTY_PAYLOAD_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+/// Bytes of a file read into memory at a time while uploading it
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug)]
+pub enum UploadError {
+    Http(reqwest::Error),
+    Status { status: reqwest::StatusCode, message: String },
+    Io(std::io::Error),
+}
+
+impl Display for UploadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadError::Http(why) => why.fmt(f),
+            UploadError::Status { status, message } => write!(f, "{} {}", status, message),
+            UploadError::Io(why) => why.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for UploadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UploadError::Http(why) => Some(why),
+            UploadError::Io(why) => Some(why),
+            UploadError::Status { .. } => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for UploadError {
+    fn from(why: reqwest::Error) -> Self {
+        UploadError::Http(why)
+    }
+}
+
+#[derive(Clone)]
+struct Credentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+/// A bucket and key prefix to upload to, with the credentials to do it.
+#[derive(Clone)]
+pub struct Uploader {
+    client: reqwest::Client,
+    bucket: String,
+    prefix: String,
+    endpoint: Option<Url>,
+    region: String,
+    credentials: Credentials,
+}
+
+impl Uploader {
+    /// Parses an `s3://bucket/prefix` target and looks up credentials and the region the way the AWS CLI does:
+    /// `AWS_ACCESS_KEY_ID` and friends first, then the `AWS_PROFILE` (or default) profile of the shared
+    /// credentials and config files, unless an access key ID and secret access key are given. Without an endpoint,
+    /// the bucket is assumed to be on AWS.
+    pub fn new(client: &reqwest::Client, target: &str, endpoint: Option<&str>, keys: Option<&(String, String)>)
+        -> Result<Uploader, String> {
+        let location = target.strip_prefix("s3://").ok_or_else(|| format!("expected s3://BUCKET[/PREFIX], got `{}`", target))?;
+        let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+        if bucket.is_empty() {
+            return Err(format!("expected s3://BUCKET[/PREFIX], got `{}`", target))
+        }
+        let endpoint = endpoint.map(|endpoint| Url::parse(endpoint).map_err(|why| format!("invalid endpoint `{}`: {}", endpoint, why)))
+            .transpose()?;
+
+        let profile = std::env::var("AWS_PROFILE").unwrap_or_else(|_| "default".to_string());
+        let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+        let shared_file = |variable: &str, default: &str| {
+            let path = std::env::var_os(variable).map(PathBuf::from).unwrap_or_else(|| home.join(".aws").join(default));
+            read_profile(&path, &profile)
+        };
+        let credentials_file = shared_file("AWS_SHARED_CREDENTIALS_FILE", "credentials");
+        let config_file = shared_file("AWS_CONFIG_FILE", "config");
+        let setting = |variable: &str, key: &str| std::env::var(variable).ok()
+            .or_else(|| credentials_file.get(key).cloned())
+            .or_else(|| config_file.get(key).cloned());
+
+        let credentials = match keys {
+            Some((access_key_id, secret_access_key)) => Credentials {
+                access_key_id: access_key_id.clone(),
+                secret_access_key: secret_access_key.clone(),
+                session_token: None,
+            },
+            None => Credentials {
+                access_key_id: setting("AWS_ACCESS_KEY_ID", "aws_access_key_id")
+                    .ok_or_else(|| format!("no credentials in the environment or the `{}` profile", profile))?,
+                secret_access_key: setting("AWS_SECRET_ACCESS_KEY", "aws_secret_access_key")
+                    .ok_or_else(|| format!("no secret access key in the environment or the `{}` profile", profile))?,
+                session_token: setting("AWS_SESSION_TOKEN", "aws_session_token"),
+            },
+        };
+        let region = std::env::var("AWS_REGION").ok()
+            .or_else(|| setting("AWS_DEFAULT_REGION", "region"))
+            .unwrap_or_else(|| "us-east-1".to_string());
+
+        Ok(Uploader {
+            client: client.clone(),
+            bucket: bucket.to_string(),
+            prefix: prefix.trim_matches('/').to_string(),
+            endpoint,
+            region,
+            credentials,
+        })
+    }
+
+    /// Uploads an object under the prefix, unless one with the same content is already there.
+    /// Returns whether it was uploaded.
+    pub async fn put_if_changed(&self, name: &str, body: &[u8], cache_control: &str) -> Result<bool, UploadError> {
+        let content_hash = hex::encode(Sha256::digest(body));
+        self.put_object(name, &content_hash, body.len() as u64, body.to_vec().into(), cache_control).await
+    }
+
+    /// Uploads every file in a directory under the prefix, keeping their paths, reading each from disk as it's sent.
+    /// Files whose SHA-256 in `uploaded` is still theirs are skipped without asking the bucket; the others are
+    /// recorded there by their object URL once they're known to be up there. Returns how many were uploaded.
+    pub async fn put_directory(&self, root: &Path, cache_control: &str, uploaded: &mut BTreeMap<String, String>)
+        -> Result<usize, (String, UploadError)> {
+        let mut files = Vec::new();
+        list_files(root, &mut files).map_err(|why| (root.display().to_string(), UploadError::Io(why)))?;
+        files.sort();
+
+        let mut count = 0;
+        for file in files {
+            let name = file.strip_prefix(root).unwrap_or(&file).components()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let (digests, size) = Digests::compute_file(&file, &[HashAlgorithm::Sha256])
+                .map_err(|why| (name.clone(), UploadError::Io(why)))?;
+            let content_hash = digests.sha256.expect("SHA-256 was asked for");
+            let url = self.object_url(&name).to_string();
+            if uploaded.get(&url) == Some(&content_hash) {
+                continue
+            }
+
+            let body = tokio::fs::File::open(&file).await.map_err(|why| (name.clone(), UploadError::Io(why)))?;
+            let changed = self.put_object(&name, &content_hash, size as u64, file_body(body), cache_control).await
+                .map_err(|why| (name.clone(), why))?;
+            count += usize::from(changed);
+            uploaded.insert(url, content_hash);
+        }
+
+        Ok(count)
+    }
+
+    /// Uploads an object of `length` bytes hashing to `content_hash`, unless one with the same content is already
+    /// there. Returns whether it was uploaded.
+    async fn put_object(&self, name: &str, content_hash: &str, length: u64, body: reqwest::Body, cache_control: &str)
+        -> Result<bool, UploadError> {
+        let url = self.object_url(name);
+
+        let head = self.signed(Method::HEAD, &url, &[], EMPTY_PAYLOAD_HASH).send().await?;
+        let current = head.headers().get(CONTENT_HASH_HEADER).and_then(|v| v.to_str().ok());
+        if head.status().is_success() && current == Some(content_hash) {
+            return Ok(false)
+        }
+
+        let length = length.to_string();
+        let headers = [
+            ("cache-control", cache_control),
+            ("content-length", length.as_str()),
+            ("content-type", content_type(name)),
+            (CONTENT_HASH_HEADER, content_hash),
+        ];
+        // The content is the payload, so its hash is the one to sign
+        let response = self.signed(Method::PUT, &url, &headers, content_hash).body(body).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let message = response.text().await.unwrap_or_default();
+            return Err(UploadError::Status { status, message: message.trim().to_string() })
+        }
+
+        Ok(true)
+    }
+
+    /// A URL anyone can download an object under the prefix from until `expiry` from now, signed for it in the query
+    /// string. Signature Version 4 allows at most 7 days.
+    pub fn presign(&self, name: &str, expiry: Duration) -> Url {
+        let now = Utc::now();
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let mut url = self.object_url(name);
+
+        let mut query = vec![
+            ("X-Amz-Algorithm", "AWS4-HMAC-SHA256".to_string()),
+            ("X-Amz-Credential", format!("{}/{}", self.credentials.access_key_id, scope)),
+            ("X-Amz-Date", timestamp.clone()),
+            ("X-Amz-Expires", expiry.as_secs().to_string()),
+            ("X-Amz-SignedHeaders", "host".to_string()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            query.push(("X-Amz-Security-Token", token.clone()));
+        }
+        query.sort();
+        let canonical_query = query.iter()
+            .map(|(name, value)| format!("{}={}", name, uri_encode(value).replace('/', "%2F")))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let canonical_request = format!("GET\n{}\n{}\nhost:{}\n\nhost\nUNSIGNED-PAYLOAD", url.path(), canonical_query, host(&url));
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", timestamp, scope, hex::encode(Sha256::digest(&canonical_request)));
+        let signature = hex::encode(hmac_sha256(&self.signing_key(&date), string_to_sign.as_bytes()));
+
+        url.set_query(Some(&format!("{}&X-Amz-Signature={}", canonical_query, signature)));
+        url
+    }
+
+    fn object_url(&self, name: &str) -> Url {
+        let key = if self.prefix.is_empty() { name.to_string() } else { format!("{}/{}", self.prefix, name) };
+        let url = match &self.endpoint {
+            // Custom endpoints (MinIO, R2, ...) are addressed path-style, AWS virtual-hosted style
+            Some(endpoint) => format!("{}/{}/{}", endpoint.as_str().trim_end_matches('/'), self.bucket, uri_encode(&key)),
+            None => format!("https://{}.s3.{}.amazonaws.com/{}", self.bucket, self.region, uri_encode(&key)),
+        };
+        Url::parse(&url).expect("object URLs are always valid")
+    }
+
+    /// A request carrying the given headers, signed with Signature Version 4 for a payload hashing to `payload_hash`.
+    fn signed(&self, method: Method, url: &Url, headers: &[(&str, &str)], payload_hash: &str) -> reqwest::RequestBuilder {
+        let now = Utc::now();
+        let timestamp = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let host = host(url);
+
+        let mut signed_headers: Vec<(String, String)> = headers.iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value.trim().to_string()))
+            .collect();
+        signed_headers.push(("host".to_string(), host));
+        signed_headers.push(("x-amz-content-sha256".to_string(), payload_hash.to_string()));
+        signed_headers.push(("x-amz-date".to_string(), timestamp.clone()));
+        if let Some(token) = &self.credentials.session_token {
+            signed_headers.push(("x-amz-security-token".to_string(), token.clone()));
+        }
+        signed_headers.sort();
+        let header_names = signed_headers.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>().join(";");
+
+        let canonical_request = format!("{}\n{}\n{}\n{}\n{}\n{}",
+            method.as_str(),
+            url.path(),
+            url.query().unwrap_or_default(),
+            signed_headers.iter().map(|(name, value)| format!("{}:{}\n", name, value)).collect::<String>(),
+            header_names,
+            payload_hash);
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", timestamp, scope, hex::encode(Sha256::digest(&canonical_request)));
+
+        let signature = hex::encode(hmac_sha256(&self.signing_key(&date), string_to_sign.as_bytes()));
+
+        let mut request = self.client.request(method, url.clone())
+            .header("authorization", format!("AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.credentials.access_key_id, scope, header_names, signature));
+        for (name, value) in signed_headers.iter().filter(|(name, _)| name != "host") {
+            request = request.header(name, value);
+        }
+        request
+    }
+
+    /// The key derived from the secret access key to sign requests of `date` with.
+    fn signing_key(&self, date: &str) -> [u8; 32] {
+        [self.region.as_str(), "s3", "aws4_request"].into_iter()
+            .fold(hmac_sha256(format!("AWS4{}", self.credentials.secret_access_key).as_bytes(), date.as_bytes()),
+                |key, part| hmac_sha256(&key, part.as_bytes()))
+    }
+}
+
+/// The Host header of a request to `url`.
+fn host(url: &Url) -> String {
+    match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    }
+}
+
+/// Uploads a file with a PUT request, with the credentials of the repository `url` is in.
+pub async fn put(client: &reqwest::Client, credentials: &[RepositoryCredentials], url: &str, body: &[u8], cache_control: &str)
+    -> Result<(), UploadError> {
+    let request = client.put(url)
+        .header(reqwest::header::CONTENT_TYPE, content_type(url))
+        .header(reqwest::header::CACHE_CONTROL, cache_control)
+        .body(body.to_vec());
+    let response = auth::authorize(credentials, request, url).send().await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let message = response.text().await.unwrap_or_default();
+        return Err(UploadError::Status { status, message: message.trim().to_string() })
+    }
+
+    Ok(())
+}
+
+/// A request body streaming a file a chunk at a time.
+fn file_body(file: tokio::fs::File) -> reqwest::Body {
+    let chunks = futures::stream::try_unfold(file, |mut file| async move {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let read = file.read(&mut chunk).await?;
+        chunk.truncate(read);
+        Ok::<_, std::io::Error>((read > 0).then(|| (bytes::Bytes::from(chunk), file)))
+    });
+    reqwest::Body::wrap_stream(chunks)
+}
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+/// Percent-encodes an object key the way Signature Version 4 expects, leaving slashes alone.
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Content-Type to publish a file with, by its extension.
+pub fn content_type(name: &str) -> &'static str {
+    match name.rsplit_once('.').map(|(_, extension)| extension) {
+        Some("json") => "application/json",
+        Some("jar") => "application/java-archive",
+        Some("gz") => "application/gzip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// The settings of a profile in an AWS shared credentials or config file, empty if there is none.
+fn read_profile(path: &Path, profile: &str) -> HashMap<String, String> {
+    let Ok(contents) = std::fs::read_to_string(path) else {
+        return HashMap::new()
+    };
+    // The config file prefixes every profile but the default one with `profile `
+    let sections = [profile.to_string(), format!("profile {}", profile)];
+    let mut settings = HashMap::new();
+    let mut in_profile = false;
+    for line in contents.lines().map(str::trim) {
+        if let Some(section) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            in_profile = sections.iter().any(|s| s == section.trim());
+        } else if in_profile && let Some((key, value)) = line.split_once('=') {
+            settings.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    settings
+}
+
+fn list_files(directory: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else if path.extension().is_none_or(|extension| extension != "tmp") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
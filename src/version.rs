@@ -0,0 +1,205 @@
+/*
+ *  EPLMetadataGenerator - Metadata generator for ElyPrismLauncher
+ *  Copyright (C) 2025 Octol1ttle <l1ttleofficial@outlook.com>
+ *
+ *  This program is free software: you can redistribute it and/or modify
+ *  it under the terms of the GNU General Public License as published by
+ *  the Free Software Foundation, either version 3 of the License, or
+ *  (at your option) any later version.
+ *
+ *  This program is distributed in the hope that it will be useful,
+ *  but WITHOUT ANY WARRANTY; without even the implied warranty of
+ *  MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ *  GNU General Public License for more details.
+ *
+ *  You should have received a copy of the GNU General Public License
+ *  along with this program.  If not, see <https://www.gnu.org/licenses/>.
+ */
+
+//! Parsing and ordering artifact versions.
+
+use std::cmp::Ordering;
+
+/// A version as `BASE[-QUALIFIER]`, where the base is dotted numbers (`5.17.31`) and the qualifier
+/// anything after the first dash (`ely.2`, `rc1`).
+///
+/// Bases are compared component by component, missing components counting as 0. Qualifiers are
+/// compared token by token, tokens being runs of digits or letters: pre-releases (`alpha`, `beta`,
+/// `m`, `rc`, `snapshot`) come before the plain release, other words such as `ely` after it, and
+/// numbers after words, so `5.17.31-rc1 < 5.17.31 < 5.17.31-ely.2 < 5.17.31-ely.10`.
+#[derive(Clone, Debug)]
+pub struct Version {
+    base: Vec<u64>,
+    qualifier: Vec<Token>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Number(u64),
+    Word(String),
+}
+
+impl Version {
+    /// Parses a version, `None` if its base isn't dotted numbers.
+    pub fn parse(version: &str) -> Option<Version> {
+        let (base, qualifier) = version.split_once('-').unwrap_or((version, ""));
+        let base = base.split('.')
+            .map(|component| if component.bytes().all(|b| b.is_ascii_digit()) { component.parse().ok() } else { None })
+            .collect::<Option<Vec<u64>>>()?;
+
+        let mut qualifier_tokens = Vec::new();
+        for part in qualifier.split(['.', '-', '_']).filter(|part| !part.is_empty()) {
+            let mut rest = part;
+            while let Some(first) = rest.chars().next() {
+                let digits = first.is_ascii_digit();
+                let end = rest.find(|c: char| c.is_ascii_digit() != digits).unwrap_or(rest.len());
+                qualifier_tokens.push(if digits {
+                    Token::Number(rest[..end].parse().ok()?)
+                } else {
+                    Token::word(&rest[..end])
+                });
+                rest = &rest[end..];
+            }
+        }
+
+        Some(Version { base, qualifier: qualifier_tokens })
+    }
+}
+
+impl Token {
+    /// A word token, with the abbreviations of pre-release words spelled out.
+    fn word(word: &str) -> Token {
+        let word = word.to_ascii_lowercase();
+        Token::Word(match word.as_str() {
+            "a" => "alpha".to_string(),
+            "b" => "beta".to_string(),
+            "m" => "milestone".to_string(),
+            "cr" => "rc".to_string(),
+            _ => word,
+        })
+    }
+
+    /// Where a token sorts relative to the others, a missing token ranking as the release.
+    fn rank(token: Option<&Token>) -> u8 {
+        match token {
+            Some(Token::Word(word)) => match word.as_str() {
+                "alpha" => 0,
+                "beta" => 1,
+                "milestone" => 2,
+                "rc" => 3,
+                "snapshot" => 4,
+                _ => 6,
+            },
+            None => 5,
+            Some(Token::Number(_)) => 7,
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Version) -> Ordering {
+        let base = (0..self.base.len().max(other.base.len()))
+            .map(|i| self.base.get(i).unwrap_or(&0).cmp(other.base.get(i).unwrap_or(&0)))
+            .find(|ordering| ordering.is_ne());
+        if let Some(ordering) = base {
+            return ordering
+        }
+
+        for i in 0..self.qualifier.len().max(other.qualifier.len()) {
+            let (a, b) = (self.qualifier.get(i), other.qualifier.get(i));
+            let ordering = Token::rank(a).cmp(&Token::rank(b)).then_with(|| match (a, b) {
+                (Some(Token::Number(a)), Some(Token::Number(b))) => a.cmp(b),
+                (Some(Token::Word(a)), Some(Token::Word(b))) => a.cmp(b),
+                _ => Ordering::Equal,
+            });
+            if ordering.is_ne() {
+                return ordering
+            }
+        }
+
+        Ordering::Equal
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Version) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Version) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Version {}
+
+/// Orders versions as [`Version`] does, versions that can't be parsed before all others.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    Version::parse(a).cmp(&Version::parse(b))
+}
+
+/// Orders base versions newest first, as the entries of a library are listed. Versions [`compare_versions`] ranks
+/// the same, like `1.0` and `1.0.0` or any two it can't parse, are ordered by their text, so the order depends on
+/// nothing but the versions.
+pub fn newest_first(a: &str, b: &str) -> Ordering {
+    compare_versions(b, a).then_with(|| b.cmp(a))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_ascending(versions: &[&str]) {
+        for pair in versions.windows(2) {
+            assert_eq!(compare_versions(pair[0], pair[1]), Ordering::Less, "{} < {}", pair[0], pair[1]);
+            assert_eq!(compare_versions(pair[1], pair[0]), Ordering::Greater, "{} > {}", pair[1], pair[0]);
+        }
+    }
+
+    #[test]
+    fn orders_base_versions_numerically() {
+        assert_ascending(&["1", "1.5", "1.5.21", "1.10", "3.11.49", "5.17.31", "5.17.31.1"]);
+    }
+
+    #[test]
+    fn treats_missing_components_as_zero() {
+        assert_eq!(compare_versions("1.0", "1"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.0", "1.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn orders_ely_builds_by_patch_number() {
+        assert_ascending(&["5.17.31", "5.17.31-ely.1", "5.17.31-ely.2", "5.17.31-ely.10"]);
+    }
+
+    #[test]
+    fn orders_pre_releases_before_the_release() {
+        assert_ascending(&["1.5.25-alpha", "1.5.25-beta2", "1.5.25-rc1", "1.5.25-rc2", "1.5.25-SNAPSHOT", "1.5.25", "1.5.25-ely.1"]);
+    }
+
+    #[test]
+    fn compares_qualifiers_case_insensitively() {
+        assert_eq!(compare_versions("1.0-RC1", "1.0-rc.1"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0-cr1", "1.0-rc1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn base_version_takes_precedence_over_qualifier() {
+        assert_ascending(&["3.11.49-ely.9", "5.17.31-rc1", "5.17.31-ely.1"]);
+    }
+
+    #[test]
+    fn rejects_non_numeric_bases() {
+        for version in ["", "abc", "1..2", "1.x", "1.5.25rc1", "-ely.1"] {
+            assert!(Version::parse(version).is_none(), "{}", version);
+        }
+    }
+
+    #[test]
+    fn sorts_unparseable_versions_first() {
+        assert_eq!(compare_versions("abc", "0"), Ordering::Less);
+        assert_eq!(compare_versions("abc", "def"), Ordering::Equal);
+    }
+}